@@ -73,10 +73,7 @@ fn run_animation() {
             .insert_bundle(InstanceBundle::new(Instance2D {
                 shape: line,
                 scale: Vec2::splat(500.0),
-                outline: Some(Outline {
-                    scale: 55.0,
-                    color: Vec4::ONE,
-                }),
+                outline: Some(Outline::new(25.0, Vec4::ONE)),
                 ..Default::default()
             }))
             .insert_bundle(AnimationBundle::from_animation(Animation::new(
@@ -0,0 +1,115 @@
+use bevy_ecs::{
+    prelude::Component,
+    system::{Commands, Query, Res},
+};
+use glam::{Vec2, Vec4};
+use libprim::{
+    instance::{Gradient, Instance2D, InstanceBundle},
+    material::BlendMode,
+    particle_system::{
+        components::{EmitterPosition, ParticleSystem, ParticleSystemBundle, Playing, TimeScale},
+        systems::system_set,
+        values::JitteredValue,
+    },
+    shape_registry::ShapeRegistry,
+    state::CoreStages,
+    time::Time,
+    window::PrimWindowOptions,
+};
+
+/// Circles which orbit the center of their column.
+#[derive(Component)]
+struct Orbit {
+    center: Vec2,
+    phase: f32,
+}
+
+fn orbit(time: Res<Time>, mut instances: Query<(&mut Instance2D, &Orbit)>) {
+    for (mut instance, orbit) in &mut instances {
+        let angle = time.total_seconds() * 0.5 + orbit.phase;
+        instance.position = orbit.center + Vec2::new(angle.cos(), angle.sin()) * 18.0;
+    }
+}
+
+fn spawn_world(mut commands: Commands, shape_registry: Res<ShapeRegistry>) {
+    let square = shape_registry.get_id("Square").unwrap();
+    let circle = shape_registry.get_id("Circle").unwrap();
+
+    // A backdrop running from dark to light, so each mode can be seen over both.
+    commands
+        .spawn()
+        .insert_bundle(InstanceBundle::new(Instance2D {
+            position: Vec2::new(0.0, 40.0),
+            scale: Vec2::new(400.0, 130.0),
+            shape: square,
+            ..Default::default()
+        }))
+        .insert(Gradient::vertical(
+            Vec4::new(0.85, 0.85, 0.8, 1.0),
+            Vec4::new(0.1, 0.1, 0.15, 1.0),
+        ));
+
+    // Red, green and blue circles drawn with each blend mode, from left to right.
+    let colors = [
+        Vec4::new(1.0, 0.2, 0.2, 0.9),
+        Vec4::new(0.2, 1.0, 0.2, 0.9),
+        Vec4::new(0.2, 0.2, 1.0, 0.9),
+    ];
+    for (column, blend_mode) in BlendMode::ALL.into_iter().enumerate() {
+        let center = Vec2::new(column as f32 * 95.0 - 142.5, 40.0);
+        for (i, color) in colors.into_iter().enumerate() {
+            commands
+                .spawn()
+                .insert_bundle(InstanceBundle::new(Instance2D {
+                    position: center,
+                    scale: Vec2::splat(50.0),
+                    color,
+                    shape: circle,
+                    ..Default::default()
+                }))
+                .insert(blend_mode)
+                .insert(Orbit {
+                    center,
+                    phase: i as f32 * std::f32::consts::TAU / 3.0,
+                });
+        }
+    }
+
+    // The same sparks with alpha blending on the left and additive blending on the right.
+    for (x, blend_mode) in [(-100.0, BlendMode::Alpha), (100.0, BlendMode::Additive)] {
+        commands
+            .spawn()
+            .insert_bundle(ParticleSystemBundle {
+                particle_system: ParticleSystem {
+                    max_particles: 400,
+                    shape_id: circle,
+                    spawn_rate_per_second: 200.0.into(),
+                    emitter_shape: 0.6,
+                    emitter_angle: std::f32::consts::FRAC_PI_2,
+                    initial_velocity: JitteredValue::jittered(60.0, -20.0..20.0),
+                    lifetime: JitteredValue::jittered(1.0, -0.3..0.3),
+                    color: Vec4::new(1.0, 0.45, 0.1, 0.5).into(),
+                    scale: 10.0.into(),
+                    blend_mode,
+                    ..Default::default()
+                },
+                position: EmitterPosition(Vec2::new(x, -120.0)),
+                ..Default::default()
+            })
+            .insert(Playing);
+    }
+}
+
+fn run_blend_modes() {
+    libprim::run(PrimWindowOptions::default(), |state| {
+        state.add_setup_system(spawn_world);
+        state.borrow_world().init_resource::<Option<TimeScale>>();
+        let schedule = state.borrow_schedule();
+        schedule.add_system_to_stage(CoreStages::Update, orbit);
+        schedule.add_system_set_to_stage(CoreStages::Update, system_set());
+    });
+}
+
+fn main() {
+    run_blend_modes();
+}
@@ -0,0 +1,104 @@
+use bevy_ecs::{
+    prelude::Component,
+    system::{Commands, Query, Res},
+};
+use glam::{Vec2, Vec4};
+use libprim::{
+    initialization::InitializeCommand,
+    instance::{Gradient, Instance2D, InstanceBundle, Outline},
+    shape::InitializeShape,
+    shape_registry::ShapeRegistry,
+    state::CoreStages,
+    time::Time,
+    window::PrimWindowOptions,
+};
+
+#[derive(Component)]
+struct Bob(f32);
+
+fn bob(time: Res<Time>, mut instances: Query<(&mut Instance2D, &Bob)>) {
+    for (mut instance, bob) in &mut instances {
+        instance.rotation = (time.total_seconds() + bob.0).sin() * 0.2;
+    }
+}
+
+fn spawn_world(mut commands: Commands, shape_registry: Res<ShapeRegistry>) {
+    let ship = shape_registry.get_id("Ship").unwrap();
+    let square = shape_registry.get_id("Square").unwrap();
+
+    // The vertex colors of the ship are tinted by each instance's color.
+    for (i, color) in [Vec4::ONE, Vec4::new(1.0, 0.5, 0.5, 1.0)]
+        .into_iter()
+        .enumerate()
+    {
+        commands
+            .spawn()
+            .insert_bundle(InstanceBundle::new(Instance2D {
+                position: Vec2::new(i as f32 * 100.0 - 150.0, 40.0),
+                scale: Vec2::splat(80.0),
+                color,
+                shape: ship,
+                ..Default::default()
+            }))
+            .insert(Bob(i as f32));
+    }
+
+    // A panel shaded from top to bottom.
+    commands
+        .spawn()
+        .insert_bundle(InstanceBundle::new(Instance2D {
+            position: Vec2::new(0.0, -100.0),
+            scale: Vec2::new(300.0, 50.0),
+            shape: square,
+            outline: Some(Outline::new(2.0, Vec4::new(0.8, 0.8, 0.9, 1.0))),
+            ..Default::default()
+        }))
+        .insert(Gradient::vertical(
+            Vec4::new(0.3, 0.35, 0.5, 1.0),
+            Vec4::new(0.1, 0.1, 0.2, 1.0),
+        ));
+
+    // A glowing orb.
+    commands
+        .spawn()
+        .insert_bundle(InstanceBundle::new(Instance2D {
+            position: Vec2::new(110.0, 40.0),
+            scale: Vec2::splat(100.0),
+            shape: square,
+            ..Default::default()
+        }))
+        .insert(Gradient::radial(
+            Vec4::new(1.0, 0.9, 0.5, 1.0),
+            Vec4::new(1.0, 0.3, 0.0, 0.0),
+        ));
+}
+
+fn run_gradients() {
+    libprim::run(PrimWindowOptions::default(), |state| {
+        state.add_initializer(InitializeCommand::InitializeShape(
+            InitializeShape::new(
+                "Ship".to_string(),
+                Vec::from([
+                    Vec2::new(0.0, 0.5),
+                    Vec2::new(-0.5, -0.5),
+                    Vec2::new(0.0, -0.25),
+                    Vec2::new(0.5, -0.5),
+                ]),
+                Vec::from([0, 1, 2, 0, 2, 3]),
+            )
+            .with_colors(Vec::from([
+                Vec4::new(0.9, 0.9, 1.0, 1.0),
+                Vec4::new(0.2, 0.3, 0.6, 1.0),
+                Vec4::new(0.5, 0.6, 0.9, 1.0),
+                Vec4::new(0.2, 0.3, 0.6, 1.0),
+            ])),
+        ));
+        state.add_setup_system(spawn_world);
+        let schedule = state.borrow_schedule();
+        schedule.add_system_to_stage(CoreStages::Update, bob);
+    });
+}
+
+fn main() {
+    run_gradients();
+}
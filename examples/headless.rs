@@ -0,0 +1,48 @@
+use bevy_ecs::system::Commands;
+use glam::{Vec2, Vec3, Vec4};
+use libprim::{
+    headless::HeadlessOptions,
+    instance::{Instance2D, InstanceBundle},
+    run_headless,
+};
+
+fn spawn_shapes(mut commands: Commands) {
+    for i in 0..3 {
+        commands
+            .spawn()
+            .insert_bundle(InstanceBundle::new(Instance2D {
+                position: Vec2::new((i as f32 - 1.0) * 100.0, 0.0),
+                scale: Vec2::splat(80.0),
+                color: Vec4::new(0.2 * i as f32, 0.4, 1.0 - 0.3 * i as f32, 1.0),
+                shape: i,
+                ..Default::default()
+            }));
+    }
+}
+
+fn main() {
+    let options = HeadlessOptions::default()
+        .with_size((640, 480))
+        .with_clear_color(Vec3::new(0.05, 0.05, 0.05));
+
+    let mut state = match run_headless(options, |state| state.add_setup_system(spawn_shapes)) {
+        Some(state) => state,
+        None => {
+            eprintln!("No adapter available for headless rendering");
+            return;
+        }
+    };
+
+    if let Err(err) = state.render_frame() {
+        eprintln!("Error rendering frame: {err:?}");
+        return;
+    }
+
+    match state.capture_frame() {
+        Some(frame) => match frame.save_png("headless.png") {
+            Ok(_) => println!("Saved headless.png"),
+            Err(err) => eprintln!("Error saving frame: {err}"),
+        },
+        None => eprintln!("Could not read the frame back from the GPU"),
+    }
+}
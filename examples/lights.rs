@@ -0,0 +1,99 @@
+use bevy_ecs::{
+    prelude::Component,
+    query::With,
+    system::{Query, Res},
+};
+use glam::{Vec2, Vec3, Vec4};
+use libprim::{
+    instance::{Instance2D, InstanceBundle},
+    light::{AmbientLight, PointLight, ShadowCaster, Shadows, SpotLight, Unlit},
+    shape_registry::ShapeRegistry,
+    state::CoreStages,
+    time::Time,
+    window::PrimWindowOptions,
+};
+
+#[derive(Component)]
+struct Orbit;
+
+fn orbit_light(time: Res<Time>, mut lights: Query<&mut PointLight, With<Orbit>>) {
+    let t = time.total_seconds();
+    for mut light in &mut lights {
+        light.position = Vec2::new(t.cos(), t.sin()) * 150.0;
+    }
+}
+
+fn sweep_spotlight(time: Res<Time>, mut lights: Query<&mut SpotLight>) {
+    for mut light in &mut lights {
+        light.direction = std::f32::consts::FRAC_PI_2 + time.total_seconds().sin() * 0.75;
+    }
+}
+
+fn run_lights() {
+    libprim::run(PrimWindowOptions::default(), |state| {
+        let schedule = state.borrow_schedule();
+        schedule.add_system_to_stage(CoreStages::Update, orbit_light);
+        schedule.add_system_to_stage(CoreStages::Update, sweep_spotlight);
+        let world = state.borrow_world();
+
+        let shape_registry = world.get_resource::<ShapeRegistry>().unwrap();
+        let square = shape_registry.get_id("Square").unwrap();
+        let triangle = shape_registry.get_id("Triangle").unwrap();
+
+        // Darken the scene so the lights stand out.
+        world.insert_resource(AmbientLight::new(Vec3::new(0.6, 0.7, 1.0), 0.1));
+
+        for x in -6..=6 {
+            for y in -4..=4 {
+                let mut entity = world.spawn();
+                entity.insert_bundle(InstanceBundle::new(Instance2D {
+                    position: Vec2::new(x as f32 * 40.0, y as f32 * 40.0),
+                    scale: Vec2::splat(if (x + y) % 2 == 0 { 15.0 } else { 30.0 }),
+                    color: Vec4::new(0.8, 0.8, 0.8, 1.0),
+                    shape: if (x + y) % 2 == 0 { square } else { triangle },
+                    ..Default::default()
+                }));
+                // The smaller squares block light, casting shadows over their neighbours.
+                if (x + y) % 2 == 0 {
+                    entity.insert(ShadowCaster);
+                }
+            }
+        }
+
+        // Unlit instances keep their exact color no matter the lighting.
+        world
+            .spawn()
+            .insert_bundle(InstanceBundle::new(Instance2D {
+                position: Vec2::new(0.0, 200.0),
+                scale: Vec2::new(200.0, 20.0),
+                color: Vec4::new(1.0, 0.8, 0.2, 1.0),
+                shape: square,
+                ..Default::default()
+            }))
+            .insert(Unlit);
+
+        world
+            .spawn()
+            .insert(PointLight {
+                color: Vec3::new(1.0, 0.5, 0.2),
+                intensity: 1.5,
+                radius: 150.0,
+                ..Default::default()
+            })
+            .insert(Orbit);
+
+        world.spawn().insert(SpotLight {
+            position: Vec2::new(0.0, -200.0),
+            color: Vec3::new(0.3, 0.6, 1.0),
+            intensity: 1.5,
+            radius: 400.0,
+            angle: 0.6,
+            shadows: Shadows::Soft(8.0),
+            ..Default::default()
+        });
+    });
+}
+
+fn main() {
+    run_lights();
+}
@@ -0,0 +1,81 @@
+use bevy_ecs::{
+    prelude::Component,
+    query::With,
+    system::{Commands, Query, Res},
+};
+use glam::{Vec2, Vec4};
+use libprim::{
+    polyline::Polyline,
+    state::CoreStages,
+    stroke::{LineCap, LineJoin, StrokeUnits},
+    time::Time,
+    window::PrimWindowOptions,
+};
+
+/// A polyline which is redrawn as a moving sine wave.
+#[derive(Component)]
+struct Wave;
+
+fn spawn_world(mut commands: Commands) {
+    commands.spawn().insert(Wave).insert(
+        Polyline::new(Vec::new(), 3.0, Vec4::new(0.3, 0.9, 0.5, 1.0))
+            .with_join(LineJoin::Round)
+            .with_cap(LineCap::Round),
+    );
+
+    // A closed polyline draws the wireframe of a star.
+    let star = (0..10)
+        .map(|i| {
+            let angle = i as f32 * std::f32::consts::PI / 5.0;
+            let radius = if i % 2 == 0 { 60.0 } else { 25.0 };
+            Vec2::new(angle.sin(), angle.cos()) * radius + Vec2::new(-120.0, -60.0)
+        })
+        .collect();
+    commands.spawn().insert(
+        Polyline::new(star, 4.0, Vec4::new(1.0, 0.8, 0.2, 1.0))
+            .with_join(LineJoin::Miter)
+            .with_closed(true),
+    );
+
+    // A thin border which stays one pixel wide however the camera is scaled.
+    commands.spawn().insert(
+        Polyline::new(
+            Vec::from([
+                Vec2::new(20.0, -120.0),
+                Vec2::new(220.0, -120.0),
+                Vec2::new(220.0, 0.0),
+                Vec2::new(20.0, 0.0),
+            ]),
+            1.0,
+            Vec4::ONE,
+        )
+        .with_units(StrokeUnits::Screen)
+        .with_closed(true),
+    );
+}
+
+fn update_wave(time: Res<Time>, mut waves: Query<&mut Polyline, With<Wave>>) {
+    for mut wave in &mut waves {
+        wave.points = (0..=64)
+            .map(|i| {
+                let x = i as f32 * 7.0 - 224.0;
+                Vec2::new(
+                    x,
+                    (x * 0.03 + time.total_seconds() * 2.0).sin() * 40.0 + 90.0,
+                )
+            })
+            .collect();
+    }
+}
+
+fn run_lines() {
+    libprim::run(PrimWindowOptions::default(), |state| {
+        state.add_setup_system(spawn_world);
+        let schedule = state.borrow_schedule();
+        schedule.add_system_to_stage(CoreStages::Update, update_wave);
+    });
+}
+
+fn main() {
+    run_lines();
+}
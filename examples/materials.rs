@@ -0,0 +1,170 @@
+use bevy_ecs::{
+    prelude::Component,
+    system::{Commands, Query, Res, ResMut},
+};
+use glam::{Vec2, Vec4};
+use libprim::{
+    initialization::InitializeCommand,
+    instance::{Instance2D, InstanceBundle, InstanceData},
+    material::{InitializeMaterial, Material, MaterialRegistry},
+    shape_registry::ShapeRegistry,
+    state::CoreStages,
+    time::Time,
+    window::PrimWindowOptions,
+};
+
+/// Fills shapes with diagonal stripes of their color.
+const STRIPES: &str = "
+fn material(in: VertexOutput) -> vec4<f32> {
+    let stripe = step(0.5, fract((in.local_pos.x + in.local_pos.y) * 4.0));
+    return vec4<f32>(in.color.rgb * (0.4 + 0.6 * stripe), in.color.a);
+}
+";
+
+/// Cuts shapes down to a circle with a ring pulsing outwards from the middle.
+const PULSE: &str = "
+struct Pulse {
+    time: f32,
+};
+@group(2) @binding(0)
+var<uniform> pulse: Pulse;
+
+fn material(in: VertexOutput) -> vec4<f32> {
+    let dist = length(in.local_pos) * 2.0;
+    let ring = 1.0 - smoothstep(0.0, 0.1, abs(dist - fract(pulse.time * 0.5)));
+    let alpha = in.color.a * (1.0 - smoothstep(0.97, 1.0, dist));
+    return vec4<f32>(mix(in.color.rgb * 0.3, vec3<f32>(1.0), ring), alpha);
+}
+";
+
+/// Eats away at shapes in blocks, dissolving more of them as the instance's `data.x` rises from 0
+/// to 1.
+const DISSOLVE: &str = "
+fn material(in: VertexOutput) -> vec4<f32> {
+    let block = floor(in.local_pos * 12.0);
+    let noise = fract(sin(dot(block, vec2<f32>(12.9898, 78.233))) * 43758.5453);
+    // Dissolved blocks are fully transparent, and blocks about to dissolve glow.
+    let alpha = in.color.a * step(in.data.x, noise);
+    let glow = 1.0 - smoothstep(0.0, 0.15, noise - in.data.x);
+    return vec4<f32>(mix(in.color.rgb, vec3<f32>(1.0, 0.8, 0.3), glow), alpha);
+}
+";
+
+/// The uniforms of the `Pulse` material.
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct PulseUniforms {
+    time: f32,
+    _padding: [f32; 3],
+}
+
+#[derive(Component)]
+struct Spin(f32);
+
+fn spin(time: Res<Time>, mut instances: Query<(&mut Instance2D, &Spin)>) {
+    for (mut instance, spin) in &mut instances {
+        instance.rotation += spin.0 * time.delta_seconds();
+    }
+}
+
+/// Dissolves an instance in and out, offset by the given phase.
+#[derive(Component)]
+struct Dissolve(f32);
+
+fn dissolve(time: Res<Time>, mut instances: Query<(&mut InstanceData, &Dissolve)>) {
+    for (mut data, dissolve) in &mut instances {
+        data.0.x = (time.total_seconds() + dissolve.0).sin() * 0.5 + 0.5;
+    }
+}
+
+fn update_pulse(time: Res<Time>, mut material_registry: ResMut<MaterialRegistry>) {
+    if let Some(pulse) = material_registry.get_id("Pulse") {
+        material_registry.set_uniforms(
+            pulse,
+            &PulseUniforms {
+                time: time.total_seconds(),
+                _padding: [0.0; 3],
+            },
+        );
+    }
+}
+
+fn spawn_world(
+    mut commands: Commands,
+    shape_registry: Res<ShapeRegistry>,
+    material_registry: Res<MaterialRegistry>,
+) {
+    let square = shape_registry.get_id("Square").unwrap();
+    let triangle = shape_registry.get_id("Triangle").unwrap();
+    let stripes = Material(material_registry.get_id("Stripes").unwrap());
+    let pulse = Material(material_registry.get_id("Pulse").unwrap());
+    let dissolve = Material(material_registry.get_id("Dissolve").unwrap());
+
+    let colors = [
+        Vec4::new(1.0, 0.3, 0.3, 1.0),
+        Vec4::new(0.3, 1.0, 0.5, 1.0),
+        Vec4::new(0.3, 0.6, 1.0, 1.0),
+    ];
+    for (i, color) in colors.into_iter().enumerate() {
+        let x = i as f32 * 100.0 - 100.0;
+        // The same shapes drawn with each material, top to bottom.
+        for (y, material) in [(105.0, Material::DEFAULT), (35.0, stripes), (-35.0, pulse)] {
+            commands
+                .spawn()
+                .insert_bundle(InstanceBundle::new(Instance2D {
+                    position: Vec2::new(x, y),
+                    scale: Vec2::splat(60.0),
+                    color,
+                    shape: if material == pulse || i % 2 == 1 {
+                        square
+                    } else {
+                        triangle
+                    },
+                    ..Default::default()
+                }))
+                .insert(material)
+                .insert(Spin(if material == pulse { 0.0 } else { 0.5 }));
+        }
+        // Each dissolving shape carries its own progress in its instance data.
+        commands
+            .spawn()
+            .insert_bundle(InstanceBundle::new(Instance2D {
+                position: Vec2::new(x, -105.0),
+                scale: Vec2::splat(60.0),
+                color,
+                shape: square,
+                ..Default::default()
+            }))
+            .insert(dissolve)
+            .insert(InstanceData::default())
+            .insert(Dissolve(i as f32));
+    }
+}
+
+fn run_materials() {
+    libprim::run(PrimWindowOptions::default(), |state| {
+        state.add_initializer(InitializeCommand::InitializeMaterial(
+            InitializeMaterial::new("Stripes".to_string(), STRIPES.to_string()),
+        ));
+        state.add_initializer(InitializeCommand::InitializeMaterial(
+            InitializeMaterial::new("Pulse".to_string(), PULSE.to_string()).with_uniforms(
+                &PulseUniforms {
+                    time: 0.0,
+                    _padding: [0.0; 3],
+                },
+            ),
+        ));
+        state.add_initializer(InitializeCommand::InitializeMaterial(
+            InitializeMaterial::new("Dissolve".to_string(), DISSOLVE.to_string()),
+        ));
+        state.add_setup_system(spawn_world);
+        let schedule = state.borrow_schedule();
+        schedule.add_system_to_stage(CoreStages::Update, spin);
+        schedule.add_system_to_stage(CoreStages::Update, update_pulse);
+        schedule.add_system_to_stage(CoreStages::Update, dissolve);
+    });
+}
+
+fn main() {
+    run_materials();
+}
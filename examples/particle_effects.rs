@@ -0,0 +1,70 @@
+use bevy_ecs::system::{Commands, Res};
+use glam::{Vec2, Vec4};
+use libprim::{
+    initialization::InitializeCommand,
+    instance::{Instance2D, InstanceBundle},
+    particle_system::{
+        components::{EmitterPosition, Playing, TimeScale},
+        effects::{InitializeParticleEffectFile, ParticleEffects},
+        systems::system_set,
+    },
+    shape_file::InitializeShapeFile,
+    shape_registry::ShapeRegistry,
+    state::CoreStages,
+    window::PrimWindowOptions,
+};
+
+fn spawn_world(
+    mut commands: Commands,
+    shape_registry: Res<ShapeRegistry>,
+    effects: Res<ParticleEffects>,
+) {
+    // Assets which failed to load are logged and left out.
+    if let Some(shape) = shape_registry.get_id("Fighter") {
+        commands
+            .spawn()
+            .insert_bundle(InstanceBundle::new(Instance2D {
+                position: Vec2::new(-100.0, 0.0),
+                scale: Vec2::splat(80.0),
+                color: Vec4::ONE,
+                shape,
+                ..Default::default()
+            }));
+    }
+    for (name, position) in [
+        ("Exhaust", Vec2::new(-100.0, -30.0)),
+        ("Sparkles", Vec2::new(100.0, 0.0)),
+    ] {
+        if let Some(mut effect) = effects.bundle(name) {
+            effect.particle_system.position = EmitterPosition(position);
+            commands.spawn().insert_bundle(effect).insert(Playing);
+        }
+    }
+}
+
+fn run_particle_effects() {
+    libprim::run(PrimWindowOptions::default(), |state| {
+        // Shapes are loaded first, so the effects can find the shapes they use.
+        state.add_initializer(InitializeCommand::InitializeShapeFile(
+            InitializeShapeFile::new(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/assets/shapes/ships.ron"
+            )),
+        ));
+        state.add_initializer(InitializeCommand::InitializeParticleEffectFile(
+            InitializeParticleEffectFile::new(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/assets/particles/effects.ron"
+            )),
+        ));
+        state.borrow_world().init_resource::<Option<TimeScale>>();
+        state.add_setup_system(spawn_world);
+        state
+            .borrow_schedule()
+            .add_system_set_to_stage(CoreStages::Update, system_set());
+    });
+}
+
+fn main() {
+    run_particle_effects();
+}
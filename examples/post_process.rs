@@ -0,0 +1,143 @@
+use bevy_ecs::{
+    prelude::Component,
+    system::{Query, Res, ResMut},
+};
+use glam::{Vec2, Vec3, Vec4};
+use libprim::{
+    input::Keyboard,
+    instance::{Instance2D, InstanceBundle},
+    post_process::{
+        Bloom, ChromaticAberration, ColorGrade, ColorGradeLut, PostEffect, PostProcessChain,
+        Scanlines, Vignette,
+    },
+    shape_registry::ShapeRegistry,
+    state::CoreStages,
+    time::Time,
+    window::PrimWindowOptions,
+};
+use winit::event::VirtualKeyCode;
+
+#[derive(Component)]
+struct Spin(f32);
+
+fn spin(time: Res<Time>, mut instances: Query<(&mut Instance2D, &Spin)>) {
+    for (mut instance, spin) in &mut instances {
+        instance.rotation += spin.0 * time.delta_seconds();
+    }
+}
+
+/// All of the effects the example can switch between, with whether each one is enabled.
+struct Effects(Vec<(VirtualKeyCode, PostEffect, bool)>);
+
+fn toggle_effects(
+    input: Res<Keyboard>,
+    mut effects: ResMut<Effects>,
+    mut chain: ResMut<PostProcessChain>,
+) {
+    let mut changed = false;
+    for (key, _, enabled) in &mut effects.0 {
+        if input.just_down(key) {
+            *enabled = !*enabled;
+            changed = true;
+        }
+    }
+
+    if changed {
+        chain.effects = effects
+            .0
+            .iter()
+            .filter(|(_, _, enabled)| *enabled)
+            .map(|(_, effect, _)| effect.clone())
+            .collect();
+    }
+}
+
+/// Builds a look up table which gives everything a warm, faded look.
+fn warm_lut() -> ColorGradeLut {
+    let size = 16;
+    let mut data = Vec::new();
+    for b in 0..size {
+        for g in 0..size {
+            for r in 0..size {
+                let color = Vec3::new(r as f32, g as f32, b as f32) / (size - 1) as f32;
+                let warm = (color * Vec3::new(1.0, 0.9, 0.7) * 0.85 + Vec3::splat(0.1)) * 255.0;
+                data.extend_from_slice(&[warm.x as u8, warm.y as u8, warm.z as u8, 255]);
+            }
+        }
+    }
+    ColorGradeLut::new(size, data).unwrap()
+}
+
+fn run_post_process() {
+    libprim::run(PrimWindowOptions::default(), |state| {
+        let schedule = state.borrow_schedule();
+        schedule.add_system_to_stage(CoreStages::Update, spin);
+        schedule.add_system_to_stage(CoreStages::Update, toggle_effects);
+        let world = state.borrow_world();
+
+        let shape_registry = world.get_resource::<ShapeRegistry>().unwrap();
+        let triangle = shape_registry.get_id("Triangle").unwrap();
+        let square = shape_registry.get_id("Square").unwrap();
+
+        let colors = [
+            Vec4::new(1.0, 0.2, 0.8, 1.0),
+            Vec4::new(0.2, 1.0, 0.9, 1.0),
+            Vec4::new(1.0, 0.9, 0.2, 1.0),
+            Vec4::new(0.4, 0.5, 1.0, 1.0),
+        ];
+        for (i, color) in colors.into_iter().enumerate() {
+            world
+                .spawn()
+                .insert_bundle(InstanceBundle::new(Instance2D {
+                    position: Vec2::new(i as f32 * 100.0 - 150.0, 0.0),
+                    scale: Vec2::splat(50.0),
+                    color,
+                    shape: if i % 2 == 0 { triangle } else { square },
+                    ..Default::default()
+                }))
+                .insert(Spin(i as f32 * 0.5 + 0.5));
+        }
+
+        // Press 1-5 to toggle each of the effects.
+        let effects = Effects(vec![
+            (
+                VirtualKeyCode::Key1,
+                PostEffect::Bloom(Bloom::default()),
+                true,
+            ),
+            (
+                VirtualKeyCode::Key2,
+                PostEffect::ChromaticAberration(ChromaticAberration::default()),
+                false,
+            ),
+            (
+                VirtualKeyCode::Key3,
+                PostEffect::ColorGrade(ColorGrade::new(warm_lut())),
+                false,
+            ),
+            (
+                VirtualKeyCode::Key4,
+                PostEffect::Scanlines(Scanlines::default()),
+                true,
+            ),
+            (
+                VirtualKeyCode::Key5,
+                PostEffect::Vignette(Vignette::default()),
+                true,
+            ),
+        ]);
+        world.insert_resource(PostProcessChain {
+            effects: effects
+                .0
+                .iter()
+                .filter(|(_, _, enabled)| *enabled)
+                .map(|(_, effect, _)| effect.clone())
+                .collect(),
+        });
+        world.insert_resource(effects);
+    });
+}
+
+fn main() {
+    run_post_process();
+}
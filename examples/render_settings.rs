@@ -0,0 +1,110 @@
+use bevy_ecs::{
+    prelude::Component,
+    system::{Commands, Query, Res, ResMut},
+};
+use glam::{Vec2, Vec3, Vec4};
+use libprim::{
+    input::Keyboard,
+    instance::{Instance2D, InstanceBundle},
+    render_settings::RenderSettings,
+    shape_registry::ShapeRegistry,
+    state::CoreStages,
+    time::Time,
+    window::PrimWindowOptions,
+};
+use winit::event::VirtualKeyCode;
+
+/// The background colors the clear color cycles through.
+const CLEAR_COLORS: [Vec3; 3] = [
+    Vec3::new(0.02, 0.02, 0.05),
+    Vec3::new(0.2, 0.05, 0.1),
+    Vec3::new(0.05, 0.15, 0.1),
+];
+
+#[derive(Component)]
+struct Spin(f32);
+
+fn spin(time: Res<Time>, mut instances: Query<(&mut Instance2D, &Spin)>) {
+    for (mut instance, spin) in &mut instances {
+        instance.rotation += spin.0 * time.delta_seconds();
+    }
+}
+
+/// Changes the render settings with the keyboard, logging each change.
+fn change_settings(input: Res<Keyboard>, mut settings: ResMut<RenderSettings>) {
+    if input.just_down(&VirtualKeyCode::M) {
+        settings.sample_count = if settings.sample_count == 1 { 4 } else { 1 };
+        println!("Sample count: {}", settings.sample_count);
+    }
+    if input.just_down(&VirtualKeyCode::V) {
+        settings.vsync = !settings.vsync;
+        println!("VSync: {}", settings.vsync);
+    }
+    if input.just_down(&VirtualKeyCode::S) {
+        settings.sort_renderables = !settings.sort_renderables;
+        println!("Sorting: {}", settings.sort_renderables);
+    }
+    if input.just_down(&VirtualKeyCode::C) {
+        let next = CLEAR_COLORS
+            .iter()
+            .position(|color| *color == settings.clear_color)
+            .map_or(0, |i| (i + 1) % CLEAR_COLORS.len());
+        settings.clear_color = CLEAR_COLORS[next];
+    }
+}
+
+fn spawn_world(mut commands: Commands, shape_registry: Res<ShapeRegistry>) {
+    let square = shape_registry.get_id("Square").unwrap();
+    let circle = shape_registry.get_id("Circle").unwrap();
+    let star = shape_registry.get_id("Star").unwrap();
+
+    // Thin, spinning shapes show the jagged edges left without multisampling.
+    for i in 0..5 {
+        commands
+            .spawn()
+            .insert_bundle(InstanceBundle::new(Instance2D {
+                position: Vec2::new(i as f32 * 70.0 - 140.0, 60.0),
+                scale: Vec2::new(60.0, 3.0),
+                rotation: i as f32 * 0.3,
+                color: Vec4::new(0.9, 0.9, 0.9, 1.0),
+                shape: square,
+                ..Default::default()
+            }))
+            .insert(Spin(0.2 + i as f32 * 0.1));
+    }
+
+    // Alternating shapes which overlap within one layer, drawn in a different order when sorted.
+    let shapes = [
+        (circle, Vec4::new(1.0, 0.4, 0.3, 1.0)),
+        (star, Vec4::new(1.0, 0.85, 0.3, 1.0)),
+    ];
+    for i in 0..8 {
+        let (shape, color) = shapes[i % shapes.len()];
+        commands
+            .spawn()
+            .insert_bundle(InstanceBundle::new(Instance2D {
+                position: Vec2::new(i as f32 * 30.0 - 105.0, -60.0),
+                scale: Vec2::splat(50.0),
+                color,
+                shape,
+                ..Default::default()
+            }))
+            .insert(Spin(0.5));
+    }
+}
+
+fn run_render_settings() {
+    libprim::run(
+        PrimWindowOptions::default().with_clear_color(CLEAR_COLORS[0]),
+        |state| {
+            state.add_setup_system(spawn_world);
+            let schedule = state.borrow_schedule();
+            schedule.add_system_to_stage(CoreStages::Update, spin);
+            schedule.add_system_to_stage(CoreStages::Update, change_settings);
+        },
+    );
+}
+
+fn main() {
+    run_render_settings();
+}
@@ -0,0 +1,97 @@
+use bevy_ecs::{
+    prelude::{Component, With},
+    system::{Commands, Query, Res, ResMut},
+};
+use glam::{Vec2, Vec4};
+use libprim::{
+    instance::{Instance2D, InstanceBundle, Outline},
+    shape_generator,
+    shape_registry::ShapeRegistry,
+    state::CoreStages,
+    time::Time,
+    window::PrimWindowOptions,
+};
+
+/// Marks the instance drawing the shape which appears and disappears.
+#[derive(Component)]
+struct Blinker;
+
+/// How many sides the morphing polygon has at the given time.
+fn sides_at(seconds: f32) -> u32 {
+    3 + (seconds as u32) % 6
+}
+
+fn spawn_world(mut commands: Commands, mut shape_registry: ResMut<ShapeRegistry>) {
+    let polygon = shape_registry
+        .add_shape(shape_generator::regular_polygon(
+            "Morph".to_string(),
+            sides_at(0.0),
+        ))
+        .unwrap();
+    commands
+        .spawn()
+        .insert_bundle(InstanceBundle::new(Instance2D {
+            position: Vec2::new(-80.0, 0.0),
+            scale: Vec2::splat(120.0),
+            color: Vec4::new(0.4, 0.7, 1.0, 1.0),
+            shape: polygon,
+            outline: Some(Outline::new(2.0, Vec4::ONE)),
+            ..Default::default()
+        }));
+    commands
+        .spawn()
+        .insert_bundle(InstanceBundle::new(Instance2D {
+            position: Vec2::new(80.0, 0.0),
+            scale: Vec2::splat(120.0),
+            color: Vec4::new(1.0, 0.8, 0.3, 1.0),
+            ..Default::default()
+        }))
+        .insert(Blinker);
+}
+
+/// Gives the polygon another side every second, keeping its shape ID.
+fn morph(time: Res<Time>, mut shape_registry: ResMut<ShapeRegistry>) {
+    let sides = sides_at(time.total_seconds());
+    if sides != sides_at(time.total_seconds() - time.delta_seconds()) {
+        let polygon = shape_generator::regular_polygon("Morph".to_string(), sides);
+        shape_registry.replace_shape(polygon).unwrap();
+    }
+}
+
+/// Adds a star for one second and removes it for the next. The star gets a new ID each time it's
+/// added, so the instance drawing it is pointed at the new ID.
+fn blink(
+    time: Res<Time>,
+    mut shape_registry: ResMut<ShapeRegistry>,
+    mut blinkers: Query<&mut Instance2D, With<Blinker>>,
+) {
+    let visible = time.total_seconds() as u32 % 2 == 0;
+    match (visible, shape_registry.get_id("Blink")) {
+        (true, None) => {
+            let points = 4 + time.total_seconds() as u32 % 4;
+            let star = shape_generator::star("Blink".to_string(), points, 0.25);
+            let id = shape_registry.add_shape(star).unwrap();
+            for mut instance in &mut blinkers {
+                instance.shape = id;
+            }
+        }
+        (false, Some(_)) => {
+            shape_registry.remove_shape("Blink");
+        }
+        _ => {}
+    }
+}
+
+fn run_runtime_shapes() {
+    libprim::run(PrimWindowOptions::default(), |state| {
+        state.add_setup_system(spawn_world);
+        state
+            .borrow_schedule()
+            .add_system_to_stage(CoreStages::Update, morph)
+            .add_system_to_stage(CoreStages::Update, blink);
+    });
+}
+
+fn main() {
+    run_runtime_shapes();
+}
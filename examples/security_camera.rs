@@ -0,0 +1,130 @@
+use bevy_ecs::{
+    prelude::Component,
+    system::{Commands, Query, Res},
+};
+use glam::{Vec2, Vec4};
+use libprim::{
+    camera::{Camera2D, CameraClear, CameraFeed, RenderTarget},
+    instance::{Instance2D, InstanceBundle, Outline},
+    shape_registry::ShapeRegistry,
+    state::CoreStages,
+    time::Time,
+    window::PrimWindowOptions,
+};
+
+/// The vault watched by the security cameras, far away from the room with the monitors.
+const VAULT: Vec2 = Vec2::new(10_000.0, 0.0);
+
+/// A guard patrolling the vault in a circle.
+#[derive(Component)]
+struct Guard {
+    radius: f32,
+    speed: f32,
+}
+
+fn spawn_world(mut commands: Commands, shape_registry: Res<ShapeRegistry>) {
+    let square = shape_registry.get_id("Square").unwrap();
+    let triangle = shape_registry.get_id("Triangle").unwrap();
+
+    // The vault, with a safe in the middle and a couple of guards walking around it.
+    commands
+        .spawn()
+        .insert_bundle(InstanceBundle::new(Instance2D {
+            position: VAULT,
+            scale: Vec2::splat(60.0),
+            color: Vec4::new(0.9, 0.75, 0.2, 1.0),
+            shape: square,
+            outline: Some(Outline::new(4.0, Vec4::new(0.4, 0.3, 0.1, 1.0))),
+            ..Default::default()
+        }));
+    for (radius, speed, color) in [
+        (120.0, 1.0, Vec4::new(0.3, 0.6, 1.0, 1.0)),
+        (180.0, -0.6, Vec4::new(1.0, 0.4, 0.3, 1.0)),
+    ] {
+        commands
+            .spawn()
+            .insert_bundle(InstanceBundle::new(Instance2D {
+                position: VAULT + Vec2::new(radius, 0.0),
+                scale: Vec2::splat(30.0),
+                color,
+                shape: triangle,
+                ..Default::default()
+            }))
+            .insert(Guard { radius, speed });
+    }
+
+    // Two cameras watching the vault, one wide and one close up on the safe.
+    let wide = commands
+        .spawn()
+        .insert(
+            Camera2D::new(VAULT, Vec2::new(480.0, 360.0))
+                .with_target(RenderTarget::Texture {
+                    width: 320,
+                    height: 240,
+                })
+                .with_clear(CameraClear::Color(Vec4::new(0.05, 0.12, 0.05, 1.0))),
+        )
+        .id();
+    let close = commands
+        .spawn()
+        .insert(
+            Camera2D::new(VAULT, Vec2::new(160.0, 120.0))
+                .with_target(RenderTarget::Texture {
+                    width: 320,
+                    height: 240,
+                })
+                .with_clear(CameraClear::Color(Vec4::new(0.05, 0.05, 0.12, 1.0))),
+        )
+        .id();
+
+    // The monitors in the room, each showing one of the feeds in a frame.
+    for (position, camera, tint) in [
+        (Vec2::new(-110.0, 30.0), wide, Vec4::new(0.7, 1.0, 0.7, 1.0)),
+        (Vec2::new(110.0, 30.0), close, Vec4::ONE),
+    ] {
+        commands
+            .spawn()
+            .insert_bundle(InstanceBundle::new(Instance2D {
+                position,
+                scale: Vec2::new(200.0, 150.0),
+                color: tint,
+                shape: square,
+                outline: Some(Outline::new(8.0, Vec4::new(0.2, 0.2, 0.22, 1.0))),
+                ..Default::default()
+            }))
+            .insert(CameraFeed(camera));
+    }
+
+    // The desk the monitors sit on.
+    commands
+        .spawn()
+        .insert_bundle(InstanceBundle::new(Instance2D {
+            position: Vec2::new(0.0, -90.0),
+            scale: Vec2::new(500.0, 40.0),
+            color: Vec4::new(0.35, 0.25, 0.15, 1.0),
+            shape: square,
+            ..Default::default()
+        }));
+}
+
+fn patrol(time: Res<Time>, mut guards: Query<(&mut Instance2D, &Guard)>) {
+    for (mut instance, guard) in &mut guards {
+        let angle = time.total_seconds() * guard.speed;
+        let direction = Vec2::new(angle.cos(), angle.sin());
+        instance.position = VAULT + direction * guard.radius;
+        instance.rotation = angle + guard.speed.signum() * std::f32::consts::PI;
+    }
+}
+
+fn run_security_camera() {
+    libprim::run(PrimWindowOptions::default(), |state| {
+        state.add_setup_system(spawn_world);
+        state
+            .borrow_schedule()
+            .add_system_to_stage(CoreStages::Update, patrol);
+    });
+}
+
+fn main() {
+    run_security_camera();
+}
@@ -0,0 +1,93 @@
+use bevy_ecs::system::{Commands, Res};
+use glam::{Vec2, Vec4};
+use libprim::{
+    initialization::InitializeCommand,
+    instance::{Instance2D, InstanceBundle, Layer, ShaderEffect},
+    material::{InitializeMaterial, Material, MaterialRegistry},
+    shape_registry::ShapeRegistry,
+    window::PrimWindowOptions,
+};
+
+/// Shades the backdrop by where each pixel is on the screen, with a slow wave running across it.
+const BACKDROP: &str = "
+fn material(in: VertexOutput) -> vec4<f32> {
+    let uv = in.clip_position.xy / globals.resolution;
+    let wave = 0.5 + 0.5 * sin(uv.x * 6.0 + globals.time);
+    return vec4<f32>(in.color.rgb * mix(0.6, 1.0, wave * (1.0 - uv.y)), in.color.a);
+}
+";
+
+fn spawn_world(
+    mut commands: Commands,
+    shape_registry: Res<ShapeRegistry>,
+    material_registry: Res<MaterialRegistry>,
+) {
+    let square = shape_registry.get_id("Square").unwrap();
+    let circle = shape_registry.get_id("Circle").unwrap();
+    let star = shape_registry.get_id("Star").unwrap();
+    let backdrop = Material(material_registry.get_id("Backdrop").unwrap());
+
+    commands
+        .spawn()
+        .insert_bundle(InstanceBundle::new(Instance2D {
+            scale: Vec2::new(400.0, 300.0),
+            color: Vec4::new(0.15, 0.2, 0.35, 1.0),
+            shape: square,
+            ..Default::default()
+        }))
+        .insert(backdrop)
+        .insert(Layer::BACKGROUND);
+
+    let effects = [
+        (
+            circle,
+            Vec4::new(1.0, 0.4, 0.3, 1.0),
+            ShaderEffect::Pulse {
+                frequency: 1.5,
+                amount: 0.7,
+            },
+        ),
+        (
+            star,
+            Vec4::new(1.0, 0.85, 0.3, 1.0),
+            ShaderEffect::Wobble {
+                amplitude: 0.06,
+                frequency: 1.0,
+            },
+        ),
+        (
+            square,
+            Vec4::new(0.4, 0.9, 0.6, 1.0),
+            ShaderEffect::Stripes {
+                count: 4.0,
+                speed: 2.0,
+                angle: std::f32::consts::FRAC_PI_4,
+            },
+        ),
+    ];
+    for (i, (shape, color, effect)) in effects.into_iter().enumerate() {
+        commands
+            .spawn()
+            .insert_bundle(InstanceBundle::new(Instance2D {
+                position: Vec2::new(i as f32 * 120.0 - 120.0, 0.0),
+                scale: Vec2::splat(90.0),
+                color,
+                shape,
+                ..Default::default()
+            }))
+            .insert(effect);
+    }
+}
+
+fn run_shader_effects() {
+    libprim::run(PrimWindowOptions::default(), |state| {
+        state.add_initializer(InitializeCommand::InitializeMaterial(
+            InitializeMaterial::new("Backdrop".to_string(), BACKDROP.to_string()),
+        ));
+        state.add_setup_system(spawn_world);
+    });
+}
+
+fn main() {
+    run_shader_effects();
+}
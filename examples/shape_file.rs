@@ -0,0 +1,51 @@
+use bevy_ecs::system::{Commands, Res};
+use glam::{Vec2, Vec4};
+use libprim::{
+    initialization::InitializeCommand,
+    instance::{Instance2D, InstanceBundle, Outline},
+    shape_file::InitializeShapeFile,
+    shape_registry::ShapeRegistry,
+    window::PrimWindowOptions,
+};
+
+/// The shapes loaded from `assets/shapes/ships.ron`, with the color to draw each in.
+const SHIPS: [(&str, Vec4); 3] = [
+    ("Fighter", Vec4::ONE),
+    ("Cruiser", Vec4::new(0.9, 0.5, 0.3, 1.0)),
+    ("Station", Vec4::new(0.5, 0.8, 0.6, 1.0)),
+];
+
+fn spawn_world(mut commands: Commands, shape_registry: Res<ShapeRegistry>) {
+    for (i, (name, color)) in SHIPS.into_iter().enumerate() {
+        // Shapes which failed to load are logged and left out of the registry.
+        let Some(shape) = shape_registry.get_id(name) else {
+            continue;
+        };
+        commands
+            .spawn()
+            .insert_bundle(InstanceBundle::new(Instance2D {
+                position: Vec2::new((i as f32 - 1.0) * 120.0, 0.0),
+                scale: Vec2::splat(90.0),
+                color,
+                shape,
+                outline: Some(Outline::new(2.0, Vec4::ONE)),
+                ..Default::default()
+            }));
+    }
+}
+
+fn run_shape_file() {
+    libprim::run(PrimWindowOptions::default(), |state| {
+        state.add_initializer(InitializeCommand::InitializeShapeFile(
+            InitializeShapeFile::new(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/assets/shapes/ships.ron"
+            )),
+        ));
+        state.add_setup_system(spawn_world);
+    });
+}
+
+fn main() {
+    run_shape_file();
+}
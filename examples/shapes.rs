@@ -0,0 +1,80 @@
+use bevy_ecs::{
+    prelude::Component,
+    system::{Commands, Query, Res},
+};
+use glam::{Vec2, Vec4};
+use libprim::{
+    initialization::InitializeCommand,
+    instance::{Instance2D, InstanceBundle, Outline},
+    shape_generator,
+    shape_registry::ShapeRegistry,
+    state::CoreStages,
+    time::Time,
+    window::PrimWindowOptions,
+};
+
+/// Spins a shape at the given speed in radians per second.
+#[derive(Component)]
+struct Spin(f32);
+
+/// The shapes to show, in rows. The first row is built into the registry, and the second is
+/// generated on startup.
+const SHAPES: [[&str; 5]; 2] = [
+    ["Circle", "Pentagon", "Hexagon", "Star", "Ring"],
+    ["Ellipse", "Arc", "Pie", "RoundedRectangle", "Capsule"],
+];
+
+fn spawn_world(mut commands: Commands, shape_registry: Res<ShapeRegistry>) {
+    for (row, names) in SHAPES.iter().enumerate() {
+        for (column, name) in names.iter().enumerate() {
+            let Some(shape) = shape_registry.get_id(name) else {
+                continue;
+            };
+            let hue = column as f32 / names.len() as f32;
+            commands
+                .spawn()
+                .insert_bundle(InstanceBundle::new(Instance2D {
+                    position: Vec2::new(column as f32 - 2.0, 0.5 - row as f32) * 80.0,
+                    scale: Vec2::splat(64.0),
+                    color: Vec4::new(0.4 + hue * 0.6, 0.8 - hue * 0.4, 1.0 - hue * 0.6, 1.0),
+                    shape,
+                    outline: Some(Outline::new(2.0, Vec4::ONE)),
+                    ..Default::default()
+                }))
+                .insert(Spin(if row == 0 { 0.5 } else { -0.3 }));
+        }
+    }
+}
+
+fn spin(time: Res<Time>, mut shapes: Query<(&mut Instance2D, &Spin)>) {
+    for (mut instance, spin) in &mut shapes {
+        instance.rotation += spin.0 * time.delta_seconds();
+    }
+}
+
+fn run_shapes() {
+    libprim::run(PrimWindowOptions::default(), |state| {
+        for shape in [
+            shape_generator::ellipse("Ellipse".to_string(), Vec2::new(0.5, 0.3), 32),
+            shape_generator::arc("Arc".to_string(), 0.3, 0.0, 4.0, 24),
+            shape_generator::arc("Pie".to_string(), 0.0, 0.5, 5.0, 24),
+            shape_generator::rounded_rectangle(
+                "RoundedRectangle".to_string(),
+                Vec2::new(1.0, 0.7),
+                0.15,
+                6,
+            ),
+            shape_generator::capsule("Capsule".to_string(), Vec2::new(1.0, 0.4), 12),
+        ] {
+            state.add_initializer(InitializeCommand::InitializeShape(shape));
+        }
+        state.add_setup_system(spawn_world);
+        state
+            .borrow_schedule()
+            .add_system_to_stage(CoreStages::Update, spin);
+    });
+}
+
+fn main() {
+    run_shapes();
+}
@@ -1,18 +1,21 @@
 use bevy_ecs::{
-    prelude::{Component, Entity, EventReader},
+    prelude::{Component, Entity, EventReader, EventWriter},
     query::With,
     system::{Commands, Query, Res, ResMut},
 };
 use glam::{Vec2, Vec4};
 use libprim::{
     camera::{Camera2D, InitializeCamera},
+    capture::{Recording, RecordingFormat, Screenshot},
     collision::{
         base_collision_detection, collision_system_set, Collidable, Collider, CollidesWith,
         Colliding, HashGrid,
     },
+    debug_draw::DebugDraw,
     initialization::InitializeCommand,
     input::Keyboard,
     instance::{Instance2D, InstanceBundle},
+    material::BlendMode,
     particle_system::{
         components::{
             EmitterPosition, ParticleBurst, ParticleSystem, ParticleSystemBundle, Playing,
@@ -56,7 +59,7 @@ fn move_player(
     input: Res<Keyboard>,
     time: Res<Time>,
     mut player_query: Query<(&mut Instance2D, &MoveSpeed), With<Player>>,
-    mut camera: ResMut<Camera2D>,
+    mut cameras: Query<&mut Camera2D>,
 ) {
     let mut direction = Vec2::ZERO;
     if input.is_down(&VirtualKeyCode::Right) {
@@ -69,7 +72,9 @@ fn move_player(
 
     if let Ok((mut player_inst, speed)) = player_query.get_single_mut() {
         player_inst.position += speed.0 * time.delta_seconds() * direction;
-        camera.position = player_inst.position + Vec2::new(0.0, 250.0);
+        for mut camera in &mut cameras {
+            camera.position = player_inst.position + Vec2::new(0.0, 250.0);
+        }
     }
 }
 
@@ -153,6 +158,7 @@ pub fn player_fire_collision(
                     lifetime: JitteredValue::jittered(0.4, -0.2..0.1),
                     color: Vec4::new(1.0, 0.65, 0.0, 1.0).into(),
                     scale: 5.0.into(),
+                    blend_mode: BlendMode::Additive,
                     looping: false,
                     system_duration_seconds: 0.2,
                     max_distance: 50.0.into(),
@@ -306,6 +312,30 @@ fn spawn_world(
     });
 }
 
+/// Toggles drawing the collision boxes and hash grid cells with F1.
+fn toggle_collision_debug(input: Res<Keyboard>, mut debug_draw: ResMut<DebugDraw>) {
+    if input.just_down(&VirtualKeyCode::F1) {
+        debug_draw.show_collisions = !debug_draw.show_collisions;
+    }
+}
+
+/// Saves a screenshot with F12, or records five seconds of gameplay as a GIF with F11.
+fn capture_gameplay(
+    input: Res<Keyboard>,
+    mut screenshots: EventWriter<Screenshot>,
+    mut recordings: EventWriter<Recording>,
+) {
+    if input.just_down(&VirtualKeyCode::F12) {
+        screenshots.send(Screenshot::new("space_invaders.png"));
+    }
+    if input.just_down(&VirtualKeyCode::F11) {
+        recordings.send(
+            Recording::new("space_invaders.gif", RecordingFormat::Gif, 5.0)
+                .with_frames_per_second(20.0),
+        );
+    }
+}
+
 /// Reads window resize events to recenter the score text
 fn center_score(
     mut resize: EventReader<PrimWindowResized>,
@@ -344,19 +374,21 @@ pub fn space_invader() {
                 ]),
                 Vec::from([0, 1, 2, 0, 2, 3, 6, 7, 5, 5, 7, 4]),
             )));
-            state.add_initializer(InitializeCommand::InitializeShape(InitializeShape::new(
-                "Rocket".to_string(),
-                Vec::from([
-                    Vec2::new(0.0, 0.5),
-                    Vec2::new(-0.5, 0.0),
-                    Vec2::new(0.5, 0.0),
-                    Vec2::new(0.25, 0.0),
-                    Vec2::new(-0.25, 0.0),
-                    Vec2::new(-0.25, -0.5),
-                    Vec2::new(0.25, -0.5),
-                ]),
-                Vec::from([0, 1, 2, 3, 4, 5, 3, 5, 6]),
-            )));
+            state.add_initializer(InitializeCommand::InitializeShape(
+                InitializeShape::from_outline(
+                    "Rocket".to_string(),
+                    Vec::from([
+                        Vec2::new(0.0, 0.5),
+                        Vec2::new(-0.5, 0.0),
+                        Vec2::new(-0.25, 0.0),
+                        Vec2::new(-0.25, -0.5),
+                        Vec2::new(0.25, -0.5),
+                        Vec2::new(0.25, 0.0),
+                        Vec2::new(0.5, 0.0),
+                    ]),
+                )
+                .unwrap(),
+            ));
             state.add_initializer(InitializeCommand::InitializeCamera(InitializeCamera::new(
                 Vec2::new(0.0, 0.0),
                 Vec2::new(1024.0, 768.0),
@@ -386,6 +418,8 @@ pub fn space_invader() {
             schedule.add_system_to_stage(CoreStages::Update, player_fire_movement);
             schedule.add_system_to_stage(CoreStages::Update, player_fire_collision);
             schedule.add_system_to_stage(CoreStages::Update, score_display);
+            schedule.add_system_to_stage(CoreStages::Update, toggle_collision_debug);
+            schedule.add_system_to_stage(CoreStages::Update, capture_gameplay);
         },
     );
 }
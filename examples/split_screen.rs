@@ -0,0 +1,156 @@
+use bevy_ecs::{
+    prelude::{Component, Entity},
+    schedule::ParallelSystemDescriptorCoercion,
+    system::{Commands, Query, Res},
+};
+use glam::{Vec2, Vec4};
+use libprim::{
+    camera::{Camera2D, CameraClear, Viewport},
+    input::Keyboard,
+    instance::{Instance2D, InstanceBundle, Outline},
+    shape_registry::ShapeRegistry,
+    state::CoreStages,
+    time::Time,
+    window::{PrimWindow, PrimWindowOptions},
+};
+use winit::event::VirtualKeyCode;
+
+/// A player moved with its own set of keys, in the order up, down, left and right.
+#[derive(Component)]
+struct Player([VirtualKeyCode; 4]);
+
+/// A camera which keeps the given entity in the center of its view.
+#[derive(Component)]
+struct Follow(Entity);
+
+const PLAYER_SPEED: f32 = 200.0;
+
+fn spawn_world(
+    mut commands: Commands,
+    shape_registry: Res<ShapeRegistry>,
+    window: Res<PrimWindow>,
+    mut cameras: Query<(Entity, &mut Camera2D)>,
+) {
+    let square = shape_registry.get_id("Square").unwrap();
+    let triangle = shape_registry.get_id("Triangle").unwrap();
+
+    // A grid of obstacles to move around.
+    for x in -5..=5 {
+        for y in -5..=5 {
+            commands
+                .spawn()
+                .insert_bundle(InstanceBundle::new(Instance2D {
+                    position: Vec2::new(x as f32, y as f32) * 120.0,
+                    scale: Vec2::splat(25.0),
+                    rotation: (x * y) as f32 * 0.3,
+                    color: Vec4::new(0.25, 0.3 + (x + 5) as f32 * 0.05, 0.4, 1.0),
+                    shape: square,
+                    ..Default::default()
+                }));
+        }
+    }
+
+    let players = [
+        (
+            Vec2::new(-60.0, 0.0),
+            Vec4::new(1.0, 0.4, 0.3, 1.0),
+            [
+                VirtualKeyCode::W,
+                VirtualKeyCode::S,
+                VirtualKeyCode::A,
+                VirtualKeyCode::D,
+            ],
+        ),
+        (
+            Vec2::new(60.0, 0.0),
+            Vec4::new(0.3, 0.7, 1.0, 1.0),
+            [
+                VirtualKeyCode::Up,
+                VirtualKeyCode::Down,
+                VirtualKeyCode::Left,
+                VirtualKeyCode::Right,
+            ],
+        ),
+    ]
+    .map(|(position, color, keys)| {
+        commands
+            .spawn()
+            .insert_bundle(InstanceBundle::new(Instance2D {
+                position,
+                scale: Vec2::splat(20.0),
+                color,
+                shape: triangle,
+                outline: Some(Outline::new(2.0, Vec4::ONE)),
+                ..Default::default()
+            }))
+            .insert(Player(keys))
+            .id()
+    });
+
+    // Each half of the window follows a player. The camera spawned on startup becomes the left
+    // half, and another is spawned for the right.
+    let half_size = Vec2::new(window.width() as f32 / 2.0, window.height() as f32);
+    let left = Viewport::new(Vec2::ZERO, Vec2::new(0.5, 1.0));
+    let right = Viewport::new(Vec2::new(0.5, 0.0), Vec2::new(0.5, 1.0));
+    if let Some((entity, mut camera)) = cameras.iter_mut().next() {
+        *camera = Camera2D::new(Vec2::ZERO, half_size).with_viewport(left);
+        commands.entity(entity).insert(Follow(players[0]));
+    }
+    commands
+        .spawn()
+        .insert(Camera2D::new(Vec2::ZERO, half_size).with_viewport(right))
+        .insert(Follow(players[1]));
+
+    // A minimap of the whole arena, drawn over both halves.
+    commands.spawn().insert(
+        Camera2D::new(Vec2::ZERO, Vec2::splat(1400.0))
+            .with_viewport(Viewport::new(Vec2::new(0.425, 0.02), Vec2::new(0.15, 0.2)))
+            .with_priority(1)
+            .with_clear(CameraClear::Color(Vec4::new(0.05, 0.05, 0.08, 1.0))),
+    );
+}
+
+fn move_players(
+    input: Res<Keyboard>,
+    time: Res<Time>,
+    mut players: Query<(&mut Instance2D, &Player)>,
+) {
+    for (mut instance, Player([up, down, left, right])) in &mut players {
+        let mut direction = Vec2::ZERO;
+        for (key, step) in [
+            (up, Vec2::Y),
+            (down, Vec2::NEG_Y),
+            (left, Vec2::NEG_X),
+            (right, Vec2::X),
+        ] {
+            if input.is_down(key) {
+                direction += step;
+            }
+        }
+        if direction != Vec2::ZERO {
+            instance.position += direction.normalize() * PLAYER_SPEED * time.delta_seconds();
+            instance.rotation = direction.y.atan2(direction.x) - std::f32::consts::FRAC_PI_2;
+        }
+    }
+}
+
+fn follow_players(mut cameras: Query<(&mut Camera2D, &Follow)>, targets: Query<&Instance2D>) {
+    for (mut camera, follow) in &mut cameras {
+        if let Ok(target) = targets.get(follow.0) {
+            camera.position = target.position;
+        }
+    }
+}
+
+fn run_split_screen() {
+    libprim::run(PrimWindowOptions::default(), |state| {
+        state.add_setup_system(spawn_world);
+        let schedule = state.borrow_schedule();
+        schedule.add_system_to_stage(CoreStages::Update, move_players.label("move"));
+        schedule.add_system_to_stage(CoreStages::Update, follow_players.after("move"));
+    });
+}
+
+fn main() {
+    run_split_screen();
+}
@@ -0,0 +1,72 @@
+use bevy_ecs::{
+    prelude::Component,
+    system::{Commands, Query, Res},
+};
+use glam::{Vec2, Vec4};
+use libprim::{
+    initialization::InitializeCommand,
+    instance::{Instance2D, InstanceBundle},
+    shape_registry::ShapeRegistry,
+    state::CoreStages,
+    svg::InitializeSvg,
+    time::Time,
+    window::PrimWindowOptions,
+};
+
+/// Spins a shape at the given speed in radians per second.
+#[derive(Component)]
+struct Spin(f32);
+
+/// The shapes imported from `assets/shapes/icons.svg`, with the color to draw each in and how
+/// fast each spins.
+const ICONS: [(&str, Vec4, f32); 3] = [
+    ("Heart", Vec4::new(0.9, 0.2, 0.3, 1.0), 0.0),
+    ("Gear", Vec4::new(0.7, 0.7, 0.75, 1.0), 0.8),
+    // The rocket keeps the colors of its parts in the SVG.
+    ("Rocket", Vec4::ONE, 0.0),
+];
+
+fn spawn_world(mut commands: Commands, shape_registry: Res<ShapeRegistry>) {
+    for (i, (name, color, speed)) in ICONS.into_iter().enumerate() {
+        // Shapes which failed to import are logged and left out of the registry.
+        let Some(shape) = shape_registry.get_id(name) else {
+            continue;
+        };
+        commands
+            .spawn()
+            .insert_bundle(InstanceBundle::new(Instance2D {
+                position: Vec2::new((i as f32 - 1.0) * 120.0, 0.0),
+                scale: Vec2::splat(100.0),
+                color,
+                shape,
+                ..Default::default()
+            }))
+            .insert(Spin(speed));
+    }
+}
+
+fn spin(time: Res<Time>, mut shapes: Query<(&mut Instance2D, &Spin)>) {
+    for (mut instance, spin) in &mut shapes {
+        instance.rotation += spin.0 * time.delta_seconds();
+    }
+}
+
+fn run_svg() {
+    libprim::run(PrimWindowOptions::default(), |state| {
+        state.add_initializer(InitializeCommand::InitializeSvg(
+            InitializeSvg::new(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/assets/shapes/icons.svg"
+            ))
+            .with_tolerance(0.25),
+        ));
+        state.add_setup_system(spawn_world);
+        state
+            .borrow_schedule()
+            .add_system_to_stage(CoreStages::Update, spin);
+    });
+}
+
+fn main() {
+    run_svg();
+}
@@ -1,4 +1,5 @@
-use glam::{Mat3, Mat4, Vec2};
+use bevy_ecs::prelude::{Component, Entity};
+use glam::{Mat3, Mat4, Vec2, Vec4};
 
 /// Container struct for the camera View Projection matrix.
 ///
@@ -10,22 +11,180 @@ pub struct ViewMatrix {
     pub view: Mat4,
 }
 
-/// The Camera representation.
+/// The area of the window, or of its texture, a [`Camera2D`] draws into.
+///
+/// Measured as fractions of the target size, from `(0.0, 0.0)` at the top left to `(1.0, 1.0)` at
+/// the bottom right, so that viewports keep their layout when the window is resized.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    /// The top left corner of the viewport.
+    pub position: Vec2,
+    /// The width and height of the viewport.
+    pub size: Vec2,
+}
+
+impl Viewport {
+    /// A viewport covering the whole window.
+    pub const FULL: Viewport = Viewport {
+        position: Vec2::ZERO,
+        size: Vec2::ONE,
+    };
+
+    /// Creates a viewport with its top left corner at `position`, both measured as fractions of
+    /// the window size.
+    ///
+    /// ## Example
+    /// ```
+    /// # use libprim::{camera::Viewport, Vec2};
+    /// // The right half of the window, for the second player in split-screen.
+    /// let right = Viewport::new(Vec2::new(0.5, 0.0), Vec2::new(0.5, 1.0));
+    /// ```
+    #[must_use]
+    pub fn new(position: Vec2, size: Vec2) -> Self {
+        Self { position, size }
+    }
+
+    /// Converts the viewport to a pixel rectangle within a target of the given size.
+    ///
+    /// Returns `None` if no part of the viewport is within the target.
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    pub(crate) fn to_pixels(self, width: u32, height: u32) -> Option<PixelRect> {
+        let target = Vec2::new(width as f32, height as f32);
+        let min = (self.position * target).round().clamp(Vec2::ZERO, target);
+        let max = ((self.position + self.size) * target)
+            .round()
+            .clamp(Vec2::ZERO, target);
+        let size = max - min;
+        (size.x >= 1.0 && size.y >= 1.0).then_some(PixelRect {
+            x: min.x as u32,
+            y: min.y as u32,
+            width: size.x as u32,
+            height: size.y as u32,
+        })
+    }
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Self::FULL
+    }
+}
+
+/// A rectangle of pixels within a render target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct PixelRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// How a [`Camera2D`]'s viewport is cleared before it's drawn.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum CameraClear {
+    /// The camera draws over the window's clear color and anything drawn by cameras with a lower
+    /// priority.
+    #[default]
+    None,
+    /// The viewport is filled with a color before the camera draws, hiding anything drawn by
+    /// cameras with a lower priority. Useful for picture-in-picture views.
+    Color(Vec4),
+}
+
+/// Where a [`Camera2D`] draws to.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RenderTarget {
+    /// The camera draws into the window, or the output of a headless state.
+    #[default]
+    Window,
+    /// The camera draws into an offscreen texture of the given size in pixels, which can be shown
+    /// on instances with a [`CameraFeed`] component.
+    ///
+    /// Texture cameras are drawn before the window, without post-processing or debug drawing.
+    Texture {
+        /// The width of the texture.
+        width: u32,
+        /// The height of the texture.
+        height: u32,
+    },
+}
+
+/// Fills an instance's shape with the view of a camera drawing to a [`RenderTarget::Texture`],
+/// such as for a minimap or a security camera screen.
+///
+/// The view is stretched across the -0.5 to 0.5 bounds of the built-in shapes, and tinted by the
+/// instance's color. Instances with a feed are drawn with
+/// [`libprim::material::Material::CAMERA_FEED`], replacing any other material, and are lit like
+/// any other instance unless they are [`libprim::light::Unlit`].
+///
+/// A camera never shows its own feed, and feeds of cameras drawn later in the frame show their
+/// previous frame.
 ///
-/// Accessible and modifiable through a bevy Resource.
+/// ## Example
+/// ```
+/// # use bevy_ecs::system::{Commands, Res};
+/// # use libprim::{
+/// #     camera::{Camera2D, CameraFeed, RenderTarget},
+/// #     instance::{Instance2D, InstanceBundle},
+/// #     shape_registry::ShapeRegistry,
+/// #     Vec2,
+/// # };
+/// fn spawn_screen(mut commands: Commands, shape_registry: Res<ShapeRegistry>) {
+///     let camera = commands
+///         .spawn()
+///         .insert(
+///             Camera2D::new(Vec2::new(500.0, 0.0), Vec2::new(200.0, 150.0)).with_target(
+///                 RenderTarget::Texture {
+///                     width: 256,
+///                     height: 192,
+///                 },
+///             ),
+///         )
+///         .id();
+///     commands
+///         .spawn()
+///         .insert_bundle(InstanceBundle::new(Instance2D {
+///             scale: Vec2::new(120.0, 90.0),
+///             shape: shape_registry.get_id("Square").unwrap(),
+///             ..Default::default()
+///         }))
+///         .insert(CameraFeed(camera));
+/// }
+/// ```
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CameraFeed(pub Entity);
+
+/// The Camera representation.
 ///
-/// Only one camera is currently supported.
+/// Cameras are components, and every entity with a [`Camera2D`] draws the world into its
+/// [`Viewport`] each frame. Cameras are drawn in order of their `priority`, so cameras with a
+/// higher priority are drawn on top, such as a minimap over the main view. Spawning more cameras
+/// with viewports side by side gives split-screen views.
 ///
-/// Cameras are created at initialization time using an [`InitializeCamera`]
-/// initializer command.
+/// One camera covering the whole window is spawned on startup. Its position and size can be set
+/// at initialization time using an [`InitializeCamera`] initializer command, and if one is not
+/// given it defaults to being at 0,0 with a size of half the requested (or default) screen size.
 ///
-/// If a camera is not specifically created, it defaults to being at 0,0 and
-/// has a size of the requested (or default) screen size.
+/// A camera's view is stretched to fill its viewport, so `scale` should have the same aspect
+/// ratio as the viewport to avoid distortion.
+#[derive(Component, Debug, Clone)]
 pub struct Camera2D {
     /// The position of the camera center
     pub position: Vec2,
     /// Holds the width and height of the camera view.
     pub scale: Vec2,
+    /// The area of its target the camera draws into.
+    pub viewport: Viewport,
+    /// The order cameras are drawn in, from lowest to highest.
+    pub priority: i32,
+    /// How the camera's viewport is cleared before it's drawn.
+    pub clear: CameraClear,
+    /// Where the camera draws to.
+    pub target: RenderTarget,
     /// Holds the view matrix, which is the inverse of the transform matrix.
     ///
     /// This is used to multiply other transform matrices and center the world around
@@ -43,6 +202,10 @@ impl Camera2D {
         Self {
             position,
             scale,
+            viewport: Viewport::FULL,
+            priority: 0,
+            clear: CameraClear::None,
+            target: RenderTarget::Window,
             view: Mat3::from_translation(position).inverse(),
             proj: Mat4::orthographic_lh(
                 -scale.x / 2.0,
@@ -55,6 +218,44 @@ impl Camera2D {
         }
     }
 
+    /// Sets the area of the window the camera draws into.
+    ///
+    /// ## Example
+    /// ```
+    /// # use libprim::{camera::{Camera2D, CameraClear, Viewport}, Vec2, Vec4};
+    /// // A small view in the top right corner, drawn over the main camera.
+    /// let minimap = Camera2D::new(Vec2::ZERO, Vec2::new(800.0, 600.0))
+    ///     .with_viewport(Viewport::new(Vec2::new(0.75, 0.0), Vec2::new(0.25, 0.25)))
+    ///     .with_priority(1)
+    ///     .with_clear(CameraClear::Color(Vec4::new(0.1, 0.1, 0.1, 1.0)));
+    /// ```
+    #[must_use]
+    pub fn with_viewport(mut self, viewport: Viewport) -> Self {
+        self.viewport = viewport;
+        self
+    }
+
+    /// Sets the order the camera is drawn in. Cameras with a higher priority are drawn on top.
+    #[must_use]
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Sets how the camera's viewport is cleared before it's drawn.
+    #[must_use]
+    pub fn with_clear(mut self, clear: CameraClear) -> Self {
+        self.clear = clear;
+        self
+    }
+
+    /// Sets where the camera draws to.
+    #[must_use]
+    pub fn with_target(mut self, target: RenderTarget) -> Self {
+        self.target = target;
+        self
+    }
+
     /// Recomputes the orthographic matrix with a new size.
     pub fn rescale(&mut self, scale: Vec2) {
         self.scale = scale;
@@ -83,6 +284,30 @@ impl Camera2D {
             view: self.proj * Mat4::from_mat3(self.view),
         }
     }
+
+    /// Whether a box with the given corners may be visible to the camera.
+    ///
+    /// The check is generous, extending a full camera size past each edge of the view, so that
+    /// anything near the edge is always drawn.
+    pub(crate) fn overlaps(&self, min: Vec2, max: Vec2) -> bool {
+        min.x < self.position.x + self.scale.x
+            && max.x > self.position.x - self.scale.x
+            && min.y < self.position.y + self.scale.y
+            && max.y > self.position.y - self.scale.y
+    }
+
+    /// Builds two triangles covering the camera's whole view, appending them to `triangles`.
+    pub(crate) fn view_triangles(&self, triangles: &mut Vec<Vec2>) {
+        let (min, max) = (self.position - self.scale, self.position + self.scale);
+        triangles.extend_from_slice(&[
+            min,
+            Vec2::new(max.x, min.y),
+            max,
+            min,
+            max,
+            Vec2::new(min.x, max.y),
+        ]);
+    }
 }
 
 /// An initializer for the engine's Camera, allowing specification of
@@ -101,3 +326,46 @@ impl InitializeCamera {
         Self { position, size }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec2;
+
+    use super::{PixelRect, Viewport};
+
+    #[test]
+    fn test_viewport_to_pixels() {
+        assert_eq!(
+            Viewport::FULL.to_pixels(800, 600),
+            Some(PixelRect {
+                x: 0,
+                y: 0,
+                width: 800,
+                height: 600
+            })
+        );
+        assert_eq!(
+            Viewport::new(Vec2::new(0.5, 0.25), Vec2::new(0.5, 0.5)).to_pixels(800, 600),
+            Some(PixelRect {
+                x: 400,
+                y: 150,
+                width: 400,
+                height: 300
+            })
+        );
+        // Viewports are clipped to the target, and skipped entirely when nothing is left.
+        assert_eq!(
+            Viewport::new(Vec2::new(0.75, -0.5), Vec2::ONE).to_pixels(800, 600),
+            Some(PixelRect {
+                x: 600,
+                y: 0,
+                width: 200,
+                height: 300
+            })
+        );
+        assert_eq!(
+            Viewport::new(Vec2::new(1.0, 0.0), Vec2::ONE).to_pixels(800, 600),
+            None
+        );
+    }
+}
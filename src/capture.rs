@@ -0,0 +1,789 @@
+use std::{
+    fs::File,
+    io::BufWriter,
+    io::Write,
+    num::NonZeroU32,
+    path::{Path, PathBuf},
+    sync::{mpsc::SyncSender, Arc, Mutex},
+};
+
+use bevy_ecs::{
+    prelude::EventReader,
+    system::{Res, ResMut},
+};
+use log::{error, info};
+
+use crate::{post_process::PostProcessor, state::RenderState, time::Time};
+
+/// How fast GIF frames are quantized, from 1 (best quality) to 30 (fastest).
+const GIF_QUANTIZE_SPEED: i32 = 10;
+
+/// The number of captured frames which can wait for each writer thread to encode them.
+///
+/// Once a writer falls this far behind, handing it frames blocks until it catches up rather than
+/// holding on to ever more frames.
+const CAPTURE_QUEUE_SIZE: usize = 8;
+
+/// Sends PNG captures, and the paths to save them to, to the PNG writer thread.
+type PngWriter = SyncSender<(PathBuf, FrameCapture)>;
+
+/// A single rendered frame read back from the GPU.
+///
+/// Pixels are stored as tightly packed 8-bit RGBA rows, starting from the top left of the frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrameCapture {
+    width: u32,
+    height: u32,
+    data: Vec<u8>,
+}
+
+impl FrameCapture {
+    /// Builds a capture from a buffer copied out of a texture.
+    ///
+    /// Texture to buffer copies need each row aligned to [`wgpu::COPY_BYTES_PER_ROW_ALIGNMENT`],
+    /// so the padding is stripped here. BGRA formats are swizzled to RGBA.
+    #[must_use]
+    pub(crate) fn from_padded(
+        padded: &[u8],
+        width: u32,
+        height: u32,
+        padded_bytes_per_row: u32,
+        format: wgpu::TextureFormat,
+    ) -> Self {
+        let row_bytes = (width * 4) as usize;
+        let mut data = Vec::with_capacity(row_bytes * height as usize);
+        for row in padded
+            .chunks(padded_bytes_per_row as usize)
+            .take(height as usize)
+        {
+            data.extend_from_slice(&row[..row_bytes]);
+        }
+
+        if matches!(
+            format,
+            wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb
+        ) {
+            for pixel in data.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
+
+        Self {
+            width,
+            height,
+            data,
+        }
+    }
+
+    /// The width of the frame in pixels.
+    #[must_use]
+    #[inline(always)]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// The height of the frame in pixels.
+    #[must_use]
+    #[inline(always)]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The raw RGBA pixel data of the frame.
+    #[must_use]
+    #[inline(always)]
+    pub fn pixels(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Gets the RGBA value of a single pixel.
+    ///
+    /// # Panics
+    /// Panics if the coordinates are outside of the frame.
+    #[must_use]
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        assert!(x < self.width && y < self.height, "Pixel out of bounds");
+        let offset = ((y * self.width + x) * 4) as usize;
+        [
+            self.data[offset],
+            self.data[offset + 1],
+            self.data[offset + 2],
+            self.data[offset + 3],
+        ]
+    }
+
+    /// Encodes the frame as a PNG into the given writer.
+    ///
+    /// # Errors
+    /// Returns an [`std::io::Error`] if writing fails or the image cannot be encoded.
+    pub fn write_png<W: Write>(&self, writer: W) -> std::io::Result<()> {
+        let mut encoder = png::Encoder::new(writer, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .write_header()
+            .and_then(|mut png_writer| png_writer.write_image_data(&self.data))
+            .map_err(std::io::Error::other)
+    }
+
+    /// Saves the frame as a PNG file at the given path.
+    ///
+    /// # Errors
+    /// Returns an [`std::io::Error`] if the file cannot be created or the image cannot be encoded.
+    pub fn save_png<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        self.write_png(BufWriter::new(File::create(path)?))
+    }
+}
+
+/// An event requesting a screenshot of the next rendered frame, saved as a PNG file.
+///
+/// The frame is read back from the GPU and encoded in the background, so the file is written a
+/// short time after the frame is presented.
+///
+/// ## Example
+/// ```
+/// # use bevy_ecs::prelude::EventWriter;
+/// # use libprim::capture::Screenshot;
+/// fn take_screenshot(mut screenshots: EventWriter<Screenshot>) {
+///     screenshots.send(Screenshot::new("screenshot.png"));
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Screenshot {
+    /// The path to save the PNG file to.
+    pub path: PathBuf,
+}
+
+impl Screenshot {
+    /// Requests a screenshot saved to the given path.
+    #[must_use]
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+/// How the frames of a [`Recording`] are saved.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RecordingFormat {
+    /// Each frame is saved as a numbered PNG file, such as `frame_00000.png`, in the recording's
+    /// directory.
+    #[default]
+    Frames,
+    /// The frames are saved as a looping animated GIF file.
+    ///
+    /// Not supported on the web, where GIFs can't be encoded in the background.
+    Gif,
+}
+
+/// An event starting a recording of the rendered frames for a set duration.
+///
+/// Starting a recording while another is in progress finishes the current one first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recording {
+    /// The directory to save frames into, or the file to save the GIF to.
+    pub path: PathBuf,
+    /// How the frames are saved.
+    pub format: RecordingFormat,
+    /// How long to record for, in seconds.
+    pub duration: f32,
+    /// How many frames are captured each second.
+    ///
+    /// Frames are captured at most once per rendered frame, so a slower game will record fewer.
+    pub frames_per_second: f32,
+}
+
+impl Recording {
+    /// Requests a recording of the given length, captured at 30 frames per second.
+    #[must_use]
+    pub fn new(path: impl Into<PathBuf>, format: RecordingFormat, duration: f32) -> Self {
+        Self {
+            path: path.into(),
+            format,
+            duration,
+            frames_per_second: 30.0,
+        }
+    }
+
+    /// Sets how many frames are captured each second.
+    #[must_use]
+    pub fn with_frames_per_second(mut self, frames_per_second: f32) -> Self {
+        self.frames_per_second = frames_per_second;
+        self
+    }
+}
+
+/// Where a captured frame should go once it has been read back.
+enum CaptureDestination {
+    Png(PathBuf),
+    Gif(SyncSender<FrameCapture>),
+}
+
+/// Where the frames of the current recording are sent.
+enum RecordingSink {
+    Frames { directory: PathBuf, next_frame: u32 },
+    Gif(SyncSender<FrameCapture>),
+}
+
+/// A recording in progress.
+struct ActiveRecording {
+    sink: RecordingSink,
+    remaining: f32,
+    interval: f32,
+    until_next: f32,
+}
+
+impl ActiveRecording {
+    fn start(recording: &Recording) -> std::io::Result<Self> {
+        let sink = match recording.format {
+            RecordingFormat::Frames => {
+                std::fs::create_dir_all(&recording.path)?;
+                RecordingSink::Frames {
+                    directory: recording.path.clone(),
+                    next_frame: 0,
+                }
+            }
+            RecordingFormat::Gif => RecordingSink::Gif(spawn_gif_writer(
+                recording.path.clone(),
+                gif_delay(recording.frames_per_second),
+            )?),
+        };
+
+        Ok(Self {
+            sink,
+            remaining: recording.duration,
+            interval: 1.0 / recording.frames_per_second.max(f32::EPSILON),
+            until_next: 0.0,
+        })
+    }
+
+    /// Advances the recording by a frame, returning where to send the frame if it should be
+    /// captured.
+    fn advance(&mut self, delta_seconds: f32) -> Option<CaptureDestination> {
+        self.remaining -= delta_seconds;
+        self.until_next -= delta_seconds;
+        if self.until_next > 0.0 {
+            return None;
+        }
+        // Stay on the same schedule without trying to catch up on frames that were missed.
+        self.until_next = (self.until_next + self.interval).max(0.0);
+
+        Some(match &mut self.sink {
+            RecordingSink::Frames {
+                directory,
+                next_frame,
+            } => {
+                let path = directory.join(format!("frame_{next_frame:05}.png"));
+                *next_frame += 1;
+                CaptureDestination::Png(path)
+            }
+            RecordingSink::Gif(sender) => CaptureDestination::Gif(sender.clone()),
+        })
+    }
+}
+
+/// A frame being copied back from the GPU.
+struct PendingCapture {
+    buffer: wgpu::Buffer,
+    padded_bytes_per_row: u32,
+    width: u32,
+    height: u32,
+    format: wgpu::TextureFormat,
+    /// Set once the buffer has been mapped, or failed to be.
+    mapped: Arc<Mutex<Option<Result<(), wgpu::BufferAsyncError>>>>,
+    destinations: Vec<CaptureDestination>,
+}
+
+/// The texture frames are drawn into when they are being captured, before being copied to the
+/// output.
+///
+/// Window surfaces can't always be copied from, so captured frames are drawn here instead.
+struct CaptureTarget {
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    width: u32,
+    height: u32,
+    format: wgpu::TextureFormat,
+}
+
+/// A resource which handles [`Screenshot`] and [`Recording`] requests, capturing rendered frames
+/// and writing them to disk.
+#[derive(Default)]
+pub(crate) struct FrameRecorder {
+    target: Option<CaptureTarget>,
+    destinations: Vec<CaptureDestination>,
+    recording: Option<ActiveRecording>,
+    pending: Vec<PendingCapture>,
+    /// The thread PNGs are encoded on, started with the first PNG capture.
+    png_writer: Option<PngWriter>,
+}
+
+impl FrameRecorder {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The texture to draw the frame into if it is being captured, or `None` to draw it directly
+    /// to the output.
+    #[must_use]
+    pub fn capture_view(&self) -> Option<&wgpu::TextureView> {
+        self.target
+            .as_ref()
+            .filter(|_| !self.destinations.is_empty())
+            .map(|target| &target.view)
+    }
+
+    /// Makes sure the capture target matches the output if this frame is being captured.
+    fn prepare_target(&mut self, device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) {
+        if self.destinations.is_empty()
+            || self.target.as_ref().is_some_and(|target| {
+                (target.width, target.height, target.format)
+                    == (config.width, config.height, config.format)
+            })
+        {
+            return;
+        }
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Frame Capture Target"),
+            size: wgpu::Extent3d {
+                width: config.width,
+                height: config.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: config.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC,
+        });
+        self.target = Some(CaptureTarget {
+            view: texture.create_view(&wgpu::TextureViewDescriptor::default()),
+            texture,
+            width: config.width,
+            height: config.height,
+            format: config.format,
+        });
+    }
+
+    /// Copies the captured frame to the output, and starts reading it back from the GPU.
+    ///
+    /// Does nothing if the frame isn't being captured.
+    pub fn finish_frame(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        post_processor: &PostProcessor,
+        output: &wgpu::TextureView,
+    ) {
+        let Some(target) = self
+            .target
+            .as_ref()
+            .filter(|_| !self.destinations.is_empty())
+        else {
+            return;
+        };
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Frame Capture Encoder"),
+        });
+        post_processor.blit(device, &mut encoder, &target.view, output);
+        let (buffer, padded_bytes_per_row) = copy_texture_to_buffer(
+            device,
+            &mut encoder,
+            &target.texture,
+            target.width,
+            target.height,
+        );
+        queue.submit(std::iter::once(encoder.finish()));
+
+        let mapped = Arc::new(Mutex::new(None));
+        let mapped_result = Arc::clone(&mapped);
+        buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                *mapped_result.lock().unwrap() = Some(result);
+            });
+        self.pending.push(PendingCapture {
+            buffer,
+            padded_bytes_per_row,
+            width: target.width,
+            height: target.height,
+            format: target.format,
+            mapped,
+            destinations: std::mem::take(&mut self.destinations),
+        });
+    }
+
+    /// Hands any frames which have finished reading back to their destinations.
+    fn write_finished(&mut self) {
+        let png_writer = &mut self.png_writer;
+        self.pending
+            .retain(|pending| match pending.mapped.lock().unwrap().take() {
+                Some(Ok(())) => {
+                    let slice = pending.buffer.slice(..);
+                    let mapped = slice.get_mapped_range();
+                    let capture = FrameCapture::from_padded(
+                        &mapped,
+                        pending.width,
+                        pending.height,
+                        pending.padded_bytes_per_row,
+                        pending.format,
+                    );
+                    drop(mapped);
+                    pending.buffer.unmap();
+                    for destination in &pending.destinations {
+                        write_capture(capture.clone(), destination, png_writer);
+                    }
+                    false
+                }
+                Some(Err(err)) => {
+                    error!("Error reading back captured frame: {err}");
+                    false
+                }
+                None => true,
+            });
+    }
+}
+
+/// Sends a captured frame to its destination, encoding PNGs on the PNG writer thread.
+///
+/// The writer is started by the first PNG capture. When it can't be started, such as in web builds
+/// which can't start threads, PNGs are encoded straight away instead.
+fn write_capture(
+    capture: FrameCapture,
+    destination: &CaptureDestination,
+    png_writer: &mut Option<PngWriter>,
+) {
+    match destination {
+        CaptureDestination::Png(path) => {
+            if png_writer.is_none() {
+                *png_writer = spawn_png_writer();
+            }
+            match png_writer {
+                Some(sender) => {
+                    // The writer runs for as long as the recorder holds its sender.
+                    let _ = sender.send((path.clone(), capture));
+                }
+                None => save_capture(&capture, path),
+            }
+        }
+        CaptureDestination::Gif(sender) => {
+            // The writer thread only stops early if it has already failed and logged why.
+            let _ = sender.send(capture);
+        }
+    }
+}
+
+/// The delay between GIF frames in hundredths of a second, which is the unit GIFs use.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn gif_delay(frames_per_second: f32) -> u16 {
+    (100.0 / frames_per_second.max(f32::EPSILON))
+        .round()
+        .clamp(1.0, f32::from(u16::MAX)) as u16
+}
+
+/// Saves a captured frame as a PNG, logging the result.
+fn save_capture(capture: &FrameCapture, path: &Path) {
+    match capture.save_png(path) {
+        Ok(()) => info!("Saved capture to {}", path.display()),
+        Err(err) => error!("Error saving capture to {}: {err}", path.display()),
+    }
+}
+
+/// Starts a thread which saves the captures sent to it as PNGs.
+#[cfg(not(target_arch = "wasm32"))]
+fn spawn_png_writer() -> Option<PngWriter> {
+    let (sender, receiver): (PngWriter, _) = std::sync::mpsc::sync_channel(CAPTURE_QUEUE_SIZE);
+    let spawned = std::thread::Builder::new()
+        .name("PNG Writer".to_string())
+        .spawn(move || {
+            for (path, capture) in receiver {
+                save_capture(&capture, &path);
+            }
+        });
+    match spawned {
+        Ok(_) => Some(sender),
+        Err(err) => {
+            error!("Error starting the PNG writer thread: {err}");
+            None
+        }
+    }
+}
+
+/// Web builds can't start threads, so PNGs are always saved straight away.
+#[cfg(target_arch = "wasm32")]
+fn spawn_png_writer() -> Option<PngWriter> {
+    None
+}
+
+/// Starts a thread which encodes the frames sent to it into an animated GIF.
+#[cfg(not(target_arch = "wasm32"))]
+fn spawn_gif_writer(path: PathBuf, delay: u16) -> std::io::Result<SyncSender<FrameCapture>> {
+    let (sender, receiver) = std::sync::mpsc::sync_channel(CAPTURE_QUEUE_SIZE);
+    std::thread::Builder::new()
+        .name("GIF Writer".to_string())
+        .spawn(move || {
+            if let Err(err) = write_gif(&path, delay, &receiver) {
+                error!("Error writing recording {}: {err}", path.display());
+            }
+        })?;
+    Ok(sender)
+}
+
+/// GIFs are encoded while the recording runs, which needs a thread that web builds can't start.
+#[cfg(target_arch = "wasm32")]
+fn spawn_gif_writer(_path: PathBuf, _delay: u16) -> std::io::Result<SyncSender<FrameCapture>> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "GIF recordings are not supported on the web",
+    ))
+}
+
+/// Encodes frames into an animated GIF until the recording finishes and the sender is dropped.
+#[cfg(not(target_arch = "wasm32"))]
+fn write_gif(
+    path: &Path,
+    delay: u16,
+    frames: &std::sync::mpsc::Receiver<FrameCapture>,
+) -> std::io::Result<()> {
+    let Ok(first) = frames.recv() else {
+        return Ok(());
+    };
+    let size = |value: u32| {
+        u16::try_from(value).map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Frames are too large to encode as a GIF",
+            )
+        })
+    };
+    let (width, height) = (size(first.width)?, size(first.height)?);
+
+    let mut encoder = gif::Encoder::new(BufWriter::new(File::create(path)?), width, height, &[])
+        .map_err(std::io::Error::other)?;
+    encoder
+        .set_repeat(gif::Repeat::Infinite)
+        .map_err(std::io::Error::other)?;
+    for mut capture in std::iter::once(first).chain(frames.iter()) {
+        if (capture.width, capture.height) != (u32::from(width), u32::from(height)) {
+            // The window was resized during the recording, which GIFs can't represent.
+            continue;
+        }
+        let mut frame =
+            gif::Frame::from_rgba_speed(width, height, &mut capture.data, GIF_QUANTIZE_SPEED);
+        frame.delay = delay;
+        encoder.write_frame(&frame).map_err(std::io::Error::other)?;
+    }
+    info!("Saved recording to {}", path.display());
+    Ok(())
+}
+
+/// Queues the frames to capture this frame, from [`Screenshot`] and [`Recording`] requests.
+pub(crate) fn request_captures(
+    mut screenshots: EventReader<Screenshot>,
+    mut recordings: EventReader<Recording>,
+    mut recorder: ResMut<FrameRecorder>,
+    render_state: Res<RenderState>,
+    time: Res<Time>,
+) {
+    let recorder = &mut *recorder;
+    recorder.destinations.extend(
+        screenshots
+            .iter()
+            .map(|screenshot| CaptureDestination::Png(screenshot.path.clone())),
+    );
+
+    let mut delta_seconds = time.delta_seconds();
+    if let Some(recording) = recordings.iter().last() {
+        match ActiveRecording::start(recording) {
+            Ok(active) => {
+                info!("Started recording to {}", recording.path.display());
+                recorder.recording = Some(active);
+                // Capture the first frame of the recording straight away.
+                delta_seconds = 0.0;
+            }
+            Err(err) => error!(
+                "Error starting recording to {}: {err}",
+                recording.path.display()
+            ),
+        }
+    }
+    if let Some(recording) = &mut recorder.recording {
+        if recording.remaining <= 0.0 {
+            // Dropping the recording closes any GIF once its last frames have been written.
+            recorder.recording = None;
+        } else if let Some(destination) = recording.advance(delta_seconds) {
+            recorder.destinations.push(destination);
+        }
+    }
+
+    recorder.prepare_target(&render_state.device, &render_state.config);
+}
+
+/// Writes out any captured frames which have finished reading back from the GPU.
+pub(crate) fn write_captures(mut recorder: ResMut<FrameRecorder>, render_state: Res<RenderState>) {
+    if recorder.pending.is_empty() {
+        return;
+    }
+    render_state.device.poll(wgpu::Maintain::Poll);
+    recorder.write_finished();
+}
+
+/// The number of bytes per row needed to copy a texture of the given width into a buffer.
+#[must_use]
+pub(crate) fn padded_bytes_per_row(width: u32) -> u32 {
+    let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    (width * 4).div_ceil(align) * align
+}
+
+/// Creates a mappable buffer and records a copy of the given texture into it.
+///
+/// Returns the buffer along with the padded row size needed to read it back.
+pub(crate) fn copy_texture_to_buffer(
+    device: &wgpu::Device,
+    encoder: &mut wgpu::CommandEncoder,
+    texture: &wgpu::Texture,
+    width: u32,
+    height: u32,
+) -> (wgpu::Buffer, u32) {
+    let padded_bytes_per_row = padded_bytes_per_row(width);
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Frame Capture Buffer"),
+        size: wgpu::BufferAddress::from(padded_bytes_per_row * height),
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+
+    encoder.copy_texture_to_buffer(
+        wgpu::ImageCopyTexture {
+            texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        wgpu::ImageCopyBuffer {
+            buffer: &buffer,
+            layout: wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: NonZeroU32::new(padded_bytes_per_row),
+                rows_per_image: None,
+            },
+        },
+        wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+    );
+
+    (buffer, padded_bytes_per_row)
+}
+
+/// Copies the given texture back to the CPU, blocking until the GPU has finished.
+///
+/// Returns `None` if the buffer could not be mapped.
+pub(crate) fn read_texture(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+    width: u32,
+    height: u32,
+    format: wgpu::TextureFormat,
+) -> Option<FrameCapture> {
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Frame Capture Encoder"),
+    });
+    let (buffer, padded_bytes_per_row) =
+        copy_texture_to_buffer(device, &mut encoder, texture, width, height);
+    queue.submit(std::iter::once(encoder.finish()));
+
+    let slice = buffer.slice(..);
+    let (sender, receiver) = std::sync::mpsc::channel();
+    slice.map_async(wgpu::MapMode::Read, move |result| {
+        // The receiver is only dropped if this function has already returned.
+        let _ = sender.send(result);
+    });
+    device.poll(wgpu::Maintain::Wait);
+    receiver.recv().ok()?.ok()?;
+
+    let mapped = slice.get_mapped_range();
+    let capture = FrameCapture::from_padded(&mapped, width, height, padded_bytes_per_row, format);
+    drop(mapped);
+    buffer.unmap();
+
+    Some(capture)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::{
+        gif_delay, padded_bytes_per_row, ActiveRecording, CaptureDestination, FrameCapture,
+        RecordingSink,
+    };
+
+    #[test]
+    fn test_padded_bytes_per_row() {
+        assert_eq!(padded_bytes_per_row(1), 256);
+        assert_eq!(padded_bytes_per_row(64), 256);
+        assert_eq!(padded_bytes_per_row(65), 512);
+    }
+
+    #[test]
+    fn test_from_padded() {
+        let mut padded = vec![0_u8; 512];
+        padded[..8].copy_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8]);
+        padded[256..264].copy_from_slice(&[9, 10, 11, 12, 13, 14, 15, 16]);
+
+        let rgba =
+            FrameCapture::from_padded(&padded, 2, 2, 256, wgpu::TextureFormat::Rgba8UnormSrgb);
+        assert_eq!(rgba.pixels().len(), 16);
+        assert_eq!(rgba.pixel(1, 0), [5, 6, 7, 8]);
+        assert_eq!(rgba.pixel(0, 1), [9, 10, 11, 12]);
+
+        let bgra =
+            FrameCapture::from_padded(&padded, 2, 2, 256, wgpu::TextureFormat::Bgra8UnormSrgb);
+        assert_eq!(bgra.pixel(0, 0), [3, 2, 1, 4]);
+    }
+
+    #[test]
+    fn test_gif_delay() {
+        assert_eq!(gif_delay(30.0), 3);
+        assert_eq!(gif_delay(10.0), 10);
+        // GIFs can't show frames faster than a hundredth of a second.
+        assert_eq!(gif_delay(500.0), 1);
+    }
+
+    #[test]
+    fn test_recording_schedule() {
+        let mut recording = ActiveRecording {
+            sink: RecordingSink::Frames {
+                directory: PathBuf::from("frames"),
+                next_frame: 0,
+            },
+            remaining: 1.0,
+            interval: 0.25,
+            until_next: 0.0,
+        };
+        // The first frame is captured straight away, then every other frame after it.
+        let mut captured = Vec::new();
+        for delta_seconds in [0.0, 0.125, 0.125, 0.125, 0.125] {
+            if let Some(CaptureDestination::Png(path)) = recording.advance(delta_seconds) {
+                captured.push(path);
+            }
+        }
+        assert_eq!(
+            captured,
+            [
+                PathBuf::from("frames/frame_00000.png"),
+                PathBuf::from("frames/frame_00001.png"),
+                PathBuf::from("frames/frame_00002.png")
+            ]
+        );
+        assert!((recording.remaining - 0.5).abs() < f32::EPSILON);
+    }
+}
@@ -12,12 +12,22 @@ use bevy_ecs::{
     prelude::{Component, Entity},
     query::{Added, Changed, Or, With, Without},
     schedule::SystemSet,
-    system::{Commands, Query, Res},
+    system::{Commands, Query, Res, ResMut},
 };
-use glam::Vec2;
+use glam::{Vec2, Vec4};
 use hashers::fx_hash::FxHasher;
 
-use crate::{instance::Instance2D, util::FxHashMap};
+use crate::{
+    debug_draw::DebugDraw,
+    instance::Instance2D,
+    shape_registry::ShapeRegistry,
+    util::{FxHashMap, FxHashSet},
+};
+
+/// The color bounding boxes are drawn in when debug drawing collisions.
+const AABB_COLOR: Vec4 = Vec4::new(0.2, 1.0, 0.2, 1.0);
+/// The color hash grid cells are drawn in when debug drawing collisions.
+const HASH_CELL_COLOR: Vec4 = Vec4::new(1.0, 0.6, 0.1, 0.5);
 
 /// A marker indicating the entity can be collided with and should
 /// have it's hash grid status computed.
@@ -103,7 +113,7 @@ pub struct HashGrid {
 /// This is updated in the `pre_update` phase of each frame, thus its value will be based
 /// on where an entity was at the start of the frame.
 #[derive(Component)]
-struct HashMarker((i32, i32));
+pub(crate) struct HashMarker((i32, i32));
 
 impl HashMarker {
     /// Returns a list of hash grid cell identifiers with the current cell as well as all immediately surrounding cells.
@@ -164,32 +174,47 @@ pub struct Colliding<T>(pub Vec<Entity>, PhantomData<T>);
 
 type HashGridCoord = (i32, i32);
 
+/// The lowest and highest corners of a bounding box in world space.
+type Aabb = (Vec2, Vec2);
+
+/// Returns the world space bounding box of an instance, or `None` if its shape was removed.
+fn instance_aabb(inst: &Instance2D, shape_registry: &ShapeRegistry) -> Option<Aabb> {
+    let bounds = shape_registry.get_bounds(inst.shape)?;
+    Some(bounds.world_aabb(inst.position, inst.rotation, inst.scale))
+}
+
 fn collisions<T>(
     collider_query: Query<(Entity, &Instance2D, &HashMarker), With<Collider<T>>>,
     collide_with_query: Query<(Entity, &Instance2D, &HashMarker), With<CollidesWith<T>>>,
     hash_grid: Res<HashGrid>,
+    shape_registry: Res<ShapeRegistry>,
     mut commands: Commands,
 ) where
     T: Send + Sync + 'static,
 {
-    let mut m: FxHashMap<HashGridCoord, Vec<(Entity, Instance2D)>> =
+    let mut m: FxHashMap<HashGridCoord, Vec<(Entity, Aabb)>> =
         HashMap::with_capacity_and_hasher(1000, BuildHasherDefault::<FxHasher>::default());
     for (entity, inst, hash_marker) in &collide_with_query {
+        let Some(aabb) = instance_aabb(inst, &shape_registry) else {
+            continue;
+        };
         m.entry(hash_marker.0)
-            .and_modify(|v| v.push((entity, *inst)))
-            .or_insert_with(|| Vec::from([(entity, *inst)]));
+            .and_modify(|v| v.push((entity, aabb)))
+            .or_insert_with(|| Vec::from([(entity, aabb)]));
     }
 
     for (entity, inst, hash_marker) in &collider_query {
         let mut collisions = Vec::new();
-        for marker in hash_marker.get_with_neighbors(hash_grid.size) {
-            if let Some(possible_collisions) = m.get(&marker) {
-                collisions.extend(
-                    possible_collisions
-                        .iter()
-                        .filter(|(_entity, inst_b)| overlapping(inst, inst_b))
-                        .map(|(entity, _)| *entity),
-                );
+        if let Some(aabb) = instance_aabb(inst, &shape_registry) {
+            for marker in hash_marker.get_with_neighbors(hash_grid.size) {
+                if let Some(possible_collisions) = m.get(&marker) {
+                    collisions.extend(
+                        possible_collisions
+                            .iter()
+                            .filter(|(_entity, aabb_b)| overlapping(aabb, *aabb_b))
+                            .map(|(entity, _)| *entity),
+                    );
+                }
             }
         }
         if collisions.is_empty() {
@@ -224,24 +249,43 @@ where
         .after("collision_update")
 }
 
-/// Given 2 instances, determine if they are overlapping.
+/// Draws the bounding box of each [`Collidable`] entity and the hash grid cell it's in, when
+/// [`DebugDraw::show_collisions`] is enabled.
+pub(crate) fn draw_collision_debug(
+    collidable_query: Query<(&Instance2D, Option<&HashMarker>), With<Collidable>>,
+    hash_grid: Option<Res<HashGrid>>,
+    shape_registry: Res<ShapeRegistry>,
+    mut debug_draw: ResMut<DebugDraw>,
+) {
+    if !debug_draw.show_collisions {
+        return;
+    }
+
+    let mut cells = FxHashSet::default();
+    for (inst, hash_marker) in &collidable_query {
+        if let Some((min, max)) = instance_aabb(inst, &shape_registry) {
+            debug_draw.rect((min + max) / 2.0, max - min, AABB_COLOR);
+        }
+        if let Some(hash_marker) = hash_marker {
+            cells.insert(hash_marker.0);
+        }
+    }
+    if let Some(hash_grid) = hash_grid {
+        #[allow(clippy::cast_precision_loss)]
+        let size = Vec2::splat(hash_grid.size as f32);
+        for (x, y) in cells {
+            #[allow(clippy::cast_precision_loss)]
+            debug_draw.rect(Vec2::new(x as f32, y as f32), size, HASH_CELL_COLOR);
+        }
+    }
+}
+
+/// Given the world space bounding boxes of 2 instances, determine if they are overlapping.
 ///
-/// This computes a bounding box for each instance that is `instance.scale.x` wide and `instance.scale.y` high.
-/// It currently does not account for rotation, and assumes that the shape vertices are normalized to coordinates
-/// between -1.0 and 1.0 on both axes.
-#[allow(clippy::similar_names)]
-fn overlapping(a: &Instance2D, b: &Instance2D) -> bool {
-    let a_x1 = a.position.x - a.scale.x / 2.0;
-    let a_x2 = a.position.x + a.scale.x / 2.0;
-    let b_x1 = b.position.x - b.scale.x / 2.0;
-    let b_x2 = b.position.x + b.scale.x / 2.0;
-
-    let a_y1 = a.position.y - a.scale.y / 2.0;
-    let a_y2 = a.position.y + a.scale.y / 2.0;
-    let b_y1 = b.position.y - b.scale.y / 2.0;
-    let b_y2 = b.position.y + b.scale.y / 2.0;
-
-    a_x1 < b_x2 && a_x2 > b_x1 && a_y2 > b_y1 && a_y1 < b_y2
+/// The boxes come from the bounds the [`ShapeRegistry`] computed for each shape, so they account
+/// for rotation and for shapes which aren't centered on their origin.
+fn overlapping((a_min, a_max): Aabb, (b_min, b_max): Aabb) -> bool {
+    a_min.x < b_max.x && a_max.x > b_min.x && a_max.y > b_min.y && a_min.y < b_max.y
 }
 
 trait HashGridVec {
@@ -270,11 +314,24 @@ fn round_to_nearest(i: f32, incr: i32) -> i32 {
 
 #[cfg(test)]
 mod tests {
+    use std::f32::consts::FRAC_PI_4;
+
     use glam::{Vec2, Vec4};
 
-    use crate::instance::Instance2D;
+    use crate::{instance::Instance2D, shape::ShapeBounds};
+
+    use super::{overlapping, round_to_nearest, Aabb, HashGridVec, HashMarker};
 
-    use super::{overlapping, round_to_nearest, HashGridVec, HashMarker};
+    /// The bounds of the built-in square.
+    const SQUARE: ShapeBounds = ShapeBounds {
+        min: Vec2::splat(-0.5),
+        max: Vec2::splat(0.5),
+        radius: std::f32::consts::FRAC_1_SQRT_2,
+    };
+
+    fn aabb(inst: &Instance2D, bounds: &ShapeBounds) -> Aabb {
+        bounds.world_aabb(inst.position, inst.rotation, inst.scale)
+    }
 
     #[test]
     fn test_round_to_nearest() {
@@ -321,8 +378,32 @@ mod tests {
             outline: None,
         };
 
-        assert!(overlapping(&a, &b));
-        assert!(!overlapping(&a, &c));
+        assert!(overlapping(aabb(&a, &SQUARE), aabb(&b, &SQUARE)));
+        assert!(!overlapping(aabb(&a, &SQUARE), aabb(&c, &SQUARE)));
+    }
+
+    #[test]
+    fn test_overlapping_rotated_and_off_center() {
+        let a = Instance2D {
+            scale: Vec2::splat(10.0),
+            ..Default::default()
+        };
+        let mut b = Instance2D {
+            position: Vec2::new(12.0, 0.0),
+            scale: Vec2::splat(10.0),
+            ..Default::default()
+        };
+        assert!(!overlapping(aabb(&a, &SQUARE), aabb(&b, &SQUARE)));
+        // The corners of a square turned by 45 degrees reach over 7 units from its center.
+        b.rotation = FRAC_PI_4;
+        assert!(overlapping(aabb(&a, &SQUARE), aabb(&b, &SQUARE)));
+
+        // A shape whose vertices lie to the left of its origin reaches back towards `a`.
+        let left = ShapeBounds::from_points(&[Vec2::new(-1.0, -0.1), Vec2::new(-0.2, 0.1)]);
+        b.rotation = 0.0;
+        assert!(overlapping(aabb(&a, &SQUARE), aabb(&b, &left)));
+        b.rotation = FRAC_PI_4 * 4.0;
+        assert!(!overlapping(aabb(&a, &SQUARE), aabb(&b, &left)));
     }
 
     #[test]
@@ -0,0 +1,169 @@
+use std::{f32::consts::PI, ops::Range};
+
+use glam::{Vec2, Vec4};
+
+/// The number of segments used to draw a circle.
+const CIRCLE_SEGMENTS: usize = 32;
+/// The length of an arrow's head, relative to the length of the arrow.
+const ARROW_HEAD_LENGTH: f32 = 0.25;
+/// The angle between an arrow's head and its shaft.
+const ARROW_HEAD_ANGLE: f32 = PI / 6.0;
+
+/// A path added to the [`DebugDraw`] this frame.
+#[derive(Debug, Clone)]
+struct DebugPath {
+    points: Range<usize>,
+    closed: bool,
+    color: Vec4,
+}
+
+/// A resource for drawing debug shapes, such as collision boxes or velocities, without spawning
+/// any entities.
+///
+/// Shapes are drawn as thin lines in world space, on top of everything else and after any
+/// post-processing. Everything added is drawn once, at the end of the frame, and then cleared, so
+/// systems should add their shapes every frame they should be visible.
+///
+/// ## Example
+/// ```
+/// # use bevy_ecs::system::ResMut;
+/// # use libprim::{debug_draw::DebugDraw, Vec2, Vec4};
+/// fn draw_origin(mut debug_draw: ResMut<DebugDraw>) {
+///     debug_draw.arrow(Vec2::ZERO, Vec2::new(50.0, 0.0), Vec4::new(1.0, 0.0, 0.0, 1.0));
+///     debug_draw.arrow(Vec2::ZERO, Vec2::new(0.0, 50.0), Vec4::new(0.0, 1.0, 0.0, 1.0));
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct DebugDraw {
+    /// The width of the lines in screen pixels.
+    pub line_width: f32,
+    /// Whether to draw the bounding box of each [`libprim::collision::Collidable`] entity, and the
+    /// hash grid cell it's in.
+    pub show_collisions: bool,
+    points: Vec<Vec2>,
+    paths: Vec<DebugPath>,
+}
+
+impl Default for DebugDraw {
+    fn default() -> Self {
+        Self {
+            line_width: 1.0,
+            show_collisions: false,
+            points: Vec::new(),
+            paths: Vec::new(),
+        }
+    }
+}
+
+impl DebugDraw {
+    /// Draws a line between two points.
+    pub fn line(&mut self, start: Vec2, end: Vec2, color: Vec4) {
+        self.path([start, end], false, color);
+    }
+
+    /// Draws the outline of a rectangle.
+    pub fn rect(&mut self, center: Vec2, size: Vec2, color: Vec4) {
+        let half = size / 2.0;
+        self.path(
+            [
+                center - half,
+                center + Vec2::new(half.x, -half.y),
+                center + half,
+                center + Vec2::new(-half.x, half.y),
+            ],
+            true,
+            color,
+        );
+    }
+
+    /// Draws the outline of a circle.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn circle(&mut self, center: Vec2, radius: f32, color: Vec4) {
+        self.path(
+            (0..CIRCLE_SEGMENTS).map(|i| {
+                let angle = i as f32 / CIRCLE_SEGMENTS as f32 * PI * 2.0;
+                center + Vec2::from_angle(angle) * radius
+            }),
+            true,
+            color,
+        );
+    }
+
+    /// Draws an arrow from `start`, with its head at `end`.
+    pub fn arrow(&mut self, start: Vec2, end: Vec2, color: Vec4) {
+        self.line(start, end, color);
+        let back = (start - end) * ARROW_HEAD_LENGTH;
+        self.path(
+            [
+                end + Vec2::from_angle(ARROW_HEAD_ANGLE).rotate(back),
+                end,
+                end + Vec2::from_angle(-ARROW_HEAD_ANGLE).rotate(back),
+            ],
+            false,
+            color,
+        );
+    }
+
+    /// Adds a path through the given points.
+    fn path(&mut self, points: impl IntoIterator<Item = Vec2>, closed: bool, color: Vec4) {
+        let start = self.points.len();
+        self.points.extend(points);
+        self.paths.push(DebugPath {
+            points: start..self.points.len(),
+            closed,
+            color,
+        });
+    }
+
+    /// Returns each path added this frame, with whether it's closed and its color.
+    pub(crate) fn paths(&self) -> impl Iterator<Item = (&[Vec2], bool, Vec4)> {
+        self.paths
+            .iter()
+            .map(|path| (&self.points[path.points.clone()], path.closed, path.color))
+    }
+
+    /// Removes everything drawn this frame.
+    pub(crate) fn clear(&mut self) {
+        self.points.clear();
+        self.paths.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{Vec2, Vec4};
+
+    use super::DebugDraw;
+
+    #[test]
+    fn test_debug_paths() {
+        let mut debug_draw = DebugDraw::default();
+        debug_draw.rect(Vec2::ZERO, Vec2::new(4.0, 2.0), Vec4::ONE);
+        debug_draw.arrow(Vec2::ZERO, Vec2::new(4.0, 0.0), Vec4::ZERO);
+
+        let paths = debug_draw.paths().collect::<Vec<_>>();
+        assert_eq!(paths.len(), 3);
+        assert_eq!(
+            paths[0],
+            (
+                &[
+                    Vec2::new(-2.0, -1.0),
+                    Vec2::new(2.0, -1.0),
+                    Vec2::new(2.0, 1.0),
+                    Vec2::new(-2.0, 1.0)
+                ][..],
+                true,
+                Vec4::ONE
+            )
+        );
+        // The head of the arrow points back along its shaft from the end.
+        let (head, closed, _) = paths[2];
+        assert!(!closed);
+        assert_eq!(head[1], Vec2::new(4.0, 0.0));
+        assert!(head[0].x < 4.0 && head[0].y < 0.0);
+        assert!(head[2].x < 4.0 && head[2].y > 0.0);
+
+        debug_draw.clear();
+        assert_eq!(debug_draw.paths().count(), 0);
+    }
+}
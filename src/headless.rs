@@ -0,0 +1,65 @@
+use glam::Vec3;
+
+/// Options for creating a windowless [`libprim::state::State`] which renders into an offscreen
+/// texture.
+///
+/// Used with [`libprim::run_headless`] for automated tests and scripted screenshots.
+#[derive(Debug)]
+pub struct HeadlessOptions {
+    /// The size of the offscreen frame in pixels.
+    pub size: (u32, u32),
+
+    /// The default background color when nothing else is drawn.
+    pub clear_color: Vec3,
+
+    /// Anti-aliasing sample count. Currently supports either `1` or `4`.
+    ///
+    /// Defaults to `1` so that captured frames are as reproducible as possible between GPUs.
+    pub sample_count: u32,
+
+    /// Whether to request wgpu's fallback (software) adapter instead of a hardware one.
+    ///
+    /// Useful on CI machines which have no GPU.
+    pub force_fallback_adapter: bool,
+}
+
+impl Default for HeadlessOptions {
+    fn default() -> Self {
+        Self {
+            size: (1024, 768),
+            clear_color: Vec3::new(0.0, 0.0, 0.0),
+            sample_count: 1,
+            force_fallback_adapter: false,
+        }
+    }
+}
+
+impl HeadlessOptions {
+    /// Sets the size of the offscreen frame.
+    #[must_use]
+    pub fn with_size(mut self, size: (u32, u32)) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Sets the background color which will show in spaces where nothing is drawn.
+    #[must_use]
+    pub fn with_clear_color(mut self, clear_color: Vec3) -> Self {
+        self.clear_color = clear_color;
+        self
+    }
+
+    /// Sets the MSAA multi-sampling count. Not supported by all hardware.
+    #[must_use]
+    pub fn with_sample_count(mut self, sample_count: u32) -> Self {
+        self.sample_count = sample_count;
+        self
+    }
+
+    /// Requests the fallback (software) adapter rather than a hardware adapter.
+    #[must_use]
+    pub fn with_fallback_adapter(mut self, force_fallback_adapter: bool) -> Self {
+        self.force_fallback_adapter = force_fallback_adapter;
+        self
+    }
+}
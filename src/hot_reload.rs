@@ -0,0 +1,260 @@
+use std::{
+    io::Result,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use bevy_ecs::system::{Res, ResMut};
+use log::{error, info};
+
+use crate::{
+    particle_system::effects::{InitializeParticleEffectFile, ParticleEffects},
+    shape::InitializeShape,
+    shape_file::InitializeShapeFile,
+    shape_registry::ShapeRegistry,
+    svg::InitializeSvg,
+    time::Time,
+    util::AssetSource,
+};
+
+/// How often watched files are checked for changes by default, in seconds.
+pub const DEFAULT_POLL_INTERVAL: f32 = 0.5;
+
+/// A resource which reloads asset files when they change on disk, so shapes and particle effects
+/// can be tweaked without restarting the game.
+///
+/// Every shape file, SVG and particle effect file loaded from a path by an initializer is watched.
+/// When one changes, its shapes replace the geometry of the shapes with the same names in the
+/// [`ShapeRegistry`], keeping their IDs, and shapes with new names are added. Shapes which were
+/// removed from the file are left in the registry. Reloaded particle effects are swapped into
+/// every running [`crate::particle_system::components::ParticleSystem`] spawned from them. A file
+/// which fails to load is logged, and the last version which loaded keeps being used.
+///
+/// Files are checked by polling their modification times, which works the same on every desktop
+/// platform.
+#[derive(Debug)]
+pub struct HotReload {
+    /// How often to check watched files for changes, in seconds.
+    pub poll_interval: f32,
+    files: Vec<WatchedFile>,
+    since_poll: f32,
+}
+
+impl Default for HotReload {
+    fn default() -> Self {
+        Self {
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            files: Vec::new(),
+            since_poll: 0.0,
+        }
+    }
+}
+
+/// An asset file loaded by an initializer, which can be loaded again.
+#[derive(Debug, Clone)]
+pub(crate) enum WatchedAsset {
+    ShapeFile(InitializeShapeFile),
+    Svg(InitializeSvg),
+    ParticleEffectFile(InitializeParticleEffectFile),
+}
+
+impl WatchedAsset {
+    fn source(&self) -> &AssetSource {
+        match self {
+            Self::ShapeFile(shape_file) => &shape_file.source,
+            Self::Svg(svg) => &svg.source,
+            Self::ParticleEffectFile(effect_file) => &effect_file.source,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct WatchedFile {
+    asset: WatchedAsset,
+    path: PathBuf,
+    modified: Option<SystemTime>,
+}
+
+impl HotReload {
+    /// Starts watching the file an asset was loaded from. Assets loaded from text are ignored.
+    pub(crate) fn watch(&mut self, asset: WatchedAsset) {
+        let AssetSource::Path(path) = asset.source() else {
+            return;
+        };
+        let path = path.clone();
+        self.files.push(WatchedFile {
+            modified: modified_time(&path),
+            asset,
+            path,
+        });
+    }
+
+    /// The paths of every watched file.
+    pub fn watched_paths(&self) -> impl Iterator<Item = &PathBuf> {
+        self.files.iter().map(|file| &file.path)
+    }
+
+    /// Reloads every watched file which has changed, once the poll interval has passed.
+    fn poll(
+        &mut self,
+        delta_seconds: f32,
+        shape_registry: &mut ShapeRegistry,
+        effects: &mut ParticleEffects,
+    ) {
+        self.since_poll += delta_seconds;
+        if self.since_poll < self.poll_interval {
+            return;
+        }
+        self.since_poll = 0.0;
+
+        for file in &mut self.files {
+            let modified = modified_time(&file.path);
+            if modified == file.modified {
+                continue;
+            }
+            file.modified = modified;
+            // A file may briefly go missing while an editor saves it, so wait for it to return.
+            if modified.is_none() {
+                continue;
+            }
+            let path = file.path.display().to_string();
+            match &file.asset {
+                WatchedAsset::ShapeFile(shape_file) => {
+                    reload_shapes(shape_file.load(), shape_registry, &path);
+                }
+                WatchedAsset::Svg(svg) => {
+                    reload_shapes(svg.load(), shape_registry, &path);
+                }
+                WatchedAsset::ParticleEffectFile(effect_file) => match effect_file.load() {
+                    Ok(definitions) => {
+                        effects.insert_definitions(definitions, shape_registry, &path);
+                        info!("Reloaded {path}");
+                    }
+                    Err(err) => error!("Error reloading {path}: {err}"),
+                },
+            }
+        }
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Replaces the shapes with the same names as the reloaded ones, and adds any new shapes.
+fn reload_shapes(
+    shapes: Result<Vec<InitializeShape>>,
+    shape_registry: &mut ShapeRegistry,
+    path: &str,
+) {
+    let shapes = match shapes {
+        Ok(shapes) => shapes,
+        Err(err) => {
+            error!("Error reloading {path}: {err}");
+            return;
+        }
+    };
+    for shape in shapes {
+        let result = if shape_registry.get_id(&shape.name).is_some() {
+            shape_registry.replace_shape(shape)
+        } else {
+            shape_registry.add_shape(shape)
+        };
+        if let Err(err) = result {
+            error!("Error reloading {path}: {err}");
+        }
+    }
+    info!("Reloaded {path}");
+}
+
+pub(crate) fn reload_changed_assets(
+    time: Res<Time>,
+    mut hot_reload: ResMut<HotReload>,
+    mut shape_registry: ResMut<ShapeRegistry>,
+    mut effects: ResMut<ParticleEffects>,
+) {
+    hot_reload.poll(time.delta_seconds(), &mut shape_registry, &mut effects);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{HotReload, WatchedAsset};
+    use crate::{
+        particle_system::effects::{InitializeParticleEffectFile, ParticleEffects},
+        shape_file::InitializeShapeFile,
+        shape_registry::ShapeRegistry,
+    };
+
+    const TRIANGLE: &str = "vertices: [(0.0, 0.5), (-0.5, -0.5), (0.5, -0.5)]";
+    const SQUARE: &str = "vertices: [(-0.5, -0.5), (0.5, -0.5), (0.5, 0.5), (-0.5, 0.5)]";
+
+    #[test]
+    fn test_reload_changed_files() {
+        let dir = std::env::temp_dir().join(format!("prim-hot-reload-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let shape_path = dir.join("shapes.ron");
+        let effect_path = dir.join("effects.ron");
+        fs::write(
+            &shape_path,
+            format!("ShapeFile(shapes: [(name: \"A\", {TRIANGLE})])"),
+        )
+        .unwrap();
+        fs::write(
+            &effect_path,
+            "ParticleEffectFile(effects: [(name: \"E\", shape: \"A\", max_particles: 1)])",
+        )
+        .unwrap();
+
+        let mut shape_registry = ShapeRegistry::new();
+        let a = shape_registry
+            .add_shape(
+                InitializeShapeFile::new(&shape_path)
+                    .load()
+                    .unwrap()
+                    .remove(0),
+            )
+            .unwrap();
+        let mut effects = ParticleEffects::default();
+        let mut hot_reload = HotReload::default();
+        hot_reload.watch(WatchedAsset::ShapeFile(InitializeShapeFile::new(
+            &shape_path,
+        )));
+        hot_reload.watch(WatchedAsset::ParticleEffectFile(
+            InitializeParticleEffectFile::new(&effect_path),
+        ));
+        hot_reload.watch(WatchedAsset::ShapeFile(InitializeShapeFile::from_text(
+            String::new(),
+        )));
+        assert_eq!(hot_reload.watched_paths().count(), 2);
+
+        fs::write(
+            &shape_path,
+            format!("ShapeFile(shapes: [(name: \"A\", {SQUARE}), (name: \"B\", {TRIANGLE})])"),
+        )
+        .unwrap();
+        // File times can be too coarse to change between writes, so forget the last seen times.
+        for file in &mut hot_reload.files {
+            file.modified = None;
+        }
+        // Nothing is checked until the poll interval has passed.
+        hot_reload.poll(0.0, &mut shape_registry, &mut effects);
+        assert_eq!(shape_registry.get_id("B"), None);
+        hot_reload.poll(1.0, &mut shape_registry, &mut effects);
+
+        assert_eq!(shape_registry.get_id("A"), Some(a));
+        assert!(shape_registry.get_id("B").is_some());
+        assert_eq!(effects.get("E").unwrap().shape_id, a);
+
+        // A broken file keeps the last version which loaded.
+        fs::write(&shape_path, "ShapeFile(").unwrap();
+        hot_reload.files[0].modified = None;
+        hot_reload.poll(1.0, &mut shape_registry, &mut effects);
+        assert_eq!(shape_registry.get_id("A"), Some(a));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,6 +1,10 @@
 use std::collections::VecDeque;
 
-use crate::{camera::InitializeCamera, shape::InitializeShape, text::InitializeFont};
+use crate::{
+    camera::InitializeCamera, material::InitializeMaterial,
+    particle_system::effects::InitializeParticleEffectFile, shape::InitializeShape,
+    shape_file::InitializeShapeFile, svg::InitializeSvg, text::InitializeFont,
+};
 
 /// The set of initialization commands to load or create assets in the initialization phase.
 ///
@@ -11,8 +15,17 @@ pub enum InitializeCommand {
     InitializeFont(InitializeFont),
     /// Used to load a new shape into the [`libprim::shape_registry::ShapeRegistry`]
     InitializeShape(InitializeShape),
+    /// Used to load every shape in a shape file into the [`libprim::shape_registry::ShapeRegistry`]
+    InitializeShapeFile(InitializeShapeFile),
+    /// Used to import the shapes of an SVG into the [`libprim::shape_registry::ShapeRegistry`]
+    InitializeSvg(InitializeSvg),
+    /// Used to load every effect in a particle effect file into the
+    /// [`libprim::particle_system::effects::ParticleEffects`]
+    InitializeParticleEffectFile(InitializeParticleEffectFile),
     /// Used to setup a non-default camera.
     InitializeCamera(InitializeCamera),
+    /// Used to compile a new material into the [`libprim::material::MaterialRegistry`]
+    InitializeMaterial(InitializeMaterial),
 }
 
 #[derive(Default)]
@@ -1,6 +1,11 @@
 use bevy_ecs::prelude::{Bundle, Component};
 use glam::{Mat3, Mat4, Vec2, Vec4};
 
+use crate::{
+    shape::ShapeBounds,
+    stroke::{stroke_path, LineCap, LineJoin, StrokeStyle, StrokeUnits, MITER_LIMIT},
+};
+
 /// An [`Instance2D`] defines the core of a renderable object.
 ///
 /// Anything with this [`Component`] will be rendered on screen.
@@ -39,8 +44,7 @@ impl Instance2D {
     #[must_use]
     pub(crate) fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
         wgpu::VertexBufferLayout {
-            array_stride: (std::mem::size_of::<Mat4>() + std::mem::size_of::<Vec4>())
-                as wgpu::BufferAddress,
+            array_stride: std::mem::size_of::<Inst>() as wgpu::BufferAddress,
             step_mode: wgpu::VertexStepMode::Instance,
             attributes: &[
                 wgpu::VertexAttribute {
@@ -68,6 +72,36 @@ impl Instance2D {
                     shader_location: 9,
                     format: wgpu::VertexFormat::Float32x4,
                 },
+                wgpu::VertexAttribute {
+                    offset: (std::mem::size_of::<Vec4>() * 5) as wgpu::BufferAddress,
+                    shader_location: 11,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: (std::mem::size_of::<Vec4>() * 6) as wgpu::BufferAddress,
+                    shader_location: 12,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: (std::mem::size_of::<Vec4>() * 7) as wgpu::BufferAddress,
+                    shader_location: 13,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: (std::mem::size_of::<Vec4>() * 8) as wgpu::BufferAddress,
+                    shader_location: 10,
+                    format: wgpu::VertexFormat::Uint32,
+                },
+                wgpu::VertexAttribute {
+                    offset: (std::mem::size_of::<Vec4>() * 9) as wgpu::BufferAddress,
+                    shader_location: 14,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: (std::mem::size_of::<Vec4>() * 10) as wgpu::BufferAddress,
+                    shader_location: 15,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
             ],
         }
     }
@@ -110,23 +144,91 @@ impl Instance2D {
                 self.position,
             )),
             color: self.color,
+            ..bytemuck::Zeroable::zeroed()
         }
     }
 
+    /// Returns the `Inst` the instance's outline is drawn with, if it has one.
+    ///
+    /// Outlines are built in world space, so the instance has no transform.
     #[inline(always)]
     #[must_use]
-    pub(crate) fn outline_matrix(&self) -> Option<Inst> {
-        self.outline.map(|outline| Inst {
-            transform: Mat4::from_mat3(Mat3::from_scale_angle_translation(
-                self.scale * 1.0 + outline.scale,
-                self.rotation,
-                self.position,
-            )),
-            color: outline.color,
-        })
+    pub(crate) fn outline_inst(&self) -> Option<Inst> {
+        self.outline.map(|outline| Inst::world_space(outline.color))
+    }
+
+    /// Returns the corners of a box containing the instance and its outline in world space, given
+    /// the bounds of its shape.
+    ///
+    /// `pixel_size` is the size of a screen pixel in world units.
+    #[must_use]
+    pub(crate) fn world_bounds(&self, bounds: &ShapeBounds, pixel_size: f32) -> (Vec2, Vec2) {
+        let (min, max) = bounds.world_aabb(self.position, self.rotation, self.scale);
+        // Outlines sit outside the shape, and their miter joins can reach further than their width.
+        let reach = self.outline.map_or(0.0, |outline| {
+            outline.units.to_world(outline.width, pixel_size) * MITER_LIMIT
+        });
+        (min - reach, max + reach)
+    }
+
+    /// Builds the triangles of the instance's outline in world space, around the given boundary
+    /// loops of its shape.
+    ///
+    /// `pixel_size` is the size of a screen pixel in world units.
+    pub(crate) fn outline_triangles(
+        &self,
+        boundary: &[Vec<Vec2>],
+        pixel_size: f32,
+        triangles: &mut Vec<Vec2>,
+    ) {
+        let Some(outline) = self.outline else {
+            return;
+        };
+
+        let transform =
+            Mat3::from_scale_angle_translation(self.scale, self.rotation, self.position);
+        // Mirroring the shape reverses the direction of its boundary, which would put the outline on
+        // the inside.
+        let mirrored = self.scale.x * self.scale.y < 0.0;
+        let width = outline.units.to_world(outline.width, pixel_size);
+        let style = StrokeStyle {
+            width,
+            offset: width / 2.0,
+            join: outline.join,
+            cap: LineCap::Butt,
+        };
+
+        let mut path = Vec::new();
+        for boundary_loop in boundary {
+            path.clear();
+            path.extend(boundary_loop.iter().map(|&p| transform.transform_point2(p)));
+            if mirrored {
+                path.reverse();
+            }
+            stroke_path(&path, true, &style, triangles);
+        }
     }
 }
 
+/// The render layer of an [`Instance2D`], controlling the order instances are drawn in.
+///
+/// Instances on higher layers are drawn on top of instances on lower layers. Instances without a
+/// [`Layer`] component are drawn on [`Layer::DEFAULT`]. Within a single layer, draw order follows
+/// ECS iteration order, or shape ID when renderable sorting is enabled.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Layer(pub i32);
+
+impl Layer {
+    /// A layer for backgrounds, drawn behind everything on the default layers.
+    pub const BACKGROUND: Layer = Layer(-100);
+    /// The layer used by instances which have no [`Layer`] component.
+    pub const DEFAULT: Layer = Layer(0);
+    /// The layer that particle systems spawn their particles on by default.
+    pub const PARTICLES: Layer = Layer(100);
+    /// A layer for effects that should be drawn over the game world.
+    pub const EFFECTS: Layer = Layer(200);
+}
+
 /// A serializable struct passed into the Instance buffer and sent to the GPU
 ///
 /// Holds the instances transformation matrix and any other info needed by the
@@ -136,6 +238,117 @@ impl Instance2D {
 pub(crate) struct Inst {
     transform: Mat4,
     color: Vec4,
+    gradient_start_color: Vec4,
+    gradient_end_color: Vec4,
+    /// Linear gradients: xy: start, zw: end. Radial gradients: xy: center, z: radius.
+    gradient: Vec4,
+    flags: u32,
+    _padding: [u32; 3],
+    /// The instance's [`InstanceData`].
+    data: Vec4,
+    /// The parameters of the instance's [`ShaderEffect`], selected by its flags.
+    effect: Vec4,
+}
+
+impl Inst {
+    /// The instance ignores lighting and is drawn with its exact color.
+    pub(crate) const UNLIT: u32 = 1 << 0;
+    /// The instance casts shadows, and so is never shadowed itself.
+    pub(crate) const SHADOW_CASTER: u32 = 1 << 1;
+    /// The instance is filled with a linear gradient.
+    pub(crate) const GRADIENT_LINEAR: u32 = 1 << 2;
+    /// The instance is filled with a radial gradient.
+    pub(crate) const GRADIENT_RADIAL: u32 = 1 << 3;
+    /// The instance's color pulses with [`ShaderEffect::Pulse`].
+    pub(crate) const EFFECT_PULSE: u32 = 1 << 4;
+    /// The instance's vertices wobble with [`ShaderEffect::Wobble`].
+    pub(crate) const EFFECT_WOBBLE: u32 = 1 << 5;
+    /// The instance is striped with [`ShaderEffect::Stripes`].
+    pub(crate) const EFFECT_STRIPES: u32 = 1 << 6;
+
+    /// Creates an instance of a single color with no transform, for geometry built in world space.
+    #[inline(always)]
+    #[must_use]
+    pub(crate) fn world_space(color: Vec4) -> Self {
+        Self {
+            transform: Mat4::IDENTITY,
+            color,
+            ..bytemuck::Zeroable::zeroed()
+        }
+    }
+
+    /// Returns a copy of the instance with the given render flags set.
+    #[inline(always)]
+    #[must_use]
+    pub(crate) fn with_flags(mut self, flags: u32) -> Self {
+        self.flags |= flags;
+        self
+    }
+
+    /// Returns a copy of the instance filled with the given gradient.
+    #[inline(always)]
+    #[must_use]
+    pub(crate) fn with_gradient(mut self, gradient: &Gradient) -> Self {
+        match *gradient {
+            Gradient::Linear {
+                start,
+                end,
+                start_color,
+                end_color,
+            } => {
+                self.flags |= Self::GRADIENT_LINEAR;
+                self.gradient = Vec4::new(start.x, start.y, end.x, end.y);
+                self.gradient_start_color = start_color;
+                self.gradient_end_color = end_color;
+            }
+            Gradient::Radial {
+                center,
+                radius,
+                inner_color,
+                outer_color,
+            } => {
+                self.flags |= Self::GRADIENT_RADIAL;
+                self.gradient = Vec4::new(center.x, center.y, radius, 0.0);
+                self.gradient_start_color = inner_color;
+                self.gradient_end_color = outer_color;
+            }
+        }
+        self
+    }
+
+    /// Returns a copy of the instance drawn with the given built-in effect.
+    #[inline(always)]
+    #[must_use]
+    pub(crate) fn with_effect(mut self, effect: &ShaderEffect) -> Self {
+        let (flag, parameters) = match *effect {
+            ShaderEffect::Pulse { frequency, amount } => {
+                (Self::EFFECT_PULSE, Vec4::new(frequency, amount, 0.0, 0.0))
+            }
+            ShaderEffect::Wobble {
+                amplitude,
+                frequency,
+            } => (
+                Self::EFFECT_WOBBLE,
+                Vec4::new(amplitude, frequency, 0.0, 0.0),
+            ),
+            ShaderEffect::Stripes {
+                count,
+                speed,
+                angle,
+            } => (Self::EFFECT_STRIPES, Vec4::new(count, speed, angle, 0.0)),
+        };
+        self.flags |= flag;
+        self.effect = parameters;
+        self
+    }
+
+    /// Returns a copy of the instance carrying the given shader data.
+    #[inline(always)]
+    #[must_use]
+    pub(crate) fn with_data(mut self, data: &InstanceData) -> Self {
+        self.data = data.0;
+        self
+    }
 }
 
 /// A bundle to add all the components necessary for an object to render on screen.
@@ -162,10 +375,259 @@ impl InstanceBundle {
 }
 
 /// Defines outline parameters for rendering shape outlines.
-#[derive(Debug, Clone, Copy)]
+///
+/// Outlines are drawn as a stroke of a fixed width around the outside of the shape, following any
+/// holes as well as its outer edge. The stroke follows the edges of the shape's triangles which
+/// aren't shared with another triangle, so triangles within a shape should share their vertices.
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Outline {
-    /// The size of the outline.
-    pub scale: f32,
+    /// The width of the outline, measured in `units`.
+    pub width: f32,
     /// The color of the outline.
     pub color: Vec4,
+    /// The units the width is measured in.
+    pub units: StrokeUnits,
+    /// How the corners of the outline are drawn.
+    pub join: LineJoin,
+    /// Whether to only draw the outline, leaving the inside of the shape empty.
+    pub outline_only: bool,
+}
+
+impl Default for Outline {
+    fn default() -> Self {
+        Self {
+            width: 1.0,
+            color: Vec4::ONE,
+            units: StrokeUnits::World,
+            join: LineJoin::Miter,
+            outline_only: false,
+        }
+    }
+}
+
+impl Outline {
+    /// Creates an outline of the given width in world units.
+    #[must_use]
+    pub fn new(width: f32, color: Vec4) -> Self {
+        Self {
+            width,
+            color,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the units the width of the outline is measured in.
+    #[must_use]
+    pub fn with_units(mut self, units: StrokeUnits) -> Self {
+        self.units = units;
+        self
+    }
+
+    /// Sets how the corners of the outline are drawn.
+    #[must_use]
+    pub fn with_join(mut self, join: LineJoin) -> Self {
+        self.join = join;
+        self
+    }
+
+    /// Sets whether only the outline is drawn, leaving the inside of the shape empty.
+    #[must_use]
+    pub fn with_outline_only(mut self, outline_only: bool) -> Self {
+        self.outline_only = outline_only;
+        self
+    }
+}
+
+/// Fills an [`Instance2D`] with a gradient rather than a single color.
+///
+/// Positions are given in the shape's own space, before the instance is scaled, rotated and
+/// moved, where the built-in shapes span from -0.5 to 0.5. The gradient colors are multiplied with
+/// the instance color, so a white instance shows the gradient exactly. Outlines are not affected.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub enum Gradient {
+    /// Blends between two colors along the line from `start` to `end`.
+    Linear {
+        /// Where the gradient starts, taking the `start_color`.
+        start: Vec2,
+        /// Where the gradient ends, taking the `end_color`.
+        end: Vec2,
+        /// The color at and before the start of the gradient.
+        start_color: Vec4,
+        /// The color at and after the end of the gradient.
+        end_color: Vec4,
+    },
+    /// Blends between two colors outwards from a center point.
+    Radial {
+        /// The center of the gradient, taking the `inner_color`.
+        center: Vec2,
+        /// The distance from the center at which the gradient reaches the `outer_color`.
+        radius: f32,
+        /// The color at the center.
+        inner_color: Vec4,
+        /// The color at and beyond the radius.
+        outer_color: Vec4,
+    },
+}
+
+impl Gradient {
+    /// A linear gradient from the top of the shape to the bottom.
+    #[must_use]
+    pub fn vertical(top: Vec4, bottom: Vec4) -> Self {
+        Self::Linear {
+            start: Vec2::new(0.0, 0.5),
+            end: Vec2::new(0.0, -0.5),
+            start_color: top,
+            end_color: bottom,
+        }
+    }
+
+    /// A linear gradient from the left of the shape to the right.
+    #[must_use]
+    pub fn horizontal(left: Vec4, right: Vec4) -> Self {
+        Self::Linear {
+            start: Vec2::new(-0.5, 0.0),
+            end: Vec2::new(0.5, 0.0),
+            start_color: left,
+            end_color: right,
+        }
+    }
+
+    /// A radial gradient from the center of the shape out to its edges.
+    #[must_use]
+    pub fn radial(inner: Vec4, outer: Vec4) -> Self {
+        Self::Radial {
+            center: Vec2::ZERO,
+            radius: 0.5,
+            inner_color: inner,
+            outer_color: outer,
+        }
+    }
+}
+
+/// Four floats passed to the shader of an [`Instance2D`], for effects driven per entity such as a
+/// dissolve amount, hit flash intensity or wave phase.
+///
+/// Materials read the values as `in.data`, a `vec4<f32>` of `VertexOutput`. What each value means
+/// is up to the material. Instances without this component, outlines and lines pass zeroes.
+///
+/// ## Example
+/// ```
+/// # use glam::Vec4;
+/// # use libprim::instance::InstanceData;
+/// // A material could flash white by mixing towards it with `in.data.x`.
+/// let hit_flash = InstanceData(Vec4::new(0.8, 0.0, 0.0, 0.0));
+/// ```
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq)]
+pub struct InstanceData(pub Vec4);
+
+/// A built-in effect which animates an [`Instance2D`] on the GPU, without any systems updating it.
+///
+/// Effects work with any material, and are driven by the time in the globals every shader can read
+/// as `globals.time`. Outlines are not affected.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub enum ShaderEffect {
+    /// Dims the instance's color and back, `frequency` times a second.
+    Pulse {
+        /// How many pulses happen each second.
+        frequency: f32,
+        /// How far the color dims at the bottom of each pulse, from 0 to 1.
+        amount: f32,
+    },
+    /// Moves the instance's vertices back and forth in waves, `frequency` times a second.
+    Wobble {
+        /// How far vertices move, in the shape's own space where the built-in shapes span from
+        /// -0.5 to 0.5.
+        amplitude: f32,
+        /// How many wobbles happen each second.
+        frequency: f32,
+    },
+    /// Darkens alternating stripes across the instance, which scroll over time.
+    Stripes {
+        /// How many pairs of stripes fit across one unit of the shape's own space.
+        count: f32,
+        /// How many stripes scroll past each second.
+        speed: f32,
+        /// The direction the stripes scroll in, in radians, where zero is to the right.
+        angle: f32,
+    },
+}
+
+impl ShaderEffect {
+    /// The furthest the effect moves a vertex of the shape, in the shape's own space.
+    #[must_use]
+    pub(crate) fn reach(&self) -> f32 {
+        match *self {
+            Self::Wobble { amplitude, .. } => amplitude.abs(),
+            Self::Pulse { .. } | Self::Stripes { .. } => 0.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{Vec2, Vec4};
+
+    use super::{Gradient, Inst, Instance2D, InstanceData, ShaderEffect};
+
+    #[test]
+    fn test_with_gradient() {
+        let inst = Instance2D::default().to_matrix();
+        assert_eq!(inst.flags, 0);
+
+        let linear = inst.with_gradient(&Gradient::horizontal(Vec4::X, Vec4::Y));
+        assert_eq!(linear.flags, Inst::GRADIENT_LINEAR);
+        assert_eq!(linear.gradient, Vec4::new(-0.5, 0.0, 0.5, 0.0));
+        assert_eq!(linear.gradient_start_color, Vec4::X);
+        assert_eq!(linear.gradient_end_color, Vec4::Y);
+
+        let radial = inst
+            .with_flags(Inst::UNLIT)
+            .with_gradient(&Gradient::Radial {
+                center: Vec2::new(0.25, 0.0),
+                radius: 2.0,
+                inner_color: Vec4::ONE,
+                outer_color: Vec4::ZERO,
+            });
+        assert_eq!(radial.flags, Inst::UNLIT | Inst::GRADIENT_RADIAL);
+        assert_eq!(radial.gradient, Vec4::new(0.25, 0.0, 2.0, 0.0));
+    }
+
+    #[test]
+    fn test_with_data() {
+        let inst = Instance2D::default().to_matrix();
+        assert_eq!(inst.data, Vec4::ZERO);
+        let inst = inst.with_data(&InstanceData(Vec4::new(1.0, 2.0, 3.0, 4.0)));
+        assert_eq!(inst.data, Vec4::new(1.0, 2.0, 3.0, 4.0));
+    }
+
+    #[test]
+    fn test_with_effect() {
+        let inst = Instance2D::default().to_matrix().with_flags(Inst::UNLIT);
+        let wobble = inst.with_effect(&ShaderEffect::Wobble {
+            amplitude: 0.1,
+            frequency: 2.0,
+        });
+        assert_eq!(wobble.flags, Inst::UNLIT | Inst::EFFECT_WOBBLE);
+        assert_eq!(wobble.effect, Vec4::new(0.1, 2.0, 0.0, 0.0));
+
+        let stripes = inst.with_effect(&ShaderEffect::Stripes {
+            count: 4.0,
+            speed: 1.0,
+            angle: 0.5,
+        });
+        assert_eq!(stripes.flags, Inst::UNLIT | Inst::EFFECT_STRIPES);
+        assert_eq!(stripes.effect, Vec4::new(4.0, 1.0, 0.5, 0.0));
+    }
+
+    #[test]
+    fn test_instance_layout() {
+        // Every field of the instance is read by an attribute, ending with the effect.
+        let attributes = Instance2D::desc().attributes;
+        let last = attributes.iter().max_by_key(|a| a.offset).unwrap();
+        assert_eq!(last.shader_location, 15);
+        assert_eq!(
+            last.offset + std::mem::size_of::<Vec4>() as u64,
+            std::mem::size_of::<Inst>() as u64
+        );
+    }
 }
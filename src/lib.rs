@@ -3,9 +3,9 @@
 //! Prim uses basic predefined shapes for all rendered instances, allowing for efficient
 //! GPU batching of simple geometry.
 //!
-//! Currently there is no support for texturing or lighting. Lighting is planned but
-//! texturing is not. The idea of Prim is to keep the graphics relatively simple, and
-//! focus on gameplay.
+//! Shapes can be lit by simple 2D point, spot and ambient lights. There is no support for
+//! texturing, and none is planned. The idea of Prim is to keep the graphics relatively simple,
+//! and focus on gameplay.
 #![deny(clippy::pedantic)]
 #![deny(missing_docs)]
 #![allow(clippy::needless_pass_by_value)]
@@ -22,8 +22,17 @@ pub mod animation {
 }
 /// Implementation of the engine's Camera mechanism, defining how to view the 2D world.
 pub mod camera;
+/// Reading rendered frames back from the GPU and saving them as images.
+pub mod capture;
 /// Implementation for a basic collision system between entities.
 pub mod collision;
+/// Immediate mode drawing of lines and shapes for debugging.
+pub mod debug_draw;
+/// Options for running the engine without a window.
+pub mod headless;
+/// Reloading shape and particle effect files when they change on disk.
+#[cfg(all(feature = "hot_reload", not(target_arch = "wasm32")))]
+pub mod hot_reload;
 /// Implementation of Initializer commands, used to setup assets after basic engine initialization
 /// but before game logic begins.
 pub mod initialization;
@@ -31,10 +40,16 @@ pub mod initialization;
 pub mod input;
 /// Defines the basic units of renderable objects and logic necessary to place them in the world.
 pub mod instance;
+/// Point, spot and ambient lights which shade the shapes in the world.
+pub mod light;
+/// Custom WGSL materials which decide how shapes are filled.
+pub mod material;
 /// A cpu-based particle system implementation that works with Shapes provided to the engine.
 pub mod particle_system {
     /// Components necessary for the particle system.
     pub mod components;
+    /// Named particle effects, which can be loaded from files and redefined while running.
+    pub mod effects;
     /// ECS systems related to running particle systems.
     pub mod systems;
     /// Functionality and utilities for defining particle system values and ranges.
@@ -42,16 +57,32 @@ pub mod particle_system {
 }
 /// Definition and construction of resources related to the rendering pipeline.
 pub mod pipeline;
+/// Thick lines drawn through a list of points.
+pub mod polyline;
+/// Full-screen effects such as bloom and vignettes, applied after the world is drawn.
+pub mod post_process;
+/// Renderer settings, such as anti-aliasing and vsync, which can be changed while running.
+pub mod render_settings;
 /// Defines how Shapes are stored and rendered.
 pub mod shape;
+/// Loading shapes from RON shape files.
+pub mod shape_file;
+/// Functions which build common shapes such as circles, stars and rounded rectangles.
+pub mod shape_generator;
 /// The registry which holds and allows access to shapes at runtime.
 pub mod shape_registry;
 /// The main engine and renderer runtime state.
 pub mod state;
+/// Builds the triangles of outlines and lines from their paths, with their join and cap styles.
+pub mod stroke;
+/// Importing shapes from SVG files.
+pub mod svg;
 /// Constructs for dealing with and rendering Text within Prim games.
 pub mod text;
 /// Structs and methods for dealing with game time.
 pub mod time;
+/// Splits polygons into triangles.
+mod triangulation;
 /// Engine helpers.
 pub mod util;
 ///
@@ -61,6 +92,7 @@ pub mod window;
 
 pub use glam::{Vec2, Vec3, Vec4};
 
+use headless::HeadlessOptions;
 use log::{error, warn};
 use window::PrimWindowOptions;
 use winit::{
@@ -187,3 +219,26 @@ where
         _ => {}
     });
 }
+
+/// An alternate entrypoint to the engine which renders into an offscreen texture instead of a window.
+///
+/// This works the same as [`run`], except that no event loop is started. Instead the initialized
+/// [`State`] is returned, and the caller drives it one frame at a time with [`State::render_frame`],
+/// reading results back with [`State::capture_frame`]. This is useful for golden-image tests and
+/// scripted screenshots on machines without a display.
+///
+/// Returns `None` if no suitable adapter or device could be found.
+pub fn run_headless<F>(options: HeadlessOptions, initializer: F) -> Option<State>
+where
+    F: FnOnce(&mut State),
+{
+    let mut state = State::new_headless(&options)?;
+
+    {
+        initializer(&mut state);
+    }
+
+    state.run_initializer_queue();
+
+    Some(state)
+}
@@ -0,0 +1,406 @@
+use bevy_ecs::{
+    prelude::Component,
+    query::With,
+    system::{Query, Res},
+};
+use glam::{Mat3, Vec2, Vec3};
+
+use crate::{
+    camera::Camera2D, instance::Instance2D, shape_registry::ShapeRegistry, state::RenderState,
+};
+
+/// The maximum number of lights that can affect a single frame.
+///
+/// Lights are culled against the camera before upload, so this only limits the number of lights
+/// visible at once. Any lights past this limit are ignored.
+pub const MAX_LIGHTS: usize = 64;
+
+/// The maximum number of shadow casting edges that can affect a single frame.
+///
+/// Only edges within range of a visible light are uploaded. Any edges past this limit do not cast
+/// shadows.
+pub const MAX_SHADOW_EDGES: usize = 512;
+
+/// How a light is blocked by [`ShadowCaster`]s.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Shadows {
+    /// The light passes through shadow casters.
+    None,
+    /// Shadow casters block the light completely, leaving sharp edged shadows.
+    #[default]
+    Hard,
+    /// Shadow casters block the light, with shadows softened as if the light were a disk of the
+    /// given radius in world units.
+    Soft(f32),
+}
+
+/// A marker component which makes an [`Instance2D`] block light, casting shadows.
+///
+/// Shadows are cast by the outside edges of the instance's shape, as registered in the
+/// [`ShapeRegistry`]. Shadow casters are never shadowed themselves, so the caster's own shape is
+/// always lit by the lights around it.
+#[derive(Component, Debug, Default, Clone, Copy)]
+pub struct ShadowCaster;
+
+/// A light which shines equally in all directions from a point.
+///
+/// If the entity also has an [`Instance2D`], the light follows it and ``position`` is treated
+/// as an offset from the instance's position. Otherwise ``position`` is the light's world position.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct PointLight {
+    /// The world position of the light, or its offset from the entity's [`Instance2D`].
+    pub position: Vec2,
+    /// The color of the light.
+    pub color: Vec3,
+    /// A multiplier applied to the light's color.
+    pub intensity: f32,
+    /// The distance, in world units, at which the light stops having any effect.
+    pub radius: f32,
+    /// How quickly the light fades towards its ``radius``.
+    ///
+    /// `1.0` fades linearly, larger values keep more of the light near the center.
+    pub falloff: f32,
+    /// Whether the light is blocked by [`ShadowCaster`]s.
+    pub shadows: Shadows,
+}
+
+impl Default for PointLight {
+    fn default() -> Self {
+        Self {
+            position: Vec2::ZERO,
+            color: Vec3::ONE,
+            intensity: 1.0,
+            radius: 100.0,
+            falloff: 2.0,
+            shadows: Shadows::default(),
+        }
+    }
+}
+
+/// A light which shines in a cone from a point.
+///
+/// If the entity also has an [`Instance2D`], the light follows it, ``position`` is treated as an
+/// offset from the instance's position and ``direction`` is rotated along with the instance.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct SpotLight {
+    /// The world position of the light, or its offset from the entity's [`Instance2D`].
+    pub position: Vec2,
+    /// The direction the light points in, in radian.
+    ///
+    /// Zero points along the positive X axis, matching [`Instance2D::rotation`].
+    pub direction: f32,
+    /// The full width of the cone of light, in radian.
+    pub angle: f32,
+    /// The fraction of the cone, from `0.0` to `1.0`, over which the edge of the light fades out.
+    pub softness: f32,
+    /// The color of the light.
+    pub color: Vec3,
+    /// A multiplier applied to the light's color.
+    pub intensity: f32,
+    /// The distance, in world units, at which the light stops having any effect.
+    pub radius: f32,
+    /// How quickly the light fades towards its ``radius``.
+    ///
+    /// `1.0` fades linearly, larger values keep more of the light near the center.
+    pub falloff: f32,
+    /// Whether the light is blocked by [`ShadowCaster`]s.
+    pub shadows: Shadows,
+}
+
+impl Default for SpotLight {
+    fn default() -> Self {
+        Self {
+            position: Vec2::ZERO,
+            direction: 0.0,
+            angle: std::f32::consts::FRAC_PI_4,
+            softness: 0.2,
+            color: Vec3::ONE,
+            intensity: 1.0,
+            radius: 200.0,
+            falloff: 2.0,
+            shadows: Shadows::default(),
+        }
+    }
+}
+
+/// The light applied evenly to every lit instance, regardless of any other lights.
+///
+/// Accessible and modifiable through a bevy Resource.
+///
+/// The default ambient light is full white, so that scenes without any lights look exactly as
+/// they would without lighting. Lower the ``intensity`` to make other lights stand out.
+#[derive(Debug, Clone, Copy)]
+pub struct AmbientLight {
+    /// The color of the ambient light.
+    pub color: Vec3,
+    /// A multiplier applied to the ambient light's color.
+    pub intensity: f32,
+}
+
+impl Default for AmbientLight {
+    fn default() -> Self {
+        Self {
+            color: Vec3::ONE,
+            intensity: 1.0,
+        }
+    }
+}
+
+impl AmbientLight {
+    /// Creates a new ambient light with the given color and intensity.
+    #[must_use]
+    pub fn new(color: Vec3, intensity: f32) -> Self {
+        Self { color, intensity }
+    }
+}
+
+/// A marker component which excludes an [`Instance2D`] from lighting.
+///
+/// Unlit instances are drawn with their exact color, which is useful for UI elements and
+/// for shapes that should appear to glow. Text is always unlit.
+#[derive(Component, Debug, Default, Clone, Copy)]
+pub struct Unlit;
+
+/// The header of the light uniform buffer, followed by [`MAX_LIGHTS`] [`GpuLight`]s.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct LightsHeader {
+    ambient: [f32; 4],
+    count: [u32; 4],
+}
+
+/// A single light as it's laid out in the light uniform buffer.
+///
+/// Point lights are stored as spot lights with a cone that covers every direction.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct GpuLight {
+    /// xy: position, z: radius, w: falloff.
+    position_radius: [f32; 4],
+    /// rgb: color premultiplied by intensity.
+    color: [f32; 4],
+    /// xy: direction, z: cosine of the inner cone angle, w: cosine of the outer cone angle.
+    spot: [f32; 4],
+    /// x: `1.0` if the light casts shadows, y: the radius used to soften shadows.
+    shadow: [f32; 4],
+}
+
+/// The size in bytes of the light uniform buffer.
+pub(crate) const LIGHT_BUFFER_SIZE: usize =
+    std::mem::size_of::<LightsHeader>() + std::mem::size_of::<GpuLight>() * MAX_LIGHTS;
+
+/// The size in bytes of the shadow edge uniform buffer.
+///
+/// Each edge is stored as a start and end point packed into a single `vec4`.
+pub(crate) const SHADOW_EDGE_BUFFER_SIZE: usize =
+    std::mem::size_of::<[f32; 4]>() * MAX_SHADOW_EDGES;
+
+impl Shadows {
+    fn to_gpu(self) -> [f32; 4] {
+        match self {
+            Self::None => [0.0; 4],
+            Self::Hard => [1.0, 0.0, 0.0, 0.0],
+            Self::Soft(radius) => [1.0, radius.max(0.0), 0.0, 0.0],
+        }
+    }
+}
+
+/// The smallest difference kept between the cosines of a spot light's inner and outer cone.
+const SPOT_EDGE_EPSILON: f32 = 1e-4;
+
+impl GpuLight {
+    fn point(position: Vec2, light: &PointLight) -> Self {
+        Self {
+            position_radius: [position.x, position.y, light.radius, light.falloff],
+            color: (light.color * light.intensity).extend(1.0).into(),
+            // The cone test always passes for cosines below -1.
+            spot: [1.0, 0.0, -2.0, -3.0],
+            shadow: light.shadows.to_gpu(),
+        }
+    }
+
+    fn spot(position: Vec2, direction: f32, light: &SpotLight) -> Self {
+        let half_angle = light.angle.clamp(0.0, std::f32::consts::TAU) / 2.0;
+        let inner = half_angle * (1.0 - light.softness.clamp(0.0, 1.0));
+        // The shader fades between the cosines with smoothstep, which is undefined when they're
+        // equal, so hard edges keep the inner cone a sliver narrower.
+        let outer_cos = half_angle.cos();
+        let inner_cos = inner.cos().max(outer_cos + SPOT_EDGE_EPSILON);
+        Self {
+            position_radius: [position.x, position.y, light.radius, light.falloff],
+            color: (light.color * light.intensity).extend(1.0).into(),
+            spot: [direction.cos(), direction.sin(), inner_cos, outer_cos],
+            shadow: light.shadows.to_gpu(),
+        }
+    }
+
+    #[inline(always)]
+    fn casts_shadows(&self) -> bool {
+        self.shadow[0] > 0.0
+    }
+
+    /// Whether any part of the edge is within the light's radius, using their bounding boxes.
+    fn reaches(&self, edge: [Vec2; 2]) -> bool {
+        let [x, y, radius, _] = self.position_radius;
+        edge[0].x.min(edge[1].x) < x + radius
+            && edge[0].x.max(edge[1].x) > x - radius
+            && edge[0].y.min(edge[1].y) < y + radius
+            && edge[0].y.max(edge[1].y) > y - radius
+    }
+}
+
+/// Whether a light with the given position and radius can affect anything the camera sees.
+///
+/// Uses the same generous bounds as instance culling.
+fn light_visible(camera2d: &Camera2D, position: Vec2, radius: f32) -> bool {
+    camera2d.overlaps(position - radius, position + radius)
+}
+
+/// Run in the `collect` stage, gathers the lights visible to any camera and the shadow casting
+/// edges within their reach, and writes them to the light buffers.
+#[allow(clippy::cast_possible_truncation)]
+pub(crate) fn collect_lights(
+    point_lights: Query<(&PointLight, Option<&Instance2D>)>,
+    spot_lights: Query<(&SpotLight, Option<&Instance2D>)>,
+    shadow_casters: Query<&Instance2D, With<ShadowCaster>>,
+    ambient_light: Option<Res<AmbientLight>>,
+    shape_registry: Res<ShapeRegistry>,
+    render_state: Res<RenderState>,
+    cameras: Query<&Camera2D>,
+) {
+    let ambient = ambient_light.map_or_else(AmbientLight::default, |ambient| *ambient);
+
+    let points = point_lights.iter().filter_map(|(light, instance)| {
+        let position = instance.map_or(light.position, |i| i.position + light.position);
+        cameras
+            .iter()
+            .any(|camera2d| light_visible(camera2d, position, light.radius))
+            .then(|| GpuLight::point(position, light))
+    });
+    let spots = spot_lights.iter().filter_map(|(light, instance)| {
+        let (position, direction) = instance.map_or((light.position, light.direction), |i| {
+            (i.position + light.position, i.rotation + light.direction)
+        });
+        cameras
+            .iter()
+            .any(|camera2d| light_visible(camera2d, position, light.radius))
+            .then(|| GpuLight::spot(position, direction, light))
+    });
+    let lights = points.chain(spots).take(MAX_LIGHTS).collect::<Vec<_>>();
+
+    let mut edges = Vec::new();
+    if lights.iter().any(GpuLight::casts_shadows) {
+        'casters: for caster in &shadow_casters {
+            let Some(shape) = shape_registry.get_shape(caster.shape) else {
+                continue;
+            };
+            let transform =
+                Mat3::from_scale_angle_translation(caster.scale, caster.rotation, caster.position);
+            for edge in &shape.edges {
+                let edge = [
+                    transform.transform_point2(edge[0]),
+                    transform.transform_point2(edge[1]),
+                ];
+                if lights
+                    .iter()
+                    .any(|light| light.casts_shadows() && light.reaches(edge))
+                {
+                    if edges.len() == MAX_SHADOW_EDGES {
+                        break 'casters;
+                    }
+                    edges.push([edge[0].x, edge[0].y, edge[1].x, edge[1].y]);
+                }
+            }
+        }
+    }
+
+    let header = LightsHeader {
+        ambient: (ambient.color * ambient.intensity).extend(1.0).into(),
+        count: [lights.len() as u32, edges.len() as u32, 0, 0],
+    };
+    render_state.queue.write_buffer(
+        &render_state.buffers.light_buffer,
+        0,
+        bytemuck::cast_slice(&[header]),
+    );
+    if !lights.is_empty() {
+        render_state.queue.write_buffer(
+            &render_state.buffers.light_buffer,
+            std::mem::size_of::<LightsHeader>() as wgpu::BufferAddress,
+            bytemuck::cast_slice(&lights),
+        );
+    }
+    if !edges.is_empty() {
+        render_state.queue.write_buffer(
+            &render_state.buffers.shadow_edge_buffer,
+            0,
+            bytemuck::cast_slice(&edges),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{Vec2, Vec3};
+
+    use super::{light_visible, GpuLight, PointLight, SpotLight};
+    use crate::camera::Camera2D;
+
+    #[test]
+    fn test_spot_light_cone() {
+        let light = SpotLight {
+            angle: std::f32::consts::FRAC_PI_2,
+            softness: 0.5,
+            color: Vec3::new(1.0, 0.5, 0.0),
+            intensity: 2.0,
+            ..Default::default()
+        };
+        let gpu = GpuLight::spot(Vec2::ZERO, std::f32::consts::FRAC_PI_2, &light);
+        assert!(gpu.spot[0].abs() < 1e-6);
+        assert!((gpu.spot[1] - 1.0).abs() < 1e-6);
+        assert!((gpu.spot[2] - std::f32::consts::FRAC_PI_8.cos()).abs() < 1e-6);
+        assert!((gpu.spot[3] - std::f32::consts::FRAC_PI_4.cos()).abs() < 1e-6);
+        assert_eq!(gpu.color, [2.0, 1.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn test_hard_edged_spot_light() {
+        let light = SpotLight {
+            angle: std::f32::consts::FRAC_PI_2,
+            softness: 0.0,
+            ..Default::default()
+        };
+        let gpu = GpuLight::spot(Vec2::ZERO, 0.0, &light);
+        // The edges of the fade must differ for smoothstep to be defined.
+        assert!(gpu.spot[2] > gpu.spot[3]);
+        assert!((gpu.spot[2] - gpu.spot[3]) < 1e-3);
+        assert!((gpu.spot[3] - std::f32::consts::FRAC_PI_4.cos()).abs() < 1e-6);
+
+        // A cone with no width still has distinct edges.
+        let gpu = GpuLight::spot(
+            Vec2::ZERO,
+            0.0,
+            &SpotLight {
+                angle: 0.0,
+                ..light
+            },
+        );
+        assert!(gpu.spot[2] > gpu.spot[3]);
+    }
+
+    #[test]
+    fn test_light_visible() {
+        let camera = Camera2D::new(Vec2::ZERO, Vec2::new(100.0, 100.0));
+        let light = PointLight {
+            radius: 50.0,
+            ..Default::default()
+        };
+        assert!(light_visible(&camera, Vec2::new(120.0, 0.0), light.radius));
+        assert!(!light_visible(&camera, Vec2::new(160.0, 0.0), light.radius));
+        assert!(!light_visible(
+            &camera,
+            Vec2::new(0.0, -151.0),
+            light.radius
+        ));
+    }
+}
@@ -64,10 +64,7 @@ fn spinner_spawn(mut commands: Commands) {
                         1.0,
                     ),
                     shape: u32::from((x + y) % 2 == 0),
-                    outline: Some(Outline {
-                        scale: 5.0,
-                        color: Vec4::ZERO,
-                    }),
+                    outline: Some(Outline::new(2.5, Vec4::ZERO)),
                 }))
                 .insert(SpinMultiplier(rng.gen_range(0.2..2.0)));
         }
@@ -0,0 +1,560 @@
+use bevy_ecs::prelude::Component;
+use log::error;
+use serde::Deserialize;
+use wgpu::util::DeviceExt;
+
+use crate::{
+    pipeline::{PrimBindGroupLayouts, PrimPipelineLayouts},
+    util::FxHashMap,
+};
+
+/// The vertex stage, bindings and lighting shared by every material.
+const SHAPE_SHADER: &str = include_str!("shader2d.wgsl");
+
+/// The material used by instances which don't select one, drawing them in their fill color.
+const DEFAULT_MATERIAL: &str = "\
+fn material(in: VertexOutput) -> vec4<f32> {
+    return in.color;
+}
+";
+
+/// The material used by instances with a [`libprim::camera::CameraFeed`], filling them with a
+/// camera's texture.
+const CAMERA_FEED_MATERIAL: &str = "\
+@group(2) @binding(0)
+var feed_texture: texture_2d<f32>;
+@group(2) @binding(1)
+var feed_sampler: sampler;
+
+fn material(in: VertexOutput) -> vec4<f32> {
+    // The built-in shapes span -0.5 to 0.5 upwards, while textures run from 0.0 to 1.0 downwards.
+    let uv = vec2<f32>(in.local_pos.x + 0.5, 0.5 - in.local_pos.y);
+    return textureSample(feed_texture, feed_sampler, uv) * in.color;
+}
+";
+
+/// The fragment entry point appended to every material.
+///
+/// Materials are given the shape's fill color, and the color they return is then lit. The result
+/// is premultiplied by its alpha, which lets every [`BlendMode`] fade out with transparency.
+const MATERIAL_ENTRY_POINT: &str = "\
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    var filled = in;
+    filled.color = fill_color(in);
+    let color = shade(filled, material(filled));
+    return vec4<f32>(color.rgb * color.a, color.a);
+}
+";
+
+/// The name the built-in material is registered with in the [`MaterialRegistry`].
+pub const DEFAULT_MATERIAL_NAME: &str = "Default";
+
+/// The name the built-in camera feed material is registered with in the [`MaterialRegistry`].
+pub const CAMERA_FEED_MATERIAL_NAME: &str = "CameraFeed";
+
+/// Selects the material an instance is drawn with.
+///
+/// The ID can be fetched for any material registered at initialization time using
+/// [`MaterialRegistry::get_id`]. Instances without this component, and outlines, are drawn with
+/// [`Material::DEFAULT`].
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Material(pub u32);
+
+impl Material {
+    /// The built-in material, which draws shapes in their fill color.
+    pub const DEFAULT: Self = Self(0);
+    /// The built-in material used to draw a [`libprim::camera::CameraFeed`].
+    ///
+    /// Selecting it without a feed draws with the default material instead.
+    pub const CAMERA_FEED: Self = Self(1);
+}
+
+/// Selects how an instance's color is combined with what's already been drawn behind it.
+///
+/// Instances without this component, outlines and lines are drawn with [`BlendMode::Alpha`].
+/// Instances are batched by blend mode as well as material and shape, so mixing blend modes
+/// within a layer adds draw calls.
+#[derive(
+    Component, Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize,
+)]
+pub enum BlendMode {
+    /// The instance is drawn over the background, which shows through where it's transparent.
+    #[default]
+    Alpha,
+    /// The instance's color is added to the background, so overlapping instances glow brighter.
+    /// Useful for fire, sparks and light flares.
+    Additive,
+    /// The background is multiplied by the instance's color, darkening it. Useful for shadows and
+    /// tinting.
+    Multiply,
+    /// The inverse of the colors are multiplied, brightening the background without washing out
+    /// as quickly as [`BlendMode::Additive`].
+    Screen,
+}
+
+impl BlendMode {
+    /// Every blend mode, in the order their pipelines are stored.
+    pub const ALL: [Self; 4] = [Self::Alpha, Self::Additive, Self::Multiply, Self::Screen];
+
+    /// The blend state of the pipelines drawing with this mode, for colors premultiplied by their
+    /// alpha.
+    #[must_use]
+    pub(crate) const fn blend_state(self) -> wgpu::BlendState {
+        let color = match self {
+            Self::Alpha => wgpu::BlendComponent::OVER,
+            Self::Additive => wgpu::BlendComponent {
+                src_factor: wgpu::BlendFactor::One,
+                dst_factor: wgpu::BlendFactor::One,
+                operation: wgpu::BlendOperation::Add,
+            },
+            Self::Multiply => wgpu::BlendComponent {
+                src_factor: wgpu::BlendFactor::Dst,
+                dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                operation: wgpu::BlendOperation::Add,
+            },
+            Self::Screen => wgpu::BlendComponent {
+                src_factor: wgpu::BlendFactor::One,
+                dst_factor: wgpu::BlendFactor::OneMinusSrc,
+                operation: wgpu::BlendOperation::Add,
+            },
+        };
+        // Coverage builds up the same way whatever the mode, so render textures stay opaque
+        // wherever something was drawn.
+        wgpu::BlendState {
+            color,
+            alpha: wgpu::BlendComponent::OVER,
+        }
+    }
+}
+
+/// The uniform buffer of a material and the bind group used to bind it to group 2.
+pub(crate) struct MaterialUniforms {
+    buffer: wgpu::Buffer,
+    pub bind_group: wgpu::BindGroup,
+}
+
+/// A compiled material, ready to be drawn with.
+pub(crate) struct PrimMaterial {
+    /// A pipeline for each [`BlendMode`], in the order of [`BlendMode::ALL`].
+    pipelines: [wgpu::RenderPipeline; BlendMode::ALL.len()],
+    pub uniforms: Option<MaterialUniforms>,
+    /// The compiled shader, kept to rebuild the pipelines when the sample count changes.
+    module: wgpu::ShaderModule,
+    /// Whether the material samples a camera's texture in group 2 instead of its uniforms.
+    camera_feed: bool,
+}
+
+impl PrimMaterial {
+    /// Gets the pipeline which draws the material with the given blend mode.
+    #[inline(always)]
+    #[must_use]
+    pub fn pipeline(&self, blend_mode: BlendMode) -> &wgpu::RenderPipeline {
+        &self.pipelines[blend_mode as usize]
+    }
+
+    /// Creates a pipeline for each [`BlendMode`] which draws with the given shader module.
+    fn create_pipelines(
+        module: &wgpu::ShaderModule,
+        camera_feed: bool,
+        has_uniforms: bool,
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        pipeline_layouts: &PrimPipelineLayouts,
+        sample_count: u32,
+    ) -> [wgpu::RenderPipeline; BlendMode::ALL.len()] {
+        BlendMode::ALL.map(|blend_mode| {
+            if camera_feed {
+                pipeline_layouts.create_camera_feed_pipeline(
+                    device,
+                    config,
+                    module,
+                    blend_mode,
+                    sample_count,
+                )
+            } else {
+                pipeline_layouts.create_shape_pipeline(
+                    device,
+                    config,
+                    module,
+                    has_uniforms,
+                    blend_mode,
+                    sample_count,
+                )
+            }
+        })
+    }
+}
+
+/// A registry of the materials shapes can be drawn with.
+///
+/// Materials are created using the [`libprim::initialization::InitializerQueue`] and assigned an
+/// ID which instances select them by with the [`Material`] component.
+///
+/// Instances are batched by material as well as shape, so each material in use adds at least one
+/// draw call.
+#[derive(Default)]
+pub struct MaterialRegistry {
+    materials: Vec<PrimMaterial>,
+    index: FxHashMap<String, u32>,
+    pending_uniforms: Vec<(u32, Vec<u8>)>,
+    /// The default material, for drawing over the finished frame without multisampling.
+    overlay_pipeline: Option<wgpu::RenderPipeline>,
+}
+
+impl MaterialRegistry {
+    #[must_use]
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compiles a material from its WGSL source and registers it by name.
+    ///
+    /// See [`InitializeMaterial`] for what the source must contain. If `uniforms` are given, a
+    /// uniform buffer is created with their contents and bound to group 2.
+    ///
+    /// # Errors
+    /// Fails and returns an [`std::io::ErrorKind::AlreadyExists`] if a material with the same name
+    /// is already registered, or an [`std::io::ErrorKind::InvalidInput`] if the shader fails to
+    /// compile, or is not compatible with the shape pipeline.
+    ///
+    /// # Panics
+    /// Panics if the method attempts to register more than `u32::MAX` total materials.
+    pub(crate) fn register_material(
+        &mut self,
+        name: String,
+        source: &str,
+        uniforms: Option<&[u8]>,
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        bind_group_layouts: &PrimBindGroupLayouts,
+        pipeline_layouts: &PrimPipelineLayouts,
+        sample_count: u32,
+    ) -> std::io::Result<u32> {
+        self.check_name_available(&name)?;
+        // Shader errors are reported through the device's error handler, which panics by default.
+        // Capture them instead so that a broken material can be reported to the caller.
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(&format!("{name:?} Material Shader")),
+            source: wgpu::ShaderSource::Wgsl(material_source(source).into()),
+        });
+        let pipelines = PrimMaterial::create_pipelines(
+            &module,
+            false,
+            uniforms.is_some(),
+            device,
+            config,
+            pipeline_layouts,
+            sample_count,
+        );
+        if let Some(err) = pollster::block_on(device.pop_error_scope()) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Could not build material {name:?}: {err}"),
+            ));
+        }
+
+        let uniforms = uniforms.map(|contents| {
+            // Uniform buffers are sized in multiples of 16 bytes to satisfy WGSL's layout rules.
+            let mut contents = contents.to_vec();
+            contents.resize(contents.len().max(1).next_multiple_of(16), 0);
+            let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(&format!("{name:?} Material Uniform Buffer")),
+                contents: &contents,
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            });
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some(&format!("{name:?} Material Bind Group")),
+                layout: &bind_group_layouts.material_bind_group_layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: buffer.as_entire_binding(),
+                }],
+            });
+            MaterialUniforms { buffer, bind_group }
+        });
+
+        Ok(self.push_material(
+            name,
+            PrimMaterial {
+                pipelines,
+                uniforms,
+                module,
+                camera_feed: false,
+            },
+        ))
+    }
+
+    /// Makes sure no material is registered with the given name, since instances which selected
+    /// the existing material by its ID would otherwise keep drawing with it.
+    fn check_name_available(&self, name: &str) -> std::io::Result<()> {
+        if self.index.contains_key(name) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!("Material {name:?} already exists"),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Adds a compiled material to the registry, returning its ID.
+    ///
+    /// # Panics
+    /// Panics if the method attempts to register more than `u32::MAX` total materials.
+    #[allow(clippy::cast_possible_truncation)]
+    fn push_material(&mut self, name: String, material: PrimMaterial) -> u32 {
+        self.materials.push(material);
+
+        let id = self.materials.len() - 1;
+        assert!(
+            u32::try_from(id).is_ok(),
+            "Cannot register more than {} materials",
+            u32::MAX
+        );
+        self.index.insert(name, id as u32);
+
+        id as u32
+    }
+
+    /// Registers the built-in materials as [`Material::DEFAULT`] and [`Material::CAMERA_FEED`],
+    /// and builds the overlay pipeline.
+    ///
+    /// # Panics
+    /// Panics if the built-in material fails to compile.
+    pub(crate) fn register_builtin_materials(
+        &mut self,
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        bind_group_layouts: &PrimBindGroupLayouts,
+        pipeline_layouts: &PrimPipelineLayouts,
+        sample_count: u32,
+    ) {
+        let id = self
+            .register_material(
+                DEFAULT_MATERIAL_NAME.to_string(),
+                DEFAULT_MATERIAL,
+                None,
+                device,
+                config,
+                bind_group_layouts,
+                pipeline_layouts,
+                sample_count,
+            )
+            .unwrap();
+        debug_assert_eq!(id, Material::DEFAULT.0);
+
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Camera Feed Material Shader"),
+            source: wgpu::ShaderSource::Wgsl(material_source(CAMERA_FEED_MATERIAL).into()),
+        });
+        let pipelines = PrimMaterial::create_pipelines(
+            &module,
+            true,
+            false,
+            device,
+            config,
+            pipeline_layouts,
+            sample_count,
+        );
+        let id = self.push_material(
+            CAMERA_FEED_MATERIAL_NAME.to_string(),
+            PrimMaterial {
+                pipelines,
+                uniforms: None,
+                module,
+                camera_feed: true,
+            },
+        );
+        debug_assert_eq!(id, Material::CAMERA_FEED.0);
+
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Overlay Shader"),
+            source: wgpu::ShaderSource::Wgsl(material_source(DEFAULT_MATERIAL).into()),
+        });
+        self.overlay_pipeline = Some(pipeline_layouts.create_shape_pipeline(
+            device,
+            config,
+            &module,
+            false,
+            BlendMode::Alpha,
+            1,
+        ));
+    }
+
+    /// Rebuilds the pipelines of every material to draw with a new sample count.
+    ///
+    /// The overlay pipeline is left as is, since it always draws without multisampling.
+    pub(crate) fn rebuild_pipelines(
+        &mut self,
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        pipeline_layouts: &PrimPipelineLayouts,
+        sample_count: u32,
+    ) {
+        for material in &mut self.materials {
+            material.pipelines = PrimMaterial::create_pipelines(
+                &material.module,
+                material.camera_feed,
+                material.uniforms.is_some(),
+                device,
+                config,
+                pipeline_layouts,
+                sample_count,
+            );
+        }
+    }
+
+    /// Gets the ID of a material by the name it was registered with.
+    #[inline(always)]
+    #[must_use]
+    pub fn get_id(&self, name: &str) -> Option<u32> {
+        self.index.get(name).copied()
+    }
+
+    /// Gets the material for the specified ID, falling back to the default material for unknown
+    /// IDs.
+    #[inline(always)]
+    #[must_use]
+    pub(crate) fn get_material(&self, id: u32) -> &PrimMaterial {
+        self.materials
+            .get(id as usize)
+            .unwrap_or(&self.materials[Material::DEFAULT.0 as usize])
+    }
+
+    /// Gets the pipeline used to draw over the finished frame, such as for debug drawing.
+    #[inline(always)]
+    #[must_use]
+    pub(crate) fn overlay_pipeline(&self) -> Option<&wgpu::RenderPipeline> {
+        self.overlay_pipeline.as_ref()
+    }
+
+    /// Replaces the uniforms of a material.
+    ///
+    /// The new values are uploaded before the next frame is rendered. They must be no larger than
+    /// the uniforms the material was registered with.
+    pub fn set_uniforms<T: bytemuck::Pod>(&mut self, id: u32, uniforms: &T) {
+        self.pending_uniforms
+            .push((id, bytemuck::bytes_of(uniforms).to_vec()));
+    }
+
+    /// Uploads any uniforms changed since the last frame.
+    pub(crate) fn write_pending_uniforms(&mut self, queue: &wgpu::Queue) {
+        for (id, contents) in self.pending_uniforms.drain(..) {
+            match self
+                .materials
+                .get(id as usize)
+                .and_then(|m| m.uniforms.as_ref())
+            {
+                Some(uniforms) if contents.len() as u64 <= uniforms.buffer.size() => {
+                    queue.write_buffer(&uniforms.buffer, 0, &contents);
+                }
+                Some(_) => error!(
+                    "Uniforms of {} bytes are too large for material {id}",
+                    contents.len()
+                ),
+                None => error!("Material {id} does not exist or has no uniforms"),
+            }
+        }
+    }
+}
+
+/// Builds the full shader for a material from the user provided source.
+fn material_source(source: &str) -> String {
+    format!("{SHAPE_SHADER}\n{source}\n{MATERIAL_ENTRY_POINT}")
+}
+
+/// An initializer struct for compiling a material into the [`MaterialRegistry`].
+///
+/// A material is a piece of WGSL which decides the color of each pixel of the shapes it's used
+/// with. It must define a function with the signature:
+/// ```wgsl
+/// fn material(in: VertexOutput) -> vec4<f32>
+/// ```
+///
+/// `VertexOutput` contains the fill `color`, which combines the instance color with any vertex
+/// colors and [`libprim::instance::Gradient`], the `world_pos` of the pixel, and its
+/// `local_pos` within the shape before the instance transform, where the built-in shapes span
+/// -0.5 to 0.5. It also contains the instance's `data`, set with a
+/// [`libprim::instance::InstanceData`] component. The returned color is then lit like any other
+/// shape.
+///
+/// Every material can also read the `globals` uniform, which holds the `time` in seconds since the
+/// engine started, the `delta_time` of the frame, the `frame` number and the `resolution` of the
+/// window in pixels.
+///
+/// Materials with uniforms can read them by declaring a binding in group 2, such as:
+/// ```wgsl
+/// struct Pulse {
+///     speed: f32,
+/// };
+/// @group(2) @binding(0)
+/// var<uniform> pulse: Pulse;
+/// ```
+pub struct InitializeMaterial {
+    /// The name to reference the material by when retrieving its ID.
+    pub name: String,
+    /// The WGSL source of the material.
+    pub source: String,
+    /// The initial contents of the material's uniform buffer, if it uses one.
+    pub uniforms: Option<Vec<u8>>,
+}
+
+impl InitializeMaterial {
+    /// Create a new material initializer from the given WGSL source.
+    ///
+    /// ## Example
+    /// ```
+    /// # use libprim::material::InitializeMaterial;
+    /// InitializeMaterial::new(
+    ///     "Invert".to_string(),
+    ///     "fn material(in: VertexOutput) -> vec4<f32> { return vec4<f32>(1.0 - in.color.rgb, in.color.a); }".to_string(),
+    /// );
+    /// ```
+    #[must_use]
+    pub fn new(name: String, source: String) -> Self {
+        Self {
+            name,
+            source,
+            uniforms: None,
+        }
+    }
+
+    /// Gives the material a uniform buffer bound to group 2, initialized with the given value.
+    ///
+    /// The value can later be replaced using [`MaterialRegistry::set_uniforms`].
+    #[must_use]
+    pub fn with_uniforms<T: bytemuck::Pod>(mut self, uniforms: &T) -> Self {
+        self.uniforms = Some(bytemuck::bytes_of(uniforms).to_vec());
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::ErrorKind;
+
+    use super::{material_source, MaterialRegistry, DEFAULT_MATERIAL, DEFAULT_MATERIAL_NAME};
+
+    #[test]
+    fn test_material_source() {
+        let source = material_source(DEFAULT_MATERIAL);
+        let vertex = source.find("fn vs_main").unwrap();
+        let material = source.find("fn material").unwrap();
+        let fragment = source.find("fn fs_main").unwrap();
+        // The material can use anything defined by the shape shader, and is used by the entry point.
+        assert!(vertex < material && material < fragment);
+        assert_eq!(source.matches("fn fs_main").count(), 1);
+    }
+
+    #[test]
+    fn test_names_are_unique() {
+        let mut registry = MaterialRegistry::new();
+        registry.index.insert(DEFAULT_MATERIAL_NAME.to_string(), 0);
+        let err = registry
+            .check_name_available(DEFAULT_MATERIAL_NAME)
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+        assert!(registry.check_name_available("Glow").is_ok());
+    }
+}
@@ -3,7 +3,11 @@
 use bevy_ecs::prelude::{Bundle, Component, Entity};
 use glam::Vec2;
 
-use crate::particle_system::values::{ColorOverTime, JitteredValue, ValueOverTime};
+use crate::{
+    instance::Layer,
+    material::BlendMode,
+    particle_system::values::{ColorOverTime, JitteredValue, ValueOverTime},
+};
 
 /// Defines a burst of a specified number of particles at the given time in a running particle system.
 ///
@@ -46,6 +50,17 @@ pub struct ParticleSystem {
     /// The sprite used for each particle.
     pub shape_id: u32,
 
+    /// The render layer each particle is spawned on.
+    ///
+    /// Defaults to [`Layer::PARTICLES`], drawing particles above instances on the default layer.
+    pub layer: Layer,
+
+    /// How each particle is blended with what's behind it.
+    ///
+    /// Defaults to [`BlendMode::Alpha`]. [`BlendMode::Additive`] makes overlapping particles glow,
+    /// which suits fire, sparks and explosions.
+    pub blend_mode: BlendMode,
+
     /// The number of particles to spawn per second.
     ///
     /// This uses a [`ValueOverTime`] so that the spawn rate can vary over the lifetime of the system.
@@ -125,6 +140,8 @@ impl Default for ParticleSystem {
         Self {
             max_particles: 100,
             shape_id: 1,
+            layer: Layer::PARTICLES,
+            blend_mode: BlendMode::Alpha,
             spawn_rate_per_second: 5.0.into(),
             spawn_radius: 0.0.into(),
             emitter_shape: std::f32::consts::TAU,
@@ -0,0 +1,645 @@
+//! Named particle effects, which can be loaded from effect files and redefined while running.
+
+use std::{
+    collections::HashSet,
+    io::{Error, ErrorKind, Result},
+    path::PathBuf,
+};
+
+use bevy_ecs::prelude::{Bundle, Component, Query, ResMut};
+use glam::Vec4;
+use log::error;
+use serde::Deserialize;
+
+use crate::{
+    instance::Layer,
+    material::BlendMode,
+    particle_system::{
+        components::{ParticleBurst, ParticleSystem, ParticleSystemBundle},
+        values::{
+            ColorOverTime, ColorPoint, Gradient, JitteredValue, RoughlyEqual, SinWave,
+            ValueOverTime,
+        },
+    },
+    shape_registry::ShapeRegistry,
+    util::{AssetSource, FxHashMap},
+};
+
+/// A resource holding named particle effects.
+///
+/// Entities spawned from an effect with [`ParticleEffects::bundle`] are tagged with a
+/// [`ParticleEffect`] component. Whenever the effect is redefined, such as when its effect file is
+/// reloaded, the [`ParticleSystem`] of every tagged entity is swapped for the new one by the
+/// systems in [`crate::particle_system::systems::system_set`]. Particles which are already alive
+/// keep going.
+///
+/// ## Example
+/// ```
+/// # use bevy_ecs::system::{Commands, Res};
+/// # use libprim::particle_system::{components::Playing, effects::ParticleEffects};
+/// fn spawn_fountain(mut commands: Commands, effects: Res<ParticleEffects>) {
+///     if let Some(fountain) = effects.bundle("Fountain") {
+///         commands.spawn().insert_bundle(fountain).insert(Playing);
+///     }
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct ParticleEffects {
+    effects: FxHashMap<String, ParticleSystem>,
+    changed: Vec<String>,
+}
+
+impl ParticleEffects {
+    /// Defines the effect with the given name, replacing any effect already defined by that name.
+    pub fn insert(&mut self, name: String, particle_system: ParticleSystem) {
+        if self.effects.insert(name.clone(), particle_system).is_some() {
+            self.changed.push(name);
+        }
+    }
+
+    /// Defines an effect loaded from an effect file, looking up its shape by name.
+    ///
+    /// # Errors
+    /// Returns an [`std::io::ErrorKind::NotFound`] if the effect's shape isn't in the registry.
+    pub(crate) fn insert_definition(
+        &mut self,
+        definition: ParticleEffectDefinition,
+        shape_registry: &ShapeRegistry,
+    ) -> Result<()> {
+        let Some(shape_id) = shape_registry.get_id(&definition.shape) else {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                format!(
+                    "effect {:?} uses the shape {:?}, which isn't registered",
+                    definition.name, definition.shape
+                ),
+            ));
+        };
+        self.insert(
+            definition.name,
+            ParticleSystem {
+                shape_id,
+                ..definition.particle_system
+            },
+        );
+        Ok(())
+    }
+
+    /// Defines every effect loaded from an effect file, logging any whose shape isn't registered.
+    pub(crate) fn insert_definitions(
+        &mut self,
+        definitions: Vec<ParticleEffectDefinition>,
+        shape_registry: &ShapeRegistry,
+        source: &str,
+    ) {
+        for definition in definitions {
+            if let Err(err) = self.insert_definition(definition, shape_registry) {
+                error!("Error loading particle effect file {source}: {err}");
+            }
+        }
+    }
+
+    /// Gets the particle system of the effect with the given name.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&ParticleSystem> {
+        self.effects.get(name)
+    }
+
+    /// Creates a bundle for spawning the effect with the given name, tagged so that it's updated
+    /// whenever the effect is redefined.
+    ///
+    /// Add a [`crate::particle_system::components::Playing`] component to start the effect.
+    #[must_use]
+    pub fn bundle(&self, name: &str) -> Option<ParticleEffectBundle> {
+        let particle_system = self.get(name)?.clone();
+        Some(ParticleEffectBundle {
+            particle_system: ParticleSystemBundle {
+                particle_system,
+                ..ParticleSystemBundle::default()
+            },
+            effect: ParticleEffect(name.to_string()),
+        })
+    }
+}
+
+/// A spawnable bundle for a particle system created from a named effect, created with
+/// [`ParticleEffects::bundle`].
+#[derive(Debug, Bundle)]
+pub struct ParticleEffectBundle {
+    /// The particle system and the components tracking its progress.
+    #[bundle]
+    pub particle_system: ParticleSystemBundle,
+    /// The effect the particle system was created from.
+    pub effect: ParticleEffect,
+}
+
+/// Marks an entity's [`ParticleSystem`] as coming from the named effect in [`ParticleEffects`].
+#[derive(Debug, Clone, Component)]
+pub struct ParticleEffect(pub String);
+
+/// Swaps the particle systems of entities whose effect has been redefined.
+pub(crate) fn update_particle_effects(
+    mut effects: ResMut<ParticleEffects>,
+    mut particle_systems: Query<(&ParticleEffect, &mut ParticleSystem)>,
+) {
+    if effects.changed.is_empty() {
+        return;
+    }
+    let changed = std::mem::take(&mut effects.changed);
+    for (effect, mut particle_system) in &mut particle_systems {
+        if changed.contains(&effect.0) {
+            if let Some(new_system) = effects.get(&effect.0) {
+                *particle_system = new_system.clone();
+            }
+        }
+    }
+}
+
+/// An initializer struct for loading every effect in a particle effect file into
+/// [`ParticleEffects`].
+///
+/// Effect files are written in [RON](https://github.com/ron-rs/ron), and hold a list of named
+/// effects:
+///
+/// ```ron
+/// ParticleEffectFile(
+///     effects: [
+///         (
+///             name: "Sparks",
+///             shape: "Circle",
+///             max_particles: 500,
+///             spawn_rate_per_second: Constant(100.0),
+///             initial_velocity: Jittered(value: 120.0, jitter: (-40.0, 40.0)),
+///             lifetime: Constant(1.5),
+///             color: Gradient([(0.0, (1.0, 0.9, 0.3, 1.0)), (1.0, (1.0, 0.2, 0.0, 0.0))]),
+///             scale: Lerp(8.0, 2.0),
+///             bursts: [(0.0, 50)],
+///         ),
+///     ],
+/// )
+/// ```
+///
+/// Each effect has a `name`, which must be unique within the file, and the name of the `shape`
+/// its particles are drawn with. Shapes are looked up when the file is loaded, so shapes from
+/// other files must be initialized first. Every other field of [`ParticleSystem`] can be given,
+/// and fields which are left out keep their default value. Values are written as:
+/// - `Constant(value)`, `Lerp(from, to)` or `Sin(amplitude: .., period: .., phase_shift: ..,
+///   vertical_shift: ..)` for values which change over time, where each part of a `Sin` is
+///   optional.
+/// - `Constant(value)` or `Jittered(value: .., jitter: (min, max))` for jittered values.
+/// - `Constant(r, g, b, a)` or `Gradient([(point, (r, g, b, a)), ..])` for colors, where a
+///   gradient's points run from 0 to 1 in order.
+/// - `(time, count)` for each of the `bursts`, and a number for the `layer`.
+/// - `Alpha`, `Additive`, `Multiply` or `Screen` for the `blend_mode`.
+#[derive(Debug, Clone)]
+pub struct InitializeParticleEffectFile {
+    /// Where to read the effect file from.
+    pub source: AssetSource,
+}
+
+impl InitializeParticleEffectFile {
+    /// Create a new effect file initializer which reads the file at the given path.
+    ///
+    /// The file is read when the initializer runs, so it can be edited without recompiling.
+    #[must_use]
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self {
+            source: AssetSource::Path(path.into()),
+        }
+    }
+
+    /// Create a new effect file initializer from the text of an effect file.
+    ///
+    /// ## Example
+    /// ```
+    /// # use libprim::particle_system::effects::InitializeParticleEffectFile;
+    /// InitializeParticleEffectFile::from_text(
+    ///     r#"ParticleEffectFile(effects: [(name: "Smoke", shape: "Circle", scale: Lerp(4.0, 16.0))])"#
+    ///         .to_string(),
+    /// );
+    /// ```
+    #[must_use]
+    pub fn from_text(text: String) -> Self {
+        Self {
+            source: AssetSource::Text(text),
+        }
+    }
+
+    /// Reads and parses the effect file into effect definitions.
+    ///
+    /// # Errors
+    /// Returns an [`std::io::Error`] if the file can't be read, or an
+    /// [`std::io::ErrorKind::InvalidData`] if the file is malformed or describes an invalid effect.
+    pub fn load(&self) -> Result<Vec<ParticleEffectDefinition>> {
+        self.source.parse(parse_particle_effect_file)
+    }
+
+    /// A description of where the effect file comes from, for logging.
+    pub(crate) fn describe(&self) -> String {
+        self.source.describe()
+    }
+}
+
+/// A particle effect read from an effect file, before its shape has been looked up.
+#[derive(Debug, Clone)]
+pub struct ParticleEffectDefinition {
+    /// The name of the effect.
+    pub name: String,
+    /// The name of the shape the effect's particles are drawn with.
+    pub shape: String,
+    /// The effect's particle system. Its `shape_id` is set once the shape has been looked up.
+    pub particle_system: ParticleSystem,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ParticleEffectFile {
+    effects: Vec<EffectDef>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EffectDef {
+    name: String,
+    shape: String,
+    #[serde(default)]
+    max_particles: Option<usize>,
+    #[serde(default)]
+    layer: Option<i32>,
+    #[serde(default)]
+    blend_mode: Option<BlendMode>,
+    #[serde(default)]
+    spawn_rate_per_second: Option<ValueDef>,
+    #[serde(default)]
+    spawn_radius: Option<JitterDef>,
+    #[serde(default)]
+    emitter_shape: Option<f32>,
+    #[serde(default)]
+    emitter_angle: Option<f32>,
+    #[serde(default)]
+    initial_velocity: Option<JitterDef>,
+    #[serde(default)]
+    acceleration: Option<ValueDef>,
+    #[serde(default)]
+    lifetime: Option<JitterDef>,
+    #[serde(default)]
+    color: Option<ColorDef>,
+    #[serde(default)]
+    scale: Option<ValueDef>,
+    #[serde(default)]
+    looping: Option<bool>,
+    #[serde(default)]
+    system_duration_seconds: Option<f32>,
+    #[serde(default)]
+    max_distance: Option<f32>,
+    #[serde(default)]
+    bursts: Vec<(f32, usize)>,
+    #[serde(default)]
+    use_scaled_time: Option<bool>,
+    #[serde(default)]
+    despawn_on_finish: Option<bool>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+enum ValueDef {
+    Constant(f32),
+    Lerp(f32, f32),
+    Sin {
+        #[serde(default = "one")]
+        amplitude: f32,
+        #[serde(default = "one")]
+        period: f32,
+        #[serde(default)]
+        phase_shift: f32,
+        #[serde(default)]
+        vertical_shift: f32,
+    },
+}
+
+fn one() -> f32 {
+    1.0
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+enum JitterDef {
+    Constant(f32),
+    Jittered { value: f32, jitter: (f32, f32) },
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+enum ColorDef {
+    Constant(f32, f32, f32, f32),
+    Gradient(Vec<(f32, (f32, f32, f32, f32))>),
+}
+
+/// Parses the text of a particle effect file into effect definitions.
+///
+/// # Errors
+/// Returns an [`std::io::ErrorKind::InvalidData`] if the text isn't a valid effect file, or any
+/// effect in it is invalid.
+pub fn parse_particle_effect_file(text: &str) -> Result<Vec<ParticleEffectDefinition>> {
+    let file = ron::Options::default()
+        .with_default_extension(ron::extensions::Extensions::IMPLICIT_SOME)
+        .from_str::<ParticleEffectFile>(text)
+        .map_err(|err| Error::new(ErrorKind::InvalidData, err.to_string()))?;
+
+    let mut names = HashSet::with_capacity(file.effects.len());
+    file.effects
+        .into_iter()
+        .enumerate()
+        .map(|(i, def)| {
+            if !names.insert(def.name.clone()) {
+                return Err(effect_error(
+                    i,
+                    &def.name,
+                    "the name is used more than once",
+                ));
+            }
+            let name = def.name.clone();
+            build_effect(def).map_err(|message| effect_error(i, &name, &message))
+        })
+        .collect()
+}
+
+fn effect_error(index: usize, name: &str, message: &str) -> Error {
+    Error::new(
+        ErrorKind::InvalidData,
+        format!("effect {index} ({name:?}): {message}"),
+    )
+}
+
+/// Checks an effect's definition and turns it into a particle system.
+fn build_effect(def: EffectDef) -> std::result::Result<ParticleEffectDefinition, String> {
+    if def.name.is_empty() {
+        return Err("the name is empty".to_string());
+    }
+    let defaults = ParticleSystem::default();
+    let color = match def.color {
+        Some(color) => build_color(color)?,
+        None => defaults.color,
+    };
+    let particle_system = ParticleSystem {
+        max_particles: def.max_particles.unwrap_or(defaults.max_particles),
+        shape_id: defaults.shape_id,
+        layer: def.layer.map_or(defaults.layer, Layer),
+        blend_mode: def.blend_mode.unwrap_or(defaults.blend_mode),
+        spawn_rate_per_second: def
+            .spawn_rate_per_second
+            .map_or(defaults.spawn_rate_per_second, build_value),
+        spawn_radius: def
+            .spawn_radius
+            .map(build_jitter)
+            .transpose()?
+            .unwrap_or(defaults.spawn_radius),
+        emitter_shape: def.emitter_shape.unwrap_or(defaults.emitter_shape),
+        emitter_angle: def.emitter_angle.unwrap_or(defaults.emitter_angle),
+        initial_velocity: def
+            .initial_velocity
+            .map(build_jitter)
+            .transpose()?
+            .unwrap_or(defaults.initial_velocity),
+        acceleration: def.acceleration.map_or(defaults.acceleration, build_value),
+        lifetime: def
+            .lifetime
+            .map(build_jitter)
+            .transpose()?
+            .unwrap_or(defaults.lifetime),
+        color,
+        scale: def.scale.map_or(defaults.scale, build_value),
+        looping: def.looping.unwrap_or(defaults.looping),
+        system_duration_seconds: def
+            .system_duration_seconds
+            .unwrap_or(defaults.system_duration_seconds),
+        max_distance: def.max_distance.or(defaults.max_distance),
+        bursts: def
+            .bursts
+            .into_iter()
+            .map(|(time, count)| ParticleBurst::new(time, count))
+            .collect(),
+        use_scaled_time: def.use_scaled_time.unwrap_or(defaults.use_scaled_time),
+        despawn_on_finish: def.despawn_on_finish.unwrap_or(defaults.despawn_on_finish),
+    };
+    Ok(ParticleEffectDefinition {
+        name: def.name,
+        shape: def.shape,
+        particle_system,
+    })
+}
+
+fn build_value(def: ValueDef) -> ValueOverTime {
+    match def {
+        ValueDef::Constant(value) => ValueOverTime::Constant(value),
+        ValueDef::Lerp(from, to) => (from..to).into(),
+        ValueDef::Sin {
+            amplitude,
+            period,
+            phase_shift,
+            vertical_shift,
+        } => ValueOverTime::Sin(SinWave {
+            amplitude,
+            period,
+            phase_shift,
+            vertical_shift,
+        }),
+    }
+}
+
+/// Checks a jittered value and turns it into one.
+///
+/// Jitter ranges are checked here, since an empty or reversed range panics when a particle spawns
+/// with it.
+fn build_jitter(def: JitterDef) -> std::result::Result<JitteredValue, String> {
+    match def {
+        JitterDef::Constant(value) => Ok(JitteredValue::new(value)),
+        JitterDef::Jittered {
+            value,
+            jitter: (min, max),
+        } => {
+            if !(min.is_finite() && max.is_finite() && min < max) {
+                return Err(format!(
+                    "the jitter range ({min}, {max}) must be finite, with its minimum below its maximum"
+                ));
+            }
+            Ok(JitteredValue::jittered(value, min..max))
+        }
+    }
+}
+
+/// Checks a color and turns it into a color over time.
+///
+/// Gradients are checked here rather than by [`Gradient::new`], which only checks them in debug
+/// builds and panics.
+fn build_color(def: ColorDef) -> std::result::Result<ColorOverTime, String> {
+    let points = match def {
+        ColorDef::Constant(r, g, b, a) => return Ok(Vec4::new(r, g, b, a).into()),
+        ColorDef::Gradient(points) => points,
+    };
+    if points.len() < 2 {
+        return Err("a gradient needs at least two points".to_string());
+    }
+    if !points[0].0.roughly_equal(0.0) || !points[points.len() - 1].0.roughly_equal(1.0) {
+        return Err("a gradient must start at 0.0 and end at 1.0".to_string());
+    }
+    if points.windows(2).any(|pair| pair[0].0 >= pair[1].0) {
+        return Err("a gradient's points must be in increasing order".to_string());
+    }
+    Ok(ColorOverTime::Gradient(Gradient::new(
+        points
+            .into_iter()
+            .map(|(point, (r, g, b, a))| ColorPoint::new(Vec4::new(r, g, b, a), point))
+            .collect(),
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::ErrorKind;
+
+    use glam::Vec4;
+
+    use super::{parse_particle_effect_file, ParticleEffects};
+    use crate::particle_system::{
+        components::ParticleSystem,
+        values::{ColorOverTime, RoughlyEqual, ValueOverTime},
+    };
+    use crate::{material::BlendMode, shape_registry::ShapeRegistry};
+
+    #[test]
+    fn test_parse_particle_effect_file() {
+        let effects = parse_particle_effect_file(
+            r#"
+            ParticleEffectFile(
+                effects: [
+                    (
+                        name: "Sparks",
+                        shape: "Circle",
+                        max_particles: 500,
+                        layer: 3,
+                        blend_mode: Additive,
+                        initial_velocity: Jittered(value: 120.0, jitter: (-40.0, 40.0)),
+                        acceleration: Sin(amplitude: 5.0),
+                        color: Gradient([(0.0, (1.0, 0.9, 0.3, 1.0)), (1.0, (1.0, 0.2, 0.0, 0.0))]),
+                        scale: Lerp(8.0, 2.0),
+                        bursts: [(0.0, 50), (1.0, 20)],
+                    ),
+                    (name: "Dust", shape: "Square", color: Constant(0.5, 0.5, 0.5, 1.0)),
+                ],
+            )
+            "#,
+        )
+        .unwrap();
+        assert_eq!(effects.len(), 2);
+
+        let sparks = &effects[0];
+        assert_eq!(
+            (sparks.name.as_str(), sparks.shape.as_str()),
+            ("Sparks", "Circle")
+        );
+        let system = &sparks.particle_system;
+        assert_eq!(system.max_particles, 500);
+        assert_eq!(system.layer.0, 3);
+        assert_eq!(system.blend_mode, BlendMode::Additive);
+        assert_eq!(system.initial_velocity.value, 120.0);
+        assert_eq!(system.initial_velocity.jitter_range, Some(-40.0..40.0));
+        assert!(
+            matches!(&system.acceleration, ValueOverTime::Sin(wave) if wave.period.roughly_equal(1.0))
+        );
+        assert_eq!(system.scale.at_lifetime_pct(0.5), 5.0);
+        assert_eq!(
+            system.color.at_lifetime_pct(1.0),
+            Vec4::new(1.0, 0.2, 0.0, 0.0)
+        );
+        assert_eq!(system.bursts.len(), 2);
+
+        // Fields which are left out keep their defaults.
+        let dust = &effects[1].particle_system;
+        let defaults = ParticleSystem::default();
+        assert_eq!(dust.max_particles, defaults.max_particles);
+        assert_eq!(dust.layer, defaults.layer);
+        assert_eq!(dust.blend_mode, defaults.blend_mode);
+        assert!(matches!(dust.color, ColorOverTime::Constant(color) if color.x.roughly_equal(0.5)));
+    }
+
+    #[test]
+    fn test_example_particle_effect_file() {
+        let effects =
+            parse_particle_effect_file(include_str!("../../assets/particles/effects.ron")).unwrap();
+        let names = effects
+            .iter()
+            .map(|effect| effect.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["Exhaust", "Sparkles"]);
+    }
+
+    #[test]
+    fn test_particle_effect_file_errors() {
+        let error = |text: &str| parse_particle_effect_file(text).unwrap_err().to_string();
+
+        assert!(error("ParticleEffectFile(effects: [(name: \"A\")])").contains("shape"));
+        assert!(error(
+            "ParticleEffectFile(effects: [(name: \"A\", shape: \"Circle\", speed: 2.0)])"
+        )
+        .contains("speed"));
+        assert_eq!(
+            error(
+                "ParticleEffectFile(effects: [(name: \"A\", shape: \"Circle\"), (name: \"A\", shape: \"Circle\")])"
+            ),
+            "effect 1 (\"A\"): the name is used more than once"
+        );
+        assert!(error(
+            "ParticleEffectFile(effects: [(name: \"A\", shape: \"Circle\", color: Gradient([(0.0, (1.0, 1.0, 1.0, 1.0))]))])"
+        )
+        .contains("at least two points"));
+        assert!(error(
+            "ParticleEffectFile(effects: [(name: \"A\", shape: \"Circle\", color: Gradient([(0.0, (1.0, 1.0, 1.0, 1.0)), (0.5, (1.0, 1.0, 1.0, 1.0))]))])"
+        )
+        .contains("end at 1.0"));
+        assert!(error(
+            "ParticleEffectFile(effects: [(name: \"A\", shape: \"Circle\", lifetime: Jittered(value: 1.0, jitter: (0.0, 0.0)))])"
+        )
+        .contains("jitter range"));
+        assert!(error(
+            "ParticleEffectFile(effects: [(name: \"A\", shape: \"Circle\", spawn_radius: Jittered(value: 1.0, jitter: (5.0, -5.0)))])"
+        )
+        .contains("jitter range"));
+        assert!(error(
+            "ParticleEffectFile(effects: [(name: \"A\", shape: \"Circle\", initial_velocity: Jittered(value: 1.0, jitter: (0.0, inf)))])"
+        )
+        .contains("jitter range"));
+    }
+
+    #[test]
+    fn test_redefining_effects() {
+        let mut effects = ParticleEffects::default();
+        effects.insert("A".to_string(), ParticleSystem::default());
+        assert!(effects.changed.is_empty());
+
+        let faster = ParticleSystem {
+            max_particles: 7,
+            ..ParticleSystem::default()
+        };
+        effects.insert("A".to_string(), faster);
+        assert_eq!(effects.changed, ["A"]);
+        let bundle = effects.bundle("A").unwrap();
+        assert_eq!(bundle.particle_system.particle_system.max_particles, 7);
+        assert_eq!(bundle.effect.0, "A");
+        assert!(effects.bundle("B").is_none());
+    }
+
+    #[test]
+    fn test_missing_shape() {
+        let mut effects = ParticleEffects::default();
+        let mut definitions = parse_particle_effect_file(
+            "ParticleEffectFile(effects: [(name: \"A\", shape: \"Blob\")])",
+        )
+        .unwrap();
+        let err = effects
+            .insert_definition(definitions.remove(0), &ShapeRegistry::new())
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+        assert!(effects.get("A").is_none());
+    }
+}
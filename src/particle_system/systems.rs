@@ -12,6 +12,7 @@ use crate::{
             BurstIndex, Direction, Lifetime, Particle, ParticleBundle, ParticleCount,
             ParticleSystem, Playing, RunningState, TimeScale, Velocity,
         },
+        effects::update_particle_effects,
         values::ColorOverTime,
     },
     time::Time,
@@ -140,7 +141,9 @@ pub(crate) fn particle_spawner(
                     direction: Direction::new(direction),
                     ..ParticleBundle::default()
                 })
-                .insert_bundle(InstanceBundle::new(spawn_point));
+                .insert_bundle(InstanceBundle::new(spawn_point))
+                .insert(particle_system.layer)
+                .insert(particle_system.blend_mode);
         }
         // Don't count bursts in the normal spawn rate, but still count them in the particle cap.
         running_state.spawned_this_second += to_spawn;
@@ -251,4 +254,5 @@ pub fn system_set() -> SystemSet {
         .with_system(particle_color)
         .with_system(particle_transform)
         .with_system(particle_cleanup)
+        .with_system(update_particle_effects)
 }
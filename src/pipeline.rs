@@ -1,106 +1,348 @@
 use wgpu::{
-    include_wgsl,
-    util::{BufferInitDescriptor, DeviceExt},
     BindGroupDescriptor, BindGroupEntry, BindGroupLayoutDescriptor, BindGroupLayoutEntry,
-    BindingType, BlendState, BufferAddress, BufferBindingType, BufferDescriptor, BufferUsages,
+    BindingType, BufferAddress, BufferBindingType, BufferDescriptor, BufferUsages,
     ColorTargetState, ColorWrites, Device, Extent3d, Face, FragmentState, FrontFace,
-    MultisampleState, PipelineLayoutDescriptor, PolygonMode, PrimitiveState, PrimitiveTopology,
-    RenderPipelineDescriptor, ShaderStages, SurfaceConfiguration, TextureDescriptor,
-    TextureDimension, TextureUsages, TextureViewDescriptor, VertexState,
+    MultisampleState, PipelineLayout, PipelineLayoutDescriptor, PolygonMode, PrimitiveState,
+    PrimitiveTopology, RenderPipelineDescriptor, ShaderStages, SurfaceConfiguration,
+    TextureDescriptor, TextureDimension, TextureUsages, TextureViewDescriptor, VertexState,
 };
 
+use bevy_ecs::prelude::Entity;
+use glam::Vec2;
+
 use crate::{
-    camera::Camera2D,
+    camera::ViewMatrix,
     instance::{Inst, Instance2D},
+    light::{LIGHT_BUFFER_SIZE, SHADOW_EDGE_BUFFER_SIZE},
+    material::BlendMode,
     shape::Shape2DVertex,
+    util::FxHashMap,
     vertex::Vertex,
 };
 
-pub(crate) struct PrimShaderModules {
-    pub shape_shader_module: wgpu::ShaderModule,
-}
-
+#[allow(clippy::struct_field_names)]
 pub(crate) struct PrimBindGroupLayouts {
     pub camera_bind_group_layout: wgpu::BindGroupLayout,
+    pub light_bind_group_layout: wgpu::BindGroupLayout,
+    pub material_bind_group_layout: wgpu::BindGroupLayout,
+    /// Binds the texture of a camera feed to group 2.
+    pub camera_feed_bind_group_layout: wgpu::BindGroupLayout,
 }
 
-pub(crate) struct PrimPipelines {
-    pub shape_pipeline: wgpu::RenderPipeline,
+/// The pipeline layouts shared by every material's pipeline.
+#[allow(clippy::struct_field_names)]
+pub(crate) struct PrimPipelineLayouts {
+    /// Used by materials without any uniforms of their own.
+    pub shape_pipeline_layout: PipelineLayout,
+    /// Used by materials with uniforms, which are bound to group 2.
+    pub material_pipeline_layout: PipelineLayout,
+    /// Used by the camera feed material, which binds a camera's texture to group 2.
+    pub camera_feed_pipeline_layout: PipelineLayout,
 }
 
 pub(crate) struct PrimTargets {
     pub multisample_buffer: wgpu::TextureView,
+    /// The textures drawn into by cameras with a texture target, by camera entity.
+    pub render_textures: FxHashMap<Entity, RenderTexture>,
+}
+
+/// A texture a camera draws into, which can be sampled by the camera feed material.
+pub(crate) struct RenderTexture {
+    pub view: wgpu::TextureView,
+    /// Drawn into instead of `view` when multisampling, and resolved into it.
+    pub multisample_view: Option<wgpu::TextureView>,
+    /// Binds the texture to group 2 of the camera feed material.
+    pub bind_group: wgpu::BindGroup,
+    pub width: u32,
+    pub height: u32,
 }
 
+/// Where the final frame is written each render.
+pub(crate) enum PrimOutput {
+    /// Frames are presented to a window surface.
+    Surface(wgpu::Surface),
+    /// Frames are rendered into a texture which can be read back from the GPU.
+    Offscreen(OffscreenTarget),
+}
+
+pub(crate) struct OffscreenTarget {
+    pub texture: wgpu::Texture,
+}
+
+/// The number of instances the instance buffer can hold when first created.
+///
+/// The buffer grows as needed, and never shrinks below this size.
+pub(crate) const INITIAL_INSTANCE_CAPACITY: usize = 1024;
+
+/// The number of cameras the camera buffer can hold when first created.
+///
+/// The buffer grows as needed, and never shrinks below this size.
+pub(crate) const INITIAL_CAMERA_CAPACITY: usize = 4;
+
+/// The number of vertices the mesh buffer can hold when first created.
+///
+/// The buffer grows as needed, and never shrinks below this size.
+pub(crate) const INITIAL_MESH_CAPACITY: usize = 4096;
+
 pub(crate) struct PrimBuffers {
+    /// Holds the view matrix of each camera drawn this frame, one per `camera_stride` bytes.
     pub camera_buffer: wgpu::Buffer,
+    pub camera_capacity: usize,
+    /// The distance between each camera's view matrix, aligned for dynamic offsets.
+    pub camera_stride: u32,
     pub instance_buffer: wgpu::Buffer,
-    pub time_buffer: wgpu::Buffer,
+    pub instance_capacity: usize,
+    /// Holds the vertices of geometry built each frame, such as outlines.
+    pub mesh_buffer: wgpu::Buffer,
+    pub mesh_capacity: usize,
+    pub light_buffer: wgpu::Buffer,
+    pub shadow_edge_buffer: wgpu::Buffer,
+    /// Holds the [`Globals`] shared by every shader, bound next to the camera in group 0.
+    pub globals_buffer: wgpu::Buffer,
 }
 
-pub(crate) struct PrimBindGroups {
-    pub camera_bind_group: wgpu::BindGroup,
+/// Values shared by every shader, written once per frame.
+///
+/// Must match the layout of `Globals` in shader2d.wgsl.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct Globals {
+    /// Seconds since the engine started.
+    pub time: f32,
+    /// Seconds between the previous frame and this one.
+    pub delta_time: f32,
+    /// The number of frames before this one.
+    pub frame: u32,
+    _padding: u32,
+    /// The size of the window, or of the headless output, in pixels.
+    pub resolution: Vec2,
+    _padding_end: Vec2,
 }
 
-impl PrimShaderModules {
+impl Globals {
     #[must_use]
-    pub fn new(device: &Device) -> Self {
+    pub fn new(time: f32, delta_time: f32, frame: u32, resolution: Vec2) -> Self {
         Self {
-            shape_shader_module: device.create_shader_module(include_wgsl!("shader2d.wgsl")),
+            time,
+            delta_time,
+            frame,
+            resolution,
+            ..bytemuck::Zeroable::zeroed()
         }
     }
 }
 
+pub(crate) struct PrimBindGroups {
+    pub camera_bind_group: wgpu::BindGroup,
+    pub light_bind_group: wgpu::BindGroup,
+}
+
 impl PrimBindGroupLayouts {
     #[must_use]
     pub fn new(device: &Device) -> Self {
         Self {
             camera_bind_group_layout: device.create_bind_group_layout(&BindGroupLayoutDescriptor {
                 label: Some("Prim Camera Bind Group Layout"),
-                entries: &[BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: ShaderStages::VERTEX,
-                    ty: BindingType::Buffer {
-                        ty: BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
+                // Each camera's matrix is selected with a dynamic offset when it's drawn, while the
+                // globals are the same for every camera.
+                entries: &[
+                    BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: ShaderStages::VERTEX,
+                        ty: BindingType::Buffer {
+                            ty: BufferBindingType::Uniform,
+                            has_dynamic_offset: true,
+                            min_binding_size: wgpu::BufferSize::new(
+                                std::mem::size_of::<ViewMatrix>() as u64,
+                            ),
+                        },
+                        count: None,
+                    },
+                    BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: ShaderStages::VERTEX | ShaderStages::FRAGMENT,
+                        ty: BindingType::Buffer {
+                            ty: BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: wgpu::BufferSize::new(
+                                std::mem::size_of::<Globals>() as u64
+                            ),
+                        },
+                        count: None,
+                    },
+                ],
+            }),
+            // Lights use a uniform buffer rather than a storage buffer so that they also work
+            // with WebGL2, which has no storage buffers.
+            light_bind_group_layout: device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+                label: Some("Prim Light Bind Group Layout"),
+                entries: &[
+                    BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: ShaderStages::FRAGMENT,
+                        ty: BindingType::Buffer {
+                            ty: BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                    BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: ShaderStages::FRAGMENT,
+                        ty: BindingType::Buffer {
+                            ty: BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
                     },
-                    count: None,
-                }],
+                ],
             }),
+            material_bind_group_layout: device.create_bind_group_layout(
+                &BindGroupLayoutDescriptor {
+                    label: Some("Prim Material Bind Group Layout"),
+                    entries: &[BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: ShaderStages::VERTEX | ShaderStages::FRAGMENT,
+                        ty: BindingType::Buffer {
+                            ty: BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    }],
+                },
+            ),
+            camera_feed_bind_group_layout: device.create_bind_group_layout(
+                &BindGroupLayoutDescriptor {
+                    label: Some("Prim Camera Feed Bind Group Layout"),
+                    entries: &[
+                        BindGroupLayoutEntry {
+                            binding: 0,
+                            visibility: ShaderStages::FRAGMENT,
+                            ty: BindingType::Texture {
+                                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                                view_dimension: wgpu::TextureViewDimension::D2,
+                                multisampled: false,
+                            },
+                            count: None,
+                        },
+                        BindGroupLayoutEntry {
+                            binding: 1,
+                            visibility: ShaderStages::FRAGMENT,
+                            ty: BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                            count: None,
+                        },
+                    ],
+                },
+            ),
         }
     }
 }
 
-impl PrimPipelines {
+impl PrimPipelineLayouts {
     #[must_use]
-    pub fn new(
+    pub fn new(device: &Device, layouts: &PrimBindGroupLayouts) -> Self {
+        Self {
+            shape_pipeline_layout: device.create_pipeline_layout(&PipelineLayoutDescriptor {
+                label: Some("Shape Pipeline Layout"),
+                bind_group_layouts: &[
+                    &layouts.camera_bind_group_layout,
+                    &layouts.light_bind_group_layout,
+                ],
+                push_constant_ranges: &[],
+            }),
+            material_pipeline_layout: device.create_pipeline_layout(&PipelineLayoutDescriptor {
+                label: Some("Material Pipeline Layout"),
+                bind_group_layouts: &[
+                    &layouts.camera_bind_group_layout,
+                    &layouts.light_bind_group_layout,
+                    &layouts.material_bind_group_layout,
+                ],
+                push_constant_ranges: &[],
+            }),
+            camera_feed_pipeline_layout: device.create_pipeline_layout(&PipelineLayoutDescriptor {
+                label: Some("Camera Feed Pipeline Layout"),
+                bind_group_layouts: &[
+                    &layouts.camera_bind_group_layout,
+                    &layouts.light_bind_group_layout,
+                    &layouts.camera_feed_bind_group_layout,
+                ],
+                push_constant_ranges: &[],
+            }),
+        }
+    }
+
+    /// Creates a pipeline which draws shapes using the `vs_main` and `fs_main` entry points of the
+    /// given shader module, blending them with the given mode.
+    #[must_use]
+    pub fn create_shape_pipeline(
+        &self,
         device: &Device,
         config: &SurfaceConfiguration,
-        layouts: &PrimBindGroupLayouts,
-        shaders: &PrimShaderModules,
+        module: &wgpu::ShaderModule,
+        has_uniforms: bool,
+        blend_mode: BlendMode,
         multisample_count: u32,
-    ) -> Self {
-        let shape_pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
-            label: Some("Shape Pipeline Layout"),
-            bind_group_layouts: &[&layouts.camera_bind_group_layout],
-            push_constant_ranges: &[],
-        });
+    ) -> wgpu::RenderPipeline {
+        let layout = if has_uniforms {
+            &self.material_pipeline_layout
+        } else {
+            &self.shape_pipeline_layout
+        };
+        Self::create_pipeline(
+            device,
+            config,
+            module,
+            layout,
+            blend_mode,
+            multisample_count,
+        )
+    }
+
+    /// Creates a pipeline for the camera feed material, which samples a camera's texture.
+    #[must_use]
+    pub fn create_camera_feed_pipeline(
+        &self,
+        device: &Device,
+        config: &SurfaceConfiguration,
+        module: &wgpu::ShaderModule,
+        blend_mode: BlendMode,
+        multisample_count: u32,
+    ) -> wgpu::RenderPipeline {
+        Self::create_pipeline(
+            device,
+            config,
+            module,
+            &self.camera_feed_pipeline_layout,
+            blend_mode,
+            multisample_count,
+        )
+    }
 
-        let shape_pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
+    fn create_pipeline(
+        device: &Device,
+        config: &SurfaceConfiguration,
+        module: &wgpu::ShaderModule,
+        layout: &PipelineLayout,
+        blend_mode: BlendMode,
+        multisample_count: u32,
+    ) -> wgpu::RenderPipeline {
+        device.create_render_pipeline(&RenderPipelineDescriptor {
             label: Some("Shape Pipeline"),
-            layout: Some(&shape_pipeline_layout),
+            layout: Some(layout),
             vertex: VertexState {
-                module: &shaders.shape_shader_module,
+                module,
                 entry_point: "vs_main",
                 buffers: &[Shape2DVertex::desc(), Instance2D::desc()],
             },
             fragment: Some(FragmentState {
-                module: &shaders.shape_shader_module,
+                module,
                 entry_point: "fs_main",
                 targets: &[Some(ColorTargetState {
                     format: config.format,
-                    blend: Some(BlendState::ALPHA_BLENDING),
+                    blend: Some(blend_mode.blend_state()),
                     write_mask: ColorWrites::all(),
                 })],
             }),
@@ -119,15 +361,75 @@ impl PrimPipelines {
                 ..Default::default()
             },
             multiview: None,
-        });
-
-        Self { shape_pipeline }
+        })
     }
 }
 
 impl PrimTargets {
     #[must_use]
     pub fn new(device: &Device, config: &SurfaceConfiguration, sample_count: u32) -> Self {
+        Self {
+            multisample_buffer: Self::create_multisample_buffer(device, config, sample_count),
+            render_textures: FxHashMap::default(),
+        }
+    }
+
+    /// Recreates the targets which match the size of the output.
+    pub fn resize(&mut self, device: &Device, config: &SurfaceConfiguration, sample_count: u32) {
+        self.multisample_buffer = Self::create_multisample_buffer(device, config, sample_count);
+    }
+
+    /// Recreates every target to draw with a new sample count, keeping the size of each camera's
+    /// texture.
+    pub fn set_sample_count(
+        &mut self,
+        device: &Device,
+        config: &SurfaceConfiguration,
+        layouts: &PrimBindGroupLayouts,
+        sample_count: u32,
+    ) {
+        self.resize(device, config, sample_count);
+        for texture in self.render_textures.values_mut() {
+            *texture = RenderTexture::new(
+                device,
+                config,
+                layouts,
+                sample_count,
+                texture.width,
+                texture.height,
+            );
+        }
+    }
+
+    /// Makes sure the camera has a texture of the given size to draw into, recreating it if its
+    /// size has changed.
+    pub fn fit_render_texture(
+        &mut self,
+        device: &Device,
+        config: &SurfaceConfiguration,
+        layouts: &PrimBindGroupLayouts,
+        sample_count: u32,
+        camera: Entity,
+        (width, height): (u32, u32),
+    ) {
+        if self
+            .render_textures
+            .get(&camera)
+            .is_some_and(|texture| (texture.width, texture.height) == (width, height))
+        {
+            return;
+        }
+        self.render_textures.insert(
+            camera,
+            RenderTexture::new(device, config, layouts, sample_count, width, height),
+        );
+    }
+
+    fn create_multisample_buffer(
+        device: &Device,
+        config: &SurfaceConfiguration,
+        sample_count: u32,
+    ) -> wgpu::TextureView {
         let texture_extent = Extent3d {
             width: config.width,
             height: config.height,
@@ -144,41 +446,265 @@ impl PrimTargets {
             usage: TextureUsages::RENDER_ATTACHMENT,
         };
 
+        device
+            .create_texture(frame_descriptor)
+            .create_view(&TextureViewDescriptor::default())
+    }
+}
+
+impl RenderTexture {
+    #[must_use]
+    fn new(
+        device: &Device,
+        config: &SurfaceConfiguration,
+        layouts: &PrimBindGroupLayouts,
+        sample_count: u32,
+        width: u32,
+        height: u32,
+    ) -> Self {
+        let descriptor = TextureDescriptor {
+            label: Some("Camera Render Texture"),
+            size: Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            // Materials are built for the output's format, so cameras draw in the same one.
+            format: config.format,
+            usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+        };
+        let view = device
+            .create_texture(&descriptor)
+            .create_view(&TextureViewDescriptor::default());
+        let multisample_view = (sample_count > 1).then(|| {
+            device
+                .create_texture(&TextureDescriptor {
+                    label: Some("Camera Multisample Texture"),
+                    sample_count,
+                    usage: TextureUsages::RENDER_ATTACHMENT,
+                    ..descriptor
+                })
+                .create_view(&TextureViewDescriptor::default())
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Camera Feed Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let bind_group = device.create_bind_group(&BindGroupDescriptor {
+            label: Some("Camera Feed Bind Group"),
+            layout: &layouts.camera_feed_bind_group_layout,
+            entries: &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+        });
+
         Self {
-            multisample_buffer: device
-                .create_texture(frame_descriptor)
-                .create_view(&TextureViewDescriptor::default()),
+            view,
+            multisample_view,
+            bind_group,
+            width,
+            height,
+        }
+    }
+}
+
+impl PrimOutput {
+    /// Gets the texture view to render the next frame into.
+    ///
+    /// When rendering to a surface, the surface texture is also returned and must be presented once
+    /// rendering is complete.
+    pub fn acquire(
+        &self,
+    ) -> Result<(Option<wgpu::SurfaceTexture>, wgpu::TextureView), wgpu::SurfaceError> {
+        match self {
+            Self::Surface(surface) => {
+                let output = surface.get_current_texture()?;
+                let view = output
+                    .texture
+                    .create_view(&TextureViewDescriptor::default());
+                Ok((Some(output), view))
+            }
+            Self::Offscreen(target) => Ok((
+                None,
+                target
+                    .texture
+                    .create_view(&TextureViewDescriptor::default()),
+            )),
+        }
+    }
+
+    /// Applies a changed configuration, such as a new size, to the output.
+    pub fn configure(&mut self, device: &Device, config: &SurfaceConfiguration) {
+        match self {
+            Self::Surface(surface) => surface.configure(device, config),
+            Self::Offscreen(target) => *target = OffscreenTarget::new(device, config),
+        }
+    }
+}
+
+impl OffscreenTarget {
+    #[must_use]
+    pub fn new(device: &Device, config: &SurfaceConfiguration) -> Self {
+        Self {
+            texture: device.create_texture(&TextureDescriptor {
+                label: Some("Offscreen Target"),
+                size: Extent3d {
+                    width: config.width,
+                    height: config.height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format: config.format,
+                usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC,
+            }),
         }
     }
 }
 
 impl PrimBuffers {
     #[must_use]
-    pub fn new(
-        device: &Device,
-        #[allow(unused)] config: &SurfaceConfiguration,
-        camera: &Camera2D,
-    ) -> Self {
+    pub fn new(device: &Device, #[allow(unused)] config: &SurfaceConfiguration) -> Self {
+        #[allow(clippy::cast_possible_truncation)]
+        let camera_stride = (std::mem::size_of::<ViewMatrix>() as u32)
+            .max(device.limits().min_uniform_buffer_offset_alignment);
         Self {
-            camera_buffer: device.create_buffer_init(&BufferInitDescriptor {
-                label: Some("Camera Buffer"),
-                contents: bytemuck::cast_slice(&[camera.get_view()]),
+            camera_buffer: Self::create_camera_buffer(
+                device,
+                camera_stride,
+                INITIAL_CAMERA_CAPACITY,
+            ),
+            camera_capacity: INITIAL_CAMERA_CAPACITY,
+            camera_stride,
+            instance_buffer: Self::create_instance_buffer(device, INITIAL_INSTANCE_CAPACITY),
+            instance_capacity: INITIAL_INSTANCE_CAPACITY,
+            mesh_buffer: Self::create_mesh_buffer(device, INITIAL_MESH_CAPACITY),
+            mesh_capacity: INITIAL_MESH_CAPACITY,
+            light_buffer: device.create_buffer(&BufferDescriptor {
+                label: Some("Light Buffer"),
+                size: LIGHT_BUFFER_SIZE as BufferAddress,
                 usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+                mapped_at_creation: false,
             }),
-            instance_buffer: device.create_buffer(&BufferDescriptor {
-                label: Some("Instance Buffer"),
-                size: (std::mem::size_of::<Inst>() * 100_000) as BufferAddress,
-                usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
+            shadow_edge_buffer: device.create_buffer(&BufferDescriptor {
+                label: Some("Shadow Edge Buffer"),
+                size: SHADOW_EDGE_BUFFER_SIZE as BufferAddress,
+                usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
                 mapped_at_creation: false,
             }),
-            time_buffer: device.create_buffer(&BufferDescriptor {
-                label: Some("Time Buffer"),
-                size: std::mem::size_of::<f32>() as BufferAddress,
+            globals_buffer: device.create_buffer(&BufferDescriptor {
+                label: Some("Globals Buffer"),
+                size: std::mem::size_of::<Globals>() as BufferAddress,
                 usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
                 mapped_at_creation: false,
             }),
         }
     }
+
+    fn create_camera_buffer(device: &Device, stride: u32, capacity: usize) -> wgpu::Buffer {
+        device.create_buffer(&BufferDescriptor {
+            label: Some("Camera Buffer"),
+            size: BufferAddress::from(stride) * capacity as BufferAddress,
+            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    fn create_instance_buffer(device: &Device, capacity: usize) -> wgpu::Buffer {
+        device.create_buffer(&BufferDescriptor {
+            label: Some("Instance Buffer"),
+            size: (std::mem::size_of::<Inst>() * capacity) as BufferAddress,
+            usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    fn create_mesh_buffer(device: &Device, capacity: usize) -> wgpu::Buffer {
+        device.create_buffer(&BufferDescriptor {
+            label: Some("Mesh Buffer"),
+            size: (std::mem::size_of::<Shape2DVertex>() * capacity) as BufferAddress,
+            usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    /// Grows or shrinks the instance buffer so that it can hold `instance_count` instances.
+    ///
+    /// Returns `true` if the buffer was reallocated. Any previous contents are discarded, so this
+    /// should be called before writing the frame's instances.
+    pub fn fit_instance_buffer(&mut self, device: &Device, instance_count: usize) -> bool {
+        let capacity = instance_buffer_capacity(self.instance_capacity, instance_count);
+        if capacity == self.instance_capacity {
+            return false;
+        }
+
+        self.instance_buffer = Self::create_instance_buffer(device, capacity);
+        self.instance_capacity = capacity;
+        true
+    }
+
+    /// Grows or shrinks the camera buffer so that it can hold `camera_count` view matrices.
+    ///
+    /// Returns `true` if the buffer was reallocated, in which case the camera bind group must be
+    /// recreated to use it.
+    pub fn fit_camera_buffer(&mut self, device: &Device, camera_count: usize) -> bool {
+        let capacity = buffer_capacity(self.camera_capacity, camera_count, INITIAL_CAMERA_CAPACITY);
+        if capacity == self.camera_capacity {
+            return false;
+        }
+
+        self.camera_buffer = Self::create_camera_buffer(device, self.camera_stride, capacity);
+        self.camera_capacity = capacity;
+        true
+    }
+
+    /// Grows or shrinks the mesh buffer so that it can hold `vertex_count` vertices.
+    ///
+    /// Returns `true` if the buffer was reallocated, discarding its previous contents.
+    pub fn fit_mesh_buffer(&mut self, device: &Device, vertex_count: usize) -> bool {
+        let capacity = buffer_capacity(self.mesh_capacity, vertex_count, INITIAL_MESH_CAPACITY);
+        if capacity == self.mesh_capacity {
+            return false;
+        }
+
+        self.mesh_buffer = Self::create_mesh_buffer(device, capacity);
+        self.mesh_capacity = capacity;
+        true
+    }
+}
+
+/// Determines the capacity the instance buffer should have for the number of instances required.
+fn instance_buffer_capacity(current: usize, required: usize) -> usize {
+    buffer_capacity(current, required, INITIAL_INSTANCE_CAPACITY)
+}
+
+/// Determines the capacity a growable buffer should have for the number of items required.
+///
+/// The buffer grows to the next power of two when it is too small. It only shrinks once usage drops
+/// below a quarter of the capacity, and then to twice the required size but never below `minimum`,
+/// so that a scene hovering around a boundary doesn't reallocate every frame.
+fn buffer_capacity(current: usize, required: usize, minimum: usize) -> usize {
+    if required > current {
+        required.next_power_of_two()
+    } else if current > minimum && required < current / 4 {
+        (required.next_power_of_two() * 2).max(minimum)
+    } else {
+        current
+    }
 }
 
 impl PrimBindGroups {
@@ -190,14 +716,81 @@ impl PrimBindGroups {
         buffers: &PrimBuffers,
     ) -> Self {
         Self {
-            camera_bind_group: device.create_bind_group(&BindGroupDescriptor {
-                label: Some("Camera Bind Group"),
-                layout: &layouts.camera_bind_group_layout,
-                entries: &[BindGroupEntry {
-                    binding: 0,
-                    resource: buffers.camera_buffer.as_entire_binding(),
-                }],
+            camera_bind_group: Self::create_camera_bind_group(device, layouts, buffers),
+            light_bind_group: device.create_bind_group(&BindGroupDescriptor {
+                label: Some("Light Bind Group"),
+                layout: &layouts.light_bind_group_layout,
+                entries: &[
+                    BindGroupEntry {
+                        binding: 0,
+                        resource: buffers.light_buffer.as_entire_binding(),
+                    },
+                    BindGroupEntry {
+                        binding: 1,
+                        resource: buffers.shadow_edge_buffer.as_entire_binding(),
+                    },
+                ],
             }),
         }
     }
+
+    /// Creates the bind group for the camera buffer and the globals, which must be recreated
+    /// whenever the camera buffer is reallocated.
+    #[must_use]
+    pub fn create_camera_bind_group(
+        device: &Device,
+        layouts: &PrimBindGroupLayouts,
+        buffers: &PrimBuffers,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&BindGroupDescriptor {
+            label: Some("Camera Bind Group"),
+            layout: &layouts.camera_bind_group_layout,
+            entries: &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                        buffer: &buffers.camera_buffer,
+                        offset: 0,
+                        size: wgpu::BufferSize::new(std::mem::size_of::<ViewMatrix>() as u64),
+                    }),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: buffers.globals_buffer.as_entire_binding(),
+                },
+            ],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{instance_buffer_capacity, INITIAL_INSTANCE_CAPACITY};
+
+    #[test]
+    fn test_instance_buffer_grows() {
+        assert_eq!(
+            instance_buffer_capacity(INITIAL_INSTANCE_CAPACITY, 10),
+            INITIAL_INSTANCE_CAPACITY
+        );
+        assert_eq!(
+            instance_buffer_capacity(INITIAL_INSTANCE_CAPACITY, INITIAL_INSTANCE_CAPACITY + 1),
+            INITIAL_INSTANCE_CAPACITY * 2
+        );
+        assert_eq!(instance_buffer_capacity(1024, 150_000), 262_144);
+    }
+
+    #[test]
+    fn test_instance_buffer_shrinks() {
+        // Stays put until usage drops below a quarter of the capacity.
+        assert_eq!(instance_buffer_capacity(262_144, 70_000), 262_144);
+        assert_eq!(instance_buffer_capacity(262_144, 60_000), 131_072);
+        assert_eq!(
+            instance_buffer_capacity(262_144, 0),
+            INITIAL_INSTANCE_CAPACITY
+        );
+        // Shrinking leaves room to grow without immediately reallocating again.
+        let shrunk = instance_buffer_capacity(262_144, 60_000);
+        assert_eq!(instance_buffer_capacity(shrunk, 60_000), shrunk);
+    }
 }
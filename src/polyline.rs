@@ -0,0 +1,148 @@
+use bevy_ecs::prelude::Component;
+use glam::{Vec2, Vec4};
+
+use crate::stroke::{stroke_path, LineCap, LineJoin, StrokeStyle, StrokeUnits};
+
+/// A thick line drawn through a list of points.
+///
+/// Polylines are tessellated into triangles every frame, so their points can be changed freely.
+/// Points are given in world space, and the line is drawn on the [`libprim::instance::Layer`] of
+/// its entity if it has one.
+///
+/// A closed polyline joins its last point back to the first, which can be used to draw the
+/// wireframe of a custom shape.
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct Polyline {
+    /// The points the line passes through, in world space.
+    pub points: Vec<Vec2>,
+    /// The width of the line, measured in `units`.
+    pub width: f32,
+    /// The color of the line.
+    pub color: Vec4,
+    /// The units the width is measured in.
+    pub units: StrokeUnits,
+    /// How the corners of the line are drawn.
+    pub join: LineJoin,
+    /// How the ends of the line are drawn. Closed lines have no ends.
+    pub cap: LineCap,
+    /// Whether the last point is joined back to the first.
+    pub closed: bool,
+}
+
+impl Default for Polyline {
+    fn default() -> Self {
+        Self {
+            points: Vec::new(),
+            width: 1.0,
+            color: Vec4::ONE,
+            units: StrokeUnits::World,
+            join: LineJoin::Miter,
+            cap: LineCap::Butt,
+            closed: false,
+        }
+    }
+}
+
+impl Polyline {
+    /// Creates an open line through the given points, with a width in world units.
+    ///
+    /// ## Example
+    /// ```
+    /// # use libprim::{polyline::Polyline, stroke::LineCap, Vec2, Vec4};
+    /// let beam = Polyline::new(Vec::from([Vec2::ZERO, Vec2::new(100.0, 0.0)]), 4.0, Vec4::ONE)
+    ///     .with_cap(LineCap::Round);
+    /// ```
+    #[must_use]
+    pub fn new(points: Vec<Vec2>, width: f32, color: Vec4) -> Self {
+        Self {
+            points,
+            width,
+            color,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the units the width of the line is measured in.
+    #[must_use]
+    pub fn with_units(mut self, units: StrokeUnits) -> Self {
+        self.units = units;
+        self
+    }
+
+    /// Sets how the corners of the line are drawn.
+    #[must_use]
+    pub fn with_join(mut self, join: LineJoin) -> Self {
+        self.join = join;
+        self
+    }
+
+    /// Sets how the ends of the line are drawn.
+    #[must_use]
+    pub fn with_cap(mut self, cap: LineCap) -> Self {
+        self.cap = cap;
+        self
+    }
+
+    /// Sets whether the last point is joined back to the first.
+    #[must_use]
+    pub fn with_closed(mut self, closed: bool) -> Self {
+        self.closed = closed;
+        self
+    }
+
+    /// Returns the corners of a box containing the whole line, or `None` if it has no points.
+    ///
+    /// `pixel_size` is the size of a screen pixel in world units.
+    pub(crate) fn bounds(&self, pixel_size: f32) -> Option<(Vec2, Vec2)> {
+        let first = *self.points.first()?;
+        let (min, max) = self
+            .points
+            .iter()
+            .fold((first, first), |(min, max), &p| (min.min(p), max.max(p)));
+        // Miter joins can reach further than the width of the line.
+        let reach = Vec2::splat(self.units.to_world(self.width, pixel_size) * 2.0);
+        Some((min - reach, max + reach))
+    }
+
+    /// Builds the triangles of the line, appending them to `triangles`.
+    ///
+    /// `pixel_size` is the size of a screen pixel in world units.
+    pub(crate) fn triangles(&self, pixel_size: f32, triangles: &mut Vec<Vec2>) {
+        let style = StrokeStyle {
+            width: self.units.to_world(self.width, pixel_size),
+            offset: 0.0,
+            join: self.join,
+            cap: self.cap,
+        };
+        stroke_path(&self.points, self.closed, &style, triangles);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{Vec2, Vec4};
+
+    use crate::stroke::StrokeUnits;
+
+    use super::Polyline;
+
+    #[test]
+    fn test_polyline_bounds() {
+        assert_eq!(Polyline::default().bounds(1.0), None);
+
+        let line = Polyline::new(
+            Vec::from([Vec2::new(-1.0, 2.0), Vec2::new(3.0, -4.0), Vec2::ZERO]),
+            1.0,
+            Vec4::ONE,
+        );
+        assert_eq!(
+            line.bounds(1.0),
+            Some((Vec2::new(-3.0, -6.0), Vec2::new(5.0, 4.0)))
+        );
+        // Screen widths grow with the size of a pixel.
+        assert_eq!(
+            line.with_units(StrokeUnits::Screen).bounds(0.5),
+            Some((Vec2::new(-2.0, -5.0), Vec2::new(4.0, 3.0)))
+        );
+    }
+}
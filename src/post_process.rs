@@ -0,0 +1,948 @@
+use std::{
+    num::NonZeroU64,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use glam::Vec3;
+use wgpu::util::DeviceExt;
+
+use crate::util::FxHashMap;
+
+/// An ordered list of full-screen effects applied to each frame after the world is drawn.
+///
+/// Accessible and modifiable through a bevy Resource.
+///
+/// Effects are applied in order, each one reading the output of the previous effect. When the
+/// chain is empty, the world is drawn directly to the screen with no extra cost. Text is drawn
+/// after post-processing, so it is never affected by the chain.
+#[derive(Debug, Clone, Default)]
+pub struct PostProcessChain {
+    /// The effects to apply, in order.
+    pub effects: Vec<PostEffect>,
+}
+
+impl PostProcessChain {
+    /// Creates an empty chain.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an effect to the end of the chain.
+    #[must_use]
+    pub fn with_effect(mut self, effect: PostEffect) -> Self {
+        self.effects.push(effect);
+        self
+    }
+}
+
+/// A single full-screen effect in a [`PostProcessChain`].
+#[derive(Debug, Clone)]
+pub enum PostEffect {
+    /// Makes bright colors glow.
+    Bloom(Bloom),
+    /// Darkens or tints the edges of the screen.
+    Vignette(Vignette),
+    /// Splits the red and blue channels apart towards the edges of the screen.
+    ChromaticAberration(ChromaticAberration),
+    /// Darkens alternating rows of pixels like an old CRT monitor.
+    Scanlines(Scanlines),
+    /// Remaps colors through a color look up table.
+    ColorGrade(ColorGrade),
+}
+
+/// Parameters for the [`PostEffect::Bloom`] effect.
+#[derive(Debug, Clone, Copy)]
+pub struct Bloom {
+    /// How bright a color needs to be before it starts to glow.
+    pub threshold: f32,
+    /// How strongly the glow is added back to the frame.
+    pub intensity: f32,
+    /// How far the glow spreads, in pixels.
+    pub radius: f32,
+}
+
+impl Default for Bloom {
+    fn default() -> Self {
+        Self {
+            threshold: 0.6,
+            intensity: 1.0,
+            radius: 8.0,
+        }
+    }
+}
+
+/// Parameters for the [`PostEffect::Vignette`] effect.
+#[derive(Debug, Clone, Copy)]
+pub struct Vignette {
+    /// How strongly the edges are tinted, from `0.0` to `1.0`.
+    pub intensity: f32,
+    /// The distance from the center at which the vignette is fully applied, where `1.0` is the
+    /// corner of the screen.
+    pub radius: f32,
+    /// How far inwards from the ``radius`` the vignette fades in.
+    pub softness: f32,
+    /// The color the edges are tinted towards.
+    pub color: Vec3,
+}
+
+impl Default for Vignette {
+    fn default() -> Self {
+        Self {
+            intensity: 0.6,
+            radius: 1.0,
+            softness: 0.6,
+            color: Vec3::ZERO,
+        }
+    }
+}
+
+/// Parameters for the [`PostEffect::ChromaticAberration`] effect.
+#[derive(Debug, Clone, Copy)]
+pub struct ChromaticAberration {
+    /// How far apart the color channels are at the edges of the screen, in pixels.
+    pub strength: f32,
+}
+
+impl Default for ChromaticAberration {
+    fn default() -> Self {
+        Self { strength: 4.0 }
+    }
+}
+
+/// Parameters for the [`PostEffect::Scanlines`] effect.
+#[derive(Debug, Clone, Copy)]
+pub struct Scanlines {
+    /// How dark the gaps between lines are, from `0.0` to `1.0`.
+    pub intensity: f32,
+    /// The height of each line, in pixels.
+    pub line_height: f32,
+}
+
+impl Default for Scanlines {
+    fn default() -> Self {
+        Self {
+            intensity: 0.3,
+            line_height: 3.0,
+        }
+    }
+}
+
+/// Parameters for the [`PostEffect::ColorGrade`] effect.
+#[derive(Debug, Clone)]
+pub struct ColorGrade {
+    /// The look up table colors are remapped through.
+    pub lut: Arc<ColorGradeLut>,
+    /// How much of the graded color is used, from `0.0` to `1.0`.
+    pub intensity: f32,
+}
+
+impl ColorGrade {
+    /// Creates a color grade which fully applies the given look up table.
+    #[must_use]
+    pub fn new(lut: ColorGradeLut) -> Self {
+        Self {
+            lut: Arc::new(lut),
+            intensity: 1.0,
+        }
+    }
+}
+
+static NEXT_LUT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// A 3D color look up table used by [`ColorGrade`].
+///
+/// Look up tables map sRGB encoded colors to new sRGB encoded colors, and are uploaded to the
+/// GPU the first time they are used.
+#[derive(Debug)]
+pub struct ColorGradeLut {
+    id: u64,
+    size: u32,
+    data: Vec<u8>,
+}
+
+impl ColorGradeLut {
+    /// Creates a look up table from raw RGBA data.
+    ///
+    /// The data holds `size * size * size` colors with red changing fastest, then green, then blue.
+    ///
+    /// # Errors
+    /// Returns an [`std::io::Error`] if the data is not the expected length.
+    pub fn new(size: u32, data: Vec<u8>) -> std::io::Result<Self> {
+        let expected = (size as usize).pow(3) * 4;
+        if size < 2 || data.len() != expected {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!(
+                    "A look up table of size {size} needs {expected} bytes, got {}",
+                    data.len()
+                ),
+            ));
+        }
+
+        Ok(Self {
+            id: NEXT_LUT_ID.fetch_add(1, Ordering::Relaxed),
+            size,
+            data,
+        })
+    }
+
+    /// Creates a look up table which leaves colors unchanged, as a starting point for edits.
+    ///
+    /// # Panics
+    /// Panics if ``size`` is less than `2` or greater than `256`.
+    #[must_use]
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss
+    )]
+    pub fn identity(size: u32) -> Self {
+        assert!((2..=256).contains(&size), "Invalid look up table size");
+        let scale = 255.0 / (size - 1) as f32;
+        let mut data = Vec::with_capacity((size as usize).pow(3) * 4);
+        for b in 0..size {
+            for g in 0..size {
+                for r in 0..size {
+                    data.extend_from_slice(&[
+                        (r as f32 * scale).round() as u8,
+                        (g as f32 * scale).round() as u8,
+                        (b as f32 * scale).round() as u8,
+                        255,
+                    ]);
+                }
+            }
+        }
+        Self::new(size, data).expect("Identity look up table has the correct size")
+    }
+
+    /// Loads a look up table from a PNG in the common horizontal strip layout.
+    ///
+    /// The image is `size * size` pixels wide and `size` pixels tall, made of `size` square
+    /// slices of increasing blue. Within each slice red increases to the right and green downwards.
+    ///
+    /// # Errors
+    /// Returns an [`std::io::Error`] if the image can't be decoded or isn't a valid strip.
+    pub fn from_strip_png(bytes: &[u8]) -> std::io::Result<Self> {
+        let mut decoder = png::Decoder::new(bytes);
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info().map_err(std::io::Error::other)?;
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader
+            .next_frame(&mut pixels)
+            .map_err(std::io::Error::other)?;
+
+        let size = info.height;
+        if info.width != size * size {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "A look up table strip must be size * size pixels wide and size pixels tall",
+            ));
+        }
+        let channels = match info.color_type {
+            png::ColorType::Rgb => 3,
+            png::ColorType::Rgba => 4,
+            png::ColorType::Grayscale
+            | png::ColorType::GrayscaleAlpha
+            | png::ColorType::Indexed => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    "A look up table must be an RGB or RGBA image",
+                ))
+            }
+        };
+
+        let size = size as usize;
+        let mut data = Vec::with_capacity(size.pow(3) * 4);
+        for b in 0..size {
+            for g in 0..size {
+                for r in 0..size {
+                    let offset = g * info.line_size + (b * size + r) * channels;
+                    data.extend_from_slice(&pixels[offset..offset + 3]);
+                    data.push(255);
+                }
+            }
+        }
+
+        #[allow(clippy::cast_possible_truncation)]
+        Self::new(size as u32, data)
+    }
+
+    /// The number of entries along each axis of the table.
+    #[must_use]
+    #[inline(always)]
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+}
+
+/// The textures an effect can read from or write to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PostTarget {
+    /// The frame the world was drawn into.
+    Scene,
+    Ping,
+    Pong,
+    BloomA,
+    BloomB,
+    /// The final output, such as the window surface.
+    Output,
+}
+
+/// The fragment shaders used by post-processing passes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PostKind {
+    BloomExtract,
+    BlurVertical,
+    BloomComposite,
+    Vignette,
+    ChromaticAberration,
+    Scanlines,
+    ColorGrade,
+    /// Copies the input unchanged, for moving a finished frame between textures.
+    Copy,
+}
+
+impl PostKind {
+    const ALL: [PostKind; 8] = [
+        PostKind::BloomExtract,
+        PostKind::BlurVertical,
+        PostKind::BloomComposite,
+        PostKind::Vignette,
+        PostKind::ChromaticAberration,
+        PostKind::Scanlines,
+        PostKind::ColorGrade,
+        PostKind::Copy,
+    ];
+
+    fn entry_point(self) -> &'static str {
+        match self {
+            Self::BloomExtract => "fs_bloom_extract",
+            Self::BlurVertical => "fs_blur_vertical",
+            Self::BloomComposite => "fs_bloom_composite",
+            Self::Vignette => "fs_vignette",
+            Self::ChromaticAberration => "fs_chromatic_aberration",
+            Self::Scanlines => "fs_scanlines",
+            Self::ColorGrade => "fs_color_grade",
+            Self::Copy => "fs_copy",
+        }
+    }
+}
+
+/// The uniform data for a single pass, stored in its own aligned slot of the uniform buffer.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, bytemuck::Pod, bytemuck::Zeroable)]
+struct PostUniform {
+    params: [f32; 4],
+    color: [f32; 4],
+    texel: [f32; 4],
+}
+
+/// A single full-screen draw.
+#[derive(Debug, Clone, Copy)]
+struct PostPass {
+    kind: PostKind,
+    input: PostTarget,
+    secondary: Option<PostTarget>,
+    output: PostTarget,
+    uniform: PostUniform,
+    lut: Option<u64>,
+}
+
+/// Breaks the chain's effects down into the passes needed to draw them.
+///
+/// Each effect reads the result of the previous one, alternating between two intermediate
+/// targets, and the last effect writes to the output.
+fn plan_passes(effects: &[PostEffect], linear_output: bool) -> Vec<PostPass> {
+    let mut passes = Vec::with_capacity(effects.len());
+    let mut input = PostTarget::Scene;
+    for (i, effect) in effects.iter().enumerate() {
+        let output = if i == effects.len() - 1 {
+            PostTarget::Output
+        } else if input == PostTarget::Ping {
+            PostTarget::Pong
+        } else {
+            PostTarget::Ping
+        };
+        let pass = |kind, params, color| PostPass {
+            kind,
+            input,
+            secondary: None,
+            output,
+            uniform: PostUniform {
+                params,
+                color,
+                texel: [0.0; 4],
+            },
+            lut: None,
+        };
+
+        match effect {
+            PostEffect::Bloom(bloom) => {
+                let params = [bloom.threshold, bloom.radius, bloom.intensity, 0.0];
+                passes.push(PostPass {
+                    output: PostTarget::BloomA,
+                    ..pass(PostKind::BloomExtract, params, [0.0; 4])
+                });
+                passes.push(PostPass {
+                    input: PostTarget::BloomA,
+                    output: PostTarget::BloomB,
+                    ..pass(PostKind::BlurVertical, params, [0.0; 4])
+                });
+                passes.push(PostPass {
+                    secondary: Some(PostTarget::BloomB),
+                    ..pass(PostKind::BloomComposite, params, [0.0; 4])
+                });
+            }
+            PostEffect::Vignette(vignette) => passes.push(pass(
+                PostKind::Vignette,
+                [vignette.intensity, vignette.radius, vignette.softness, 0.0],
+                vignette.color.extend(1.0).into(),
+            )),
+            PostEffect::ChromaticAberration(aberration) => passes.push(pass(
+                PostKind::ChromaticAberration,
+                [aberration.strength, 0.0, 0.0, 0.0],
+                [0.0; 4],
+            )),
+            PostEffect::Scanlines(scanlines) => passes.push(pass(
+                PostKind::Scanlines,
+                [scanlines.intensity, scanlines.line_height, 0.0, 0.0],
+                [0.0; 4],
+            )),
+            #[allow(clippy::cast_precision_loss)]
+            PostEffect::ColorGrade(grade) => passes.push(PostPass {
+                lut: Some(grade.lut.id),
+                ..pass(
+                    PostKind::ColorGrade,
+                    [
+                        grade.intensity,
+                        if linear_output { 1.0 } else { 0.0 },
+                        grade.lut.size as f32,
+                        0.0,
+                    ],
+                    [0.0; 4],
+                )
+            }),
+        }
+        input = output;
+    }
+    passes
+}
+
+/// The intermediate textures effects are drawn between.
+struct PostTargets {
+    scene: wgpu::TextureView,
+    ping: wgpu::TextureView,
+    pong: wgpu::TextureView,
+    bloom_a: wgpu::TextureView,
+    bloom_b: wgpu::TextureView,
+}
+
+impl PostTargets {
+    fn new(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) -> Self {
+        let create = |label| {
+            device
+                .create_texture(&wgpu::TextureDescriptor {
+                    label: Some(label),
+                    size: wgpu::Extent3d {
+                        width: config.width,
+                        height: config.height,
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: config.format,
+                    usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                        | wgpu::TextureUsages::TEXTURE_BINDING,
+                })
+                .create_view(&wgpu::TextureViewDescriptor::default())
+        };
+
+        Self {
+            scene: create("Post Process Scene Target"),
+            ping: create("Post Process Ping Target"),
+            pong: create("Post Process Pong Target"),
+            bloom_a: create("Post Process Bloom Target A"),
+            bloom_b: create("Post Process Bloom Target B"),
+        }
+    }
+}
+
+/// Owns the GPU resources for running a [`PostProcessChain`].
+///
+/// Intermediate targets are only created once the chain has effects in it.
+pub(crate) struct PostProcessor {
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipelines: Vec<(PostKind, wgpu::RenderPipeline)>,
+    sampler: wgpu::Sampler,
+    uniform_buffer: wgpu::Buffer,
+    uniform_capacity: usize,
+    uniform_stride: u32,
+    targets: Option<PostTargets>,
+    empty_texture: wgpu::TextureView,
+    empty_lut: wgpu::TextureView,
+    luts: FxHashMap<u64, wgpu::TextureView>,
+    passes: Vec<PostPass>,
+}
+
+impl PostProcessor {
+    #[must_use]
+    pub fn new(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) -> Self {
+        let texture_entry = |binding, view_dimension| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                view_dimension,
+                multisampled: false,
+            },
+            count: None,
+        };
+        let bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Prim Post Process Bind Group Layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: true,
+                            min_binding_size: NonZeroU64::new(
+                                std::mem::size_of::<PostUniform>() as u64
+                            ),
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                    texture_entry(2, wgpu::TextureViewDimension::D2),
+                    texture_entry(3, wgpu::TextureViewDimension::D2),
+                    texture_entry(4, wgpu::TextureViewDimension::D3),
+                ],
+            });
+
+        let pipelines = Self::create_pipelines(device, config, &bind_group_layout);
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Post Process Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let uniform_stride = device.limits().min_uniform_buffer_offset_alignment;
+        let empty_texture = Self::create_texture(
+            device,
+            "Post Process Empty Texture",
+            wgpu::TextureDimension::D2,
+            1,
+        );
+        let empty_lut = Self::create_texture(
+            device,
+            "Post Process Empty Lut",
+            wgpu::TextureDimension::D3,
+            1,
+        );
+
+        Self {
+            bind_group_layout,
+            pipelines,
+            sampler,
+            uniform_buffer: Self::create_uniform_buffer(device, uniform_stride, 1),
+            uniform_capacity: 1,
+            uniform_stride,
+            targets: None,
+            empty_texture: empty_texture.create_view(&wgpu::TextureViewDescriptor::default()),
+            empty_lut: empty_lut.create_view(&wgpu::TextureViewDescriptor::default()),
+            luts: FxHashMap::default(),
+            passes: Vec::new(),
+        }
+    }
+
+    fn create_pipelines(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        bind_group_layout: &wgpu::BindGroupLayout,
+    ) -> Vec<(PostKind, wgpu::RenderPipeline)> {
+        let shader = device.create_shader_module(wgpu::include_wgsl!("post_process.wgsl"));
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Post Process Pipeline Layout"),
+            bind_group_layouts: &[bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        PostKind::ALL
+            .iter()
+            .map(|&kind| {
+                let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                    label: Some(kind.entry_point()),
+                    layout: Some(&layout),
+                    vertex: wgpu::VertexState {
+                        module: &shader,
+                        entry_point: "vs_main",
+                        buffers: &[],
+                    },
+                    fragment: Some(wgpu::FragmentState {
+                        module: &shader,
+                        entry_point: kind.entry_point(),
+                        targets: &[Some(wgpu::ColorTargetState {
+                            format: config.format,
+                            blend: None,
+                            write_mask: wgpu::ColorWrites::all(),
+                        })],
+                    }),
+                    primitive: wgpu::PrimitiveState::default(),
+                    depth_stencil: None,
+                    multisample: wgpu::MultisampleState::default(),
+                    multiview: None,
+                });
+                (kind, pipeline)
+            })
+            .collect()
+    }
+
+    fn create_uniform_buffer(device: &wgpu::Device, stride: u32, capacity: usize) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Post Process Uniform Buffer"),
+            size: u64::from(stride) * capacity as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    fn create_texture(
+        device: &wgpu::Device,
+        label: &str,
+        dimension: wgpu::TextureDimension,
+        size: u32,
+    ) -> wgpu::Texture {
+        device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d {
+                width: size,
+                height: size,
+                depth_or_array_layers: if dimension == wgpu::TextureDimension::D3 {
+                    size
+                } else {
+                    1
+                },
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        })
+    }
+
+    /// Whether the last prepared chain had any effects, and so the world needs to be drawn into
+    /// [`PostProcessor::scene_view`].
+    #[must_use]
+    #[inline(always)]
+    pub fn is_active(&self) -> bool {
+        !self.passes.is_empty() && self.targets.is_some()
+    }
+
+    /// The texture the world should be drawn into when post-processing is active.
+    #[must_use]
+    pub fn scene_view(&self) -> Option<&wgpu::TextureView> {
+        self.targets.as_ref().map(|targets| &targets.scene)
+    }
+
+    /// Recreates the intermediate targets after the frame size changes.
+    pub fn resize(&mut self, device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) {
+        if self.targets.is_some() {
+            self.targets = Some(PostTargets::new(device, config));
+        }
+    }
+
+    /// Plans the passes for the chain and uploads everything they need.
+    pub fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        config: &wgpu::SurfaceConfiguration,
+        chain: &PostProcessChain,
+    ) {
+        self.passes = plan_passes(&chain.effects, config.format.describe().srgb);
+        if self.passes.is_empty() {
+            return;
+        }
+
+        if self.targets.is_none() {
+            self.targets = Some(PostTargets::new(device, config));
+        }
+
+        // Upload any look up tables that haven't been seen before, and drop unused ones.
+        self.luts
+            .retain(|id, _| self.passes.iter().any(|pass| pass.lut == Some(*id)));
+        for effect in &chain.effects {
+            if let PostEffect::ColorGrade(grade) = effect {
+                self.luts
+                    .entry(grade.lut.id)
+                    .or_insert_with(|| Self::upload_lut(device, queue, &grade.lut));
+            }
+        }
+
+        if self.passes.len() > self.uniform_capacity {
+            self.uniform_capacity = self.passes.len().next_power_of_two();
+            self.uniform_buffer =
+                Self::create_uniform_buffer(device, self.uniform_stride, self.uniform_capacity);
+        }
+
+        #[allow(clippy::cast_precision_loss)]
+        let texel = [
+            1.0 / config.width as f32,
+            1.0 / config.height as f32,
+            config.width as f32,
+            config.height as f32,
+        ];
+        let mut data = vec![0_u8; self.uniform_stride as usize * self.passes.len()];
+        for (pass, slot) in self
+            .passes
+            .iter()
+            .zip(data.chunks_exact_mut(self.uniform_stride as usize))
+        {
+            let uniform = PostUniform {
+                texel,
+                ..pass.uniform
+            };
+            slot[..std::mem::size_of::<PostUniform>()]
+                .copy_from_slice(bytemuck::bytes_of(&uniform));
+        }
+        queue.write_buffer(&self.uniform_buffer, 0, &data);
+    }
+
+    fn upload_lut(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        lut: &ColorGradeLut,
+    ) -> wgpu::TextureView {
+        let texture = device.create_texture_with_data(
+            queue,
+            &wgpu::TextureDescriptor {
+                label: Some("Color Grade Lut"),
+                size: wgpu::Extent3d {
+                    width: lut.size,
+                    height: lut.size,
+                    depth_or_array_layers: lut.size,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D3,
+                format: wgpu::TextureFormat::Rgba8Unorm,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            },
+            &lut.data,
+        );
+        texture.create_view(&wgpu::TextureViewDescriptor::default())
+    }
+
+    /// Records the prepared passes, reading from the scene target and writing the final result
+    /// into ``output``.
+    pub fn encode(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        output: &wgpu::TextureView,
+    ) {
+        let Some(targets) = &self.targets else {
+            return;
+        };
+        let view = |target| match target {
+            PostTarget::Scene => &targets.scene,
+            PostTarget::Ping => &targets.ping,
+            PostTarget::Pong => &targets.pong,
+            PostTarget::BloomA => &targets.bloom_a,
+            PostTarget::BloomB => &targets.bloom_b,
+            PostTarget::Output => output,
+        };
+
+        for (i, pass) in self.passes.iter().enumerate() {
+            let lut = pass
+                .lut
+                .and_then(|id| self.luts.get(&id))
+                .unwrap_or(&self.empty_lut);
+            #[allow(clippy::cast_possible_truncation)]
+            self.draw(
+                device,
+                encoder,
+                pass.kind,
+                [
+                    view(pass.input),
+                    pass.secondary.map_or(&self.empty_texture, view),
+                    lut,
+                ],
+                view(pass.output),
+                self.uniform_stride * i as u32,
+            );
+        }
+    }
+
+    /// Records a copy of ``source`` into ``output``, which may be a texture that can't be copied
+    /// to directly, such as a window surface.
+    pub fn blit(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        source: &wgpu::TextureView,
+        output: &wgpu::TextureView,
+    ) {
+        self.draw(
+            device,
+            encoder,
+            PostKind::Copy,
+            [source, &self.empty_texture, &self.empty_lut],
+            output,
+            0,
+        );
+    }
+
+    /// Records a single full-screen pass, reading from the ``input``, secondary and look up table
+    /// textures, in that order.
+    #[allow(clippy::too_many_arguments)]
+    fn draw(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        kind: PostKind,
+        [input, secondary, lut]: [&wgpu::TextureView; 3],
+        output: &wgpu::TextureView,
+        uniform_offset: u32,
+    ) {
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Post Process Bind Group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                        buffer: &self.uniform_buffer,
+                        offset: 0,
+                        size: NonZeroU64::new(std::mem::size_of::<PostUniform>() as u64),
+                    }),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::TextureView(input),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: wgpu::BindingResource::TextureView(secondary),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 4,
+                    resource: wgpu::BindingResource::TextureView(lut),
+                },
+            ],
+        });
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Post Process Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: output,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+        let pipeline = self
+            .pipelines
+            .iter()
+            .find_map(|(pipeline_kind, pipeline)| (*pipeline_kind == kind).then_some(pipeline))
+            .expect("Every post process kind has a pipeline");
+        render_pass.set_pipeline(pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[uniform_offset]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        plan_passes, Bloom, ColorGradeLut, PostEffect, PostKind, PostTarget, Scanlines, Vignette,
+    };
+
+    #[test]
+    fn test_plan_passes() {
+        assert!(plan_passes(&[], true).is_empty());
+
+        let passes = plan_passes(
+            &[
+                PostEffect::Vignette(Vignette::default()),
+                PostEffect::Bloom(Bloom::default()),
+                PostEffect::Scanlines(Scanlines::default()),
+            ],
+            true,
+        );
+        let plan = passes
+            .iter()
+            .map(|pass| (pass.kind, pass.input, pass.secondary, pass.output))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            plan,
+            vec![
+                (
+                    PostKind::Vignette,
+                    PostTarget::Scene,
+                    None,
+                    PostTarget::Ping
+                ),
+                (
+                    PostKind::BloomExtract,
+                    PostTarget::Ping,
+                    None,
+                    PostTarget::BloomA
+                ),
+                (
+                    PostKind::BlurVertical,
+                    PostTarget::BloomA,
+                    None,
+                    PostTarget::BloomB
+                ),
+                (
+                    PostKind::BloomComposite,
+                    PostTarget::Ping,
+                    Some(PostTarget::BloomB),
+                    PostTarget::Pong
+                ),
+                (
+                    PostKind::Scanlines,
+                    PostTarget::Pong,
+                    None,
+                    PostTarget::Output
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_identity_lut() {
+        let lut = ColorGradeLut::identity(4);
+        assert_eq!(lut.size(), 4);
+        assert_eq!(&lut.data[..4], &[0, 0, 0, 255]);
+        // Red changes fastest, then green, then blue.
+        assert_eq!(&lut.data[4..8], &[85, 0, 0, 255]);
+        assert_eq!(&lut.data[16..20], &[0, 85, 0, 255]);
+        assert_eq!(&lut.data[64..68], &[0, 0, 85, 255]);
+        assert!(ColorGradeLut::new(4, vec![0; 10]).is_err());
+    }
+}
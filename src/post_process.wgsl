@@ -0,0 +1,141 @@
+struct PostUniform {
+    // Effect specific parameters.
+    params: vec4<f32>,
+    color: vec4<f32>,
+    // xy: size of a texel, zw: size of the frame in pixels
+    texel: vec4<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> post: PostUniform;
+@group(0) @binding(1)
+var post_sampler: sampler;
+@group(0) @binding(2)
+var source: texture_2d<f32>;
+@group(0) @binding(3)
+var secondary: texture_2d<f32>;
+@group(0) @binding(4)
+var lut: texture_3d<f32>;
+
+let PI: f32 = 3.14159265;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+// Draws a single triangle covering the whole screen.
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+fn sample_source(uv: vec2<f32>) -> vec4<f32> {
+    return textureSampleLevel(source, post_sampler, uv, 0.0);
+}
+
+fn bright(color: vec3<f32>, threshold: f32) -> vec3<f32> {
+    return max(color - vec3<f32>(threshold), vec3<f32>(0.0));
+}
+
+// A 9 tap gaussian blur along the given direction, in texels.
+fn blur(uv: vec2<f32>, direction: vec2<f32>, threshold: f32) -> vec3<f32> {
+    var weights = array<f32, 5>(0.227027, 0.1945946, 0.1216216, 0.054054, 0.016216);
+    let texel_step = direction * post.texel.xy;
+    var color = bright(sample_source(uv).rgb, threshold) * weights[0];
+    for (var i = 1; i < 5; i++) {
+        let offset = texel_step * f32(i);
+        color += bright(sample_source(uv + offset).rgb, threshold) * weights[i];
+        color += bright(sample_source(uv - offset).rgb, threshold) * weights[i];
+    }
+    return color;
+}
+
+// params: x: threshold, y: radius
+@fragment
+fn fs_bloom_extract(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(blur(in.uv, vec2<f32>(post.params.y / 4.0, 0.0), post.params.x), 1.0);
+}
+
+// params: y: radius
+@fragment
+fn fs_blur_vertical(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(blur(in.uv, vec2<f32>(0.0, post.params.y / 4.0), 0.0), 1.0);
+}
+
+// params: z: intensity
+@fragment
+fn fs_bloom_composite(in: VertexOutput) -> @location(0) vec4<f32> {
+    let scene = sample_source(in.uv);
+    let bloom = textureSampleLevel(secondary, post_sampler, in.uv, 0.0).rgb;
+    return vec4<f32>(scene.rgb + bloom * post.params.z, scene.a);
+}
+
+// params: x: intensity, y: radius, z: softness
+@fragment
+fn fs_vignette(in: VertexOutput) -> @location(0) vec4<f32> {
+    let scene = sample_source(in.uv);
+    // Scaled so that the corners of the screen are at a distance of 1.
+    let dist = length(in.uv - vec2<f32>(0.5)) * 1.41421356;
+    let amount = smoothstep(post.params.y - post.params.z, post.params.y, dist) * post.params.x;
+    return vec4<f32>(mix(scene.rgb, post.color.rgb, amount), scene.a);
+}
+
+// params: x: strength in pixels
+@fragment
+fn fs_chromatic_aberration(in: VertexOutput) -> @location(0) vec4<f32> {
+    let offset = (in.uv - vec2<f32>(0.5)) * 2.0 * post.params.x * post.texel.xy;
+    let scene = sample_source(in.uv);
+    let r = sample_source(in.uv + offset).r;
+    let b = sample_source(in.uv - offset).b;
+    return vec4<f32>(r, scene.g, b, scene.a);
+}
+
+// params: x: intensity, y: line height in pixels
+@fragment
+fn fs_scanlines(in: VertexOutput) -> @location(0) vec4<f32> {
+    let scene = sample_source(in.uv);
+    let y = in.uv.y * post.texel.w;
+    let gap = 0.5 + 0.5 * cos(2.0 * PI * y / max(post.params.y, 1.0));
+    return vec4<f32>(scene.rgb * (1.0 - post.params.x * gap), scene.a);
+}
+
+fn to_srgb(color: vec3<f32>) -> vec3<f32> {
+    let low = color * 12.92;
+    let high = 1.055 * pow(color, vec3<f32>(1.0 / 2.4)) - 0.055;
+    return select(high, low, color <= vec3<f32>(0.0031308));
+}
+
+fn to_linear(color: vec3<f32>) -> vec3<f32> {
+    let low = color / 12.92;
+    let high = pow((color + 0.055) / 1.055, vec3<f32>(2.4));
+    return select(high, low, color <= vec3<f32>(0.04045));
+}
+
+// params: x: intensity, y: 1.0 if the frame is stored as linear color, z: lut size
+@fragment
+fn fs_color_grade(in: VertexOutput) -> @location(0) vec4<f32> {
+    let scene = sample_source(in.uv);
+    let is_linear = post.params.y > 0.5;
+    // Look up tables are authored against sRGB encoded colors.
+    var color = clamp(scene.rgb, vec3<f32>(0.0), vec3<f32>(1.0));
+    if (is_linear) {
+        color = to_srgb(color);
+    }
+    let size = post.params.z;
+    let coord = color * ((size - 1.0) / size) + vec3<f32>(0.5 / size);
+    var graded = textureSampleLevel(lut, post_sampler, coord, 0.0).rgb;
+    if (is_linear) {
+        graded = to_linear(graded);
+    }
+    return vec4<f32>(mix(scene.rgb, graded, post.params.x), scene.a);
+}
+
+@fragment
+fn fs_copy(in: VertexOutput) -> @location(0) vec4<f32> {
+    return sample_source(in.uv);
+}
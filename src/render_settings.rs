@@ -0,0 +1,138 @@
+use glam::Vec3;
+use log::error;
+
+/// The sample counts multisampling can be set to, from the fewest samples to the most.
+///
+/// Sample counts other than these are only supported by some adapters, so they are not used.
+pub const SUPPORTED_SAMPLE_COUNTS: [u32; 2] = [1, 4];
+
+/// Settings which control how the renderer draws each frame.
+///
+/// Accessible and modifiable through a bevy Resource. The initial values come from the
+/// [`libprim::window::PrimWindowOptions`] or [`libprim::headless::HeadlessOptions`] the engine was
+/// started with, and changes made by systems are applied at the start of the next render stage.
+///
+/// Changing the `sample_count` rebuilds the render targets and every material's pipelines, so it
+/// should be done sparingly, such as from a settings menu.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RenderSettings {
+    /// The background color drawn where nothing else is.
+    pub clear_color: Vec3,
+
+    /// Anti-aliasing sample count, one of [`SUPPORTED_SAMPLE_COUNTS`].
+    ///
+    /// Unsupported counts fall back to the closest count the adapter supports, and the setting is
+    /// updated to match.
+    pub sample_count: u32,
+
+    /// Whether to sync the frame rate with the display's refresh rate.
+    ///
+    /// Has no effect when running headless.
+    pub vsync: bool,
+
+    /// Whether to sort the instances in each layer by material and shape before drawing them.
+    ///
+    /// Sorting leaves exactly one draw call per material and shape in each layer, trading the CPU
+    /// time spent sorting for fewer draw calls. Instances in the same layer may then draw in a
+    /// different order than they were spawned in.
+    pub sort_renderables: bool,
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        Self {
+            clear_color: Vec3::new(0.0, 0.0, 0.0),
+            sample_count: 4,
+            vsync: false,
+            sort_renderables: false,
+        }
+    }
+}
+
+impl RenderSettings {
+    /// Sets the background color which will show in spaces where nothing is drawn.
+    #[must_use]
+    pub fn with_clear_color(mut self, clear_color: Vec3) -> Self {
+        self.clear_color = clear_color;
+        self
+    }
+
+    /// Sets the MSAA multi-sampling count.
+    #[must_use]
+    pub fn with_sample_count(mut self, sample_count: u32) -> Self {
+        self.sample_count = sample_count;
+        self
+    }
+
+    /// Enables or disables `VSync`, limiting the framerate to the refresh rate of the display.
+    #[must_use]
+    pub fn with_vsync(mut self, vsync: bool) -> Self {
+        self.vsync = vsync;
+        self
+    }
+
+    /// Enables or disables sorting instances by material and shape within each layer.
+    #[must_use]
+    pub fn with_sort_renderables(mut self, sort_renderables: bool) -> Self {
+        self.sort_renderables = sort_renderables;
+        self
+    }
+
+    /// The color render passes clear to.
+    #[must_use]
+    pub(crate) fn wgpu_clear_color(&self) -> wgpu::Color {
+        wgpu::Color {
+            r: f64::from(self.clear_color.x),
+            g: f64::from(self.clear_color.y),
+            b: f64::from(self.clear_color.z),
+            a: 1.0,
+        }
+    }
+
+    /// The present mode a window surface is configured with.
+    #[must_use]
+    pub(crate) fn present_mode(&self) -> wgpu::PresentMode {
+        if self.vsync {
+            wgpu::PresentMode::AutoVsync
+        } else {
+            wgpu::PresentMode::AutoNoVsync
+        }
+    }
+}
+
+/// Picks the sample count to render with for the one requested.
+///
+/// Counts outside of [`SUPPORTED_SAMPLE_COUNTS`] fall back to the highest supported count which
+/// doesn't exceed them, and multisampling falls back to a single sample when the output format
+/// doesn't support it. Logs an error whenever the requested count can't be used.
+#[must_use]
+pub(crate) fn supported_sample_count(requested: u32, multisample_supported: bool) -> u32 {
+    let sample_count = SUPPORTED_SAMPLE_COUNTS
+        .into_iter()
+        .filter(|&count| count == 1 || multisample_supported)
+        .take_while(|&count| count <= requested)
+        .last()
+        .unwrap_or(1);
+    if sample_count != requested {
+        error!("Sample count {requested} is not supported, falling back to {sample_count}");
+    }
+    sample_count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::supported_sample_count;
+
+    #[test]
+    fn test_supported_sample_count() {
+        assert_eq!(supported_sample_count(1, true), 1);
+        assert_eq!(supported_sample_count(4, true), 4);
+        // Counts between the supported ones round down.
+        assert_eq!(supported_sample_count(2, true), 1);
+        assert_eq!(supported_sample_count(8, true), 4);
+        assert_eq!(supported_sample_count(0, true), 1);
+        // Without multisampling, only one sample can be used.
+        assert_eq!(supported_sample_count(4, false), 1);
+        assert_eq!(supported_sample_count(1, false), 1);
+    }
+}
@@ -1,10 +1,15 @@
-
 struct InstanceInput {
     @location(5) model_matrix_0: vec4<f32>,
     @location(6) model_matrix_1: vec4<f32>,
     @location(7) model_matrix_2: vec4<f32>,
     @location(8) model_matrix_3: vec4<f32>,
     @location(9) color: vec4<f32>,
+    @location(10) flags: u32,
+    @location(11) gradient_start_color: vec4<f32>,
+    @location(12) gradient_end_color: vec4<f32>,
+    @location(13) gradient: vec4<f32>,
+    @location(14) data: vec4<f32>,
+    @location(15) effect: vec4<f32>,
 }
 
 struct CameraUniform {
@@ -14,13 +19,82 @@ struct CameraUniform {
 @group(0) @binding(0)
 var<uniform> view_proj: CameraUniform;
 
+// Must match `Globals` in pipeline.rs.
+struct Globals {
+    // Seconds since the engine started.
+    time: f32,
+    // Seconds between the previous frame and this one.
+    delta_time: f32,
+    // The number of frames before this one.
+    frame: u32,
+    // The size of the window, or of the headless output, in pixels.
+    resolution: vec2<f32>,
+};
+
+@group(0) @binding(1)
+var<uniform> globals: Globals;
+
+// Must match `MAX_LIGHTS` in light.rs.
+let MAX_LIGHTS: u32 = 64u;
+// Must match `MAX_SHADOW_EDGES` in light.rs.
+let MAX_SHADOW_EDGES: u32 = 512u;
+let FLAG_UNLIT: u32 = 1u;
+let FLAG_SHADOW_CASTER: u32 = 2u;
+let FLAG_GRADIENT_LINEAR: u32 = 4u;
+let FLAG_GRADIENT_RADIAL: u32 = 8u;
+let FLAG_EFFECT_PULSE: u32 = 16u;
+let FLAG_EFFECT_WOBBLE: u32 = 32u;
+let FLAG_EFFECT_STRIPES: u32 = 64u;
+let TAU: f32 = 6.283185307;
+// The number of samples taken across the light when softening shadows.
+let SOFT_SHADOW_SAMPLES: i32 = 5;
+
+struct Light {
+    // xy: position, z: radius, w: falloff
+    position_radius: vec4<f32>,
+    color: vec4<f32>,
+    // xy: direction, z: cos(inner angle), w: cos(outer angle)
+    spot: vec4<f32>,
+    // x: casts shadows, y: soft shadow radius
+    shadow: vec4<f32>,
+};
+
+struct Lights {
+    ambient: vec4<f32>,
+    // x: light count, y: shadow edge count
+    count: vec4<u32>,
+    lights: array<Light, MAX_LIGHTS>,
+};
+
+struct ShadowEdges {
+    // xy: start, zw: end
+    edges: array<vec4<f32>, MAX_SHADOW_EDGES>,
+};
+
+@group(1) @binding(0)
+var<uniform> lights: Lights;
+@group(1) @binding(1)
+var<uniform> shadow_edges: ShadowEdges;
+
 struct VertexInput {
     @location(0) position: vec2<f32>,
+    @location(1) color: vec4<f32>,
 }
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
     @location(0) vert_pos: vec3<f32>,
     @location(1) color: vec4<f32>,
+    @location(2) world_pos: vec2<f32>,
+    @location(3) @interpolate(flat) flags: u32,
+    // The position of the vertex within the shape, before the instance transform is applied.
+    @location(4) local_pos: vec2<f32>,
+    @location(5) @interpolate(flat) gradient_start_color: vec4<f32>,
+    @location(6) @interpolate(flat) gradient_end_color: vec4<f32>,
+    @location(7) @interpolate(flat) gradient: vec4<f32>,
+    // Per instance values for materials to use, from the instance's `InstanceData`.
+    @location(8) @interpolate(flat) data: vec4<f32>,
+    // The parameters of the instance's built-in effect, selected by its flags.
+    @location(9) @interpolate(flat) effect: vec4<f32>,
 };
 
 
@@ -37,13 +111,136 @@ fn vs_main(
     );
     var out: VertexOutput;
 
-    out.clip_position = view_proj.view_proj * model_matrix * vec4<f32>(model.position, 1.0, 1.0);
+    var position = model.position;
+    if ((instance.flags & FLAG_EFFECT_WOBBLE) != 0u) {
+        // x: amplitude, y: frequency
+        let phase = TAU * instance.effect.y * globals.time;
+        position += instance.effect.x * vec2<f32>(
+            sin(phase + model.position.y * TAU),
+            cos(phase + model.position.x * TAU),
+        );
+    }
+    let world_pos = model_matrix * vec4<f32>(position, 1.0, 1.0);
+    out.clip_position = view_proj.view_proj * world_pos;
     out.vert_pos = out.clip_position.xyz;
-    out.color = instance.color;
+    out.color = instance.color * model.color;
+    out.world_pos = world_pos.xy;
+    out.flags = instance.flags;
+    out.local_pos = model.position;
+    out.gradient_start_color = instance.gradient_start_color;
+    out.gradient_end_color = instance.gradient_end_color;
+    out.gradient = instance.gradient;
+    out.data = instance.data;
+    out.effect = instance.effect;
     return out;
 }
 
-@fragment
-fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    return in.color;
+// The color of the instance, including any gradient.
+fn base_color(in: VertexOutput) -> vec4<f32> {
+    var t: f32;
+    if ((in.flags & FLAG_GRADIENT_LINEAR) != 0u) {
+        let direction = in.gradient.zw - in.gradient.xy;
+        t = dot(in.local_pos - in.gradient.xy, direction) / max(dot(direction, direction), 0.000001);
+    } else if ((in.flags & FLAG_GRADIENT_RADIAL) != 0u) {
+        t = length(in.local_pos - in.gradient.xy) / max(in.gradient.z, 0.000001);
+    } else {
+        return in.color;
+    }
+    return in.color * mix(in.gradient_start_color, in.gradient_end_color, clamp(t, 0.0, 1.0));
+}
+
+// The color a shape is filled with before its material is applied, including any gradient and
+// built-in effect.
+fn fill_color(in: VertexOutput) -> vec4<f32> {
+    let color = base_color(in);
+    if ((in.flags & FLAG_EFFECT_PULSE) != 0u) {
+        // x: frequency, y: amount
+        let dim = in.effect.y * (0.5 - 0.5 * cos(TAU * in.effect.x * globals.time));
+        return vec4<f32>(color.rgb * (1.0 - dim), color.a);
+    }
+    if ((in.flags & FLAG_EFFECT_STRIPES) != 0u) {
+        // x: count, y: speed, z: angle
+        let direction = vec2<f32>(cos(in.effect.z), sin(in.effect.z));
+        let along = dot(in.local_pos, direction) * in.effect.x * 2.0 - globals.time * in.effect.y;
+        let stripe = step(0.5, fract(along * 0.5));
+        return vec4<f32>(color.rgb * (1.0 - 0.5 * stripe), color.a);
+    }
+    return color;
+}
+
+// Whether the segment from p to q crosses the segment from a to b.
+fn segments_intersect(p: vec2<f32>, q: vec2<f32>, a: vec2<f32>, b: vec2<f32>) -> bool {
+    let r = q - p;
+    let s = b - a;
+    let denom = r.x * s.y - r.y * s.x;
+    if (abs(denom) < 0.000001) {
+        return false;
+    }
+    let ap = a - p;
+    let t = (ap.x * s.y - ap.y * s.x) / denom;
+    let u = (ap.x * r.y - ap.y * r.x) / denom;
+    return t > 0.0 && t < 1.0 && u >= 0.0 && u <= 1.0;
+}
+
+// 1.0 if nothing blocks the path between the two points, otherwise 0.0.
+fn visibility(origin: vec2<f32>, frag_pos: vec2<f32>) -> f32 {
+    let count = min(lights.count.y, MAX_SHADOW_EDGES);
+    for (var i = 0u; i < count; i++) {
+        let edge = shadow_edges.edges[i];
+        if (segments_intersect(origin, frag_pos, edge.xy, edge.zw)) {
+            return 0.0;
+        }
+    }
+    return 1.0;
+}
+
+fn shadow(light: Light, world_pos: vec2<f32>, direction: vec2<f32>) -> f32 {
+    let softness = light.shadow.y;
+    if (softness <= 0.0) {
+        return visibility(light.position_radius.xy, world_pos);
+    }
+
+    // Treat the light as a disk, sampling points across it facing the fragment.
+    let across = vec2<f32>(-direction.y, direction.x) * softness;
+    var lit = 0.0;
+    for (var i = 0; i < SOFT_SHADOW_SAMPLES; i++) {
+        let offset = f32(i) / f32(SOFT_SHADOW_SAMPLES - 1) * 2.0 - 1.0;
+        lit += visibility(light.position_radius.xy + across * offset, world_pos);
+    }
+    return lit / f32(SOFT_SHADOW_SAMPLES);
+}
+
+fn light_contribution(light: Light, world_pos: vec2<f32>, shadowed: bool) -> vec3<f32> {
+    let to_frag = world_pos - light.position_radius.xy;
+    let dist = length(to_frag);
+    let radius = light.position_radius.z;
+    if (dist >= radius) {
+        return vec3<f32>(0.0);
+    }
+
+    var attenuation = pow(1.0 - dist / radius, light.position_radius.w);
+    let direction = to_frag / max(dist, 0.0001);
+    attenuation *= smoothstep(light.spot.w, light.spot.z, dot(direction, light.spot.xy));
+    if (shadowed && attenuation > 0.0 && light.shadow.x > 0.0) {
+        attenuation *= shadow(light, world_pos, direction);
+    }
+    return light.color.rgb * attenuation;
+}
+
+// Applies the scene lighting to the color produced by a material.
+//
+// The fragment entry point of every material passes its color through here, so that all materials
+// respect the lights and the instance flags.
+fn shade(in: VertexOutput, color: vec4<f32>) -> vec4<f32> {
+    if ((in.flags & FLAG_UNLIT) != 0u) {
+        return color;
+    }
+
+    let shadowed = (in.flags & FLAG_SHADOW_CASTER) == 0u;
+    var light = lights.ambient.rgb;
+    let count = min(lights.count.x, MAX_LIGHTS);
+    for (var i = 0u; i < count; i++) {
+        light += light_contribution(lights.lights[i], in.world_pos, shadowed);
+    }
+    return vec4<f32>(color.rgb * light, color.a);
 }
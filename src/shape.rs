@@ -1,14 +1,16 @@
 use std::ops::Range;
 
-use glam::Vec2;
+use glam::{Vec2, Vec4};
+use log::error;
 use wgpu::util::DeviceExt;
 
-use crate::vertex::Vertex;
+use crate::{triangulation::triangulate, util::FxHashMap, vertex::Vertex};
 
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
 pub(crate) struct Shape2DVertex {
     pub position: Vec2,
+    pub color: [f32; 4],
 }
 
 impl Vertex for Shape2DVertex {
@@ -16,11 +18,18 @@ impl Vertex for Shape2DVertex {
         wgpu::VertexBufferLayout {
             array_stride: std::mem::size_of::<Shape2DVertex>() as wgpu::BufferAddress,
             step_mode: wgpu::VertexStepMode::Vertex,
-            attributes: &[wgpu::VertexAttribute {
-                offset: 0,
-                shader_location: 0,
-                format: wgpu::VertexFormat::Float32x2,
-            }],
+            attributes: &[
+                wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 0,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<Vec2>() as wgpu::BufferAddress,
+                    shader_location: 1,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+            ],
         }
     }
 }
@@ -30,17 +39,29 @@ pub(crate) struct Shape2D {
     pub vertex_buffer: wgpu::Buffer,
     pub index_buffer: wgpu::Buffer,
     pub num_elements: u32,
+    /// The edges making up the outside of the shape, in local space.
+    ///
+    /// Used to cast shadows from the shape.
+    pub edges: Vec<[Vec2; 2]>,
+    /// The closed loops of points around the boundary of the shape, in local space.
+    ///
+    /// Loops around the outside of the shape are counter-clockwise. Used to draw outlines.
+    pub boundary: Vec<Vec<Vec2>>,
 }
 
 impl Shape2D {
     /// Creates a new shape, initializing its vertex and index buffers from the given points and incides lists.
     ///
+    /// Each point is given the matching color from `colors`, or white if no colors are given. Colors
+    /// are ignored if there isn't exactly one for each point.
+    ///
     /// # Panics
     /// This method panics if more than `u32::MAX` indices are passed in.
     #[must_use]
     pub fn create_from_points(
         name: String,
         points: Vec<Vec2>,
+        colors: Option<Vec<Vec4>>,
         indices: Vec<u32>,
         device: &wgpu::Device,
     ) -> Self {
@@ -50,9 +71,30 @@ impl Shape2D {
             u32::MAX
         );
 
+        let colors = match colors {
+            Some(colors) if colors.len() == points.len() => colors,
+            Some(colors) => {
+                error!(
+                    "Shape {name:?} has {} colors for {} vertices, ignoring colors",
+                    colors.len(),
+                    points.len()
+                );
+                vec![Vec4::ONE; points.len()]
+            }
+            None => vec![Vec4::ONE; points.len()],
+        };
+        let vertices = points
+            .iter()
+            .zip(colors)
+            .map(|(&position, color)| Shape2DVertex {
+                position,
+                color: color.to_array(),
+            })
+            .collect::<Vec<_>>();
+
         let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some(&format!("{name:?} Vertex Buffer")),
-            contents: bytemuck::cast_slice(&points),
+            contents: bytemuck::cast_slice(&vertices),
             usage: wgpu::BufferUsages::VERTEX,
         });
 
@@ -67,10 +109,133 @@ impl Shape2D {
             vertex_buffer,
             index_buffer,
             num_elements: indices.len() as u32,
+            edges: boundary_edges(&points, &indices),
+            boundary: boundary_loops(&points, &indices),
         }
     }
 }
 
+/// Finds the edges of a triangulated shape which lie on its boundary.
+///
+/// An edge is on the boundary when it's used by exactly one triangle. Edges shared between two
+/// triangles are internal to the shape and are skipped.
+fn boundary_edges(points: &[Vec2], indices: &[u32]) -> Vec<[Vec2; 2]> {
+    boundary_edge_indices(indices)
+        .into_iter()
+        .filter_map(|(a, b)| Some([*points.get(a as usize)?, *points.get(b as usize)?]))
+        .collect()
+}
+
+/// Chains the boundary edges of a triangulated shape into closed loops of points.
+///
+/// Edges keep the winding of the triangles they belong to, so loops follow the same direction as
+/// the shape's triangles.
+fn boundary_loops(points: &[Vec2], indices: &[u32]) -> Vec<Vec<Vec2>> {
+    let mut next = FxHashMap::<u32, Vec<u32>>::default();
+    for (a, b) in boundary_edge_indices(indices) {
+        next.entry(a).or_default().push(b);
+    }
+
+    let mut starts = next.keys().copied().collect::<Vec<_>>();
+    starts.sort_unstable();
+    let mut loops = Vec::new();
+    for start in starts {
+        let mut current = start;
+        let mut path = Vec::new();
+        while let Some(to) = next.get_mut(&current).and_then(Vec::pop) {
+            path.extend(points.get(current as usize));
+            current = to;
+            if current == start {
+                break;
+            }
+        }
+        if path.len() > 1 {
+            loops.push(path);
+        }
+    }
+    loops
+}
+
+/// Finds the index pairs of the edges used by exactly one triangle.
+fn boundary_edge_indices(indices: &[u32]) -> Vec<(u32, u32)> {
+    let mut edge_counts = FxHashMap::<(u32, u32), u32>::default();
+    let mut edges = Vec::new();
+    for triangle in indices.chunks_exact(3) {
+        for (a, b) in [
+            (triangle[0], triangle[1]),
+            (triangle[1], triangle[2]),
+            (triangle[2], triangle[0]),
+        ] {
+            let key = (a.min(b), a.max(b));
+            let count = edge_counts.entry(key).or_insert(0);
+            if *count == 0 {
+                edges.push((a, b));
+            }
+            *count += 1;
+        }
+    }
+
+    edges
+        .into_iter()
+        .filter(|&(a, b)| edge_counts[&(a.min(b), a.max(b))] == 1)
+        .collect()
+}
+
+/// The bounds of a shape in its own space, before an instance scales, rotates and moves it.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct ShapeBounds {
+    /// The corner of the shape's bounding box with the lowest coordinates.
+    pub min: Vec2,
+    /// The corner of the shape's bounding box with the highest coordinates.
+    pub max: Vec2,
+    /// The distance from the shape's origin to its furthest vertex.
+    pub radius: f32,
+}
+
+impl ShapeBounds {
+    /// Computes the bounds of the given vertices. A shape without vertices has empty bounds at the
+    /// origin.
+    #[must_use]
+    pub fn from_points(points: &[Vec2]) -> Self {
+        let Some(&first) = points.first() else {
+            return Self::default();
+        };
+        let (min, max) = points
+            .iter()
+            .fold((first, first), |(min, max), &p| (min.min(p), max.max(p)));
+        let radius = points
+            .iter()
+            .map(|p| p.length_squared())
+            .fold(0.0, f32::max)
+            .sqrt();
+        Self { min, max, radius }
+    }
+
+    /// Returns the corners of a box containing the shape once it's been scaled, rotated by
+    /// `rotation` radians and moved to `position`.
+    ///
+    /// The box contains the rotated corners of the shape's bounding box, and is trimmed to the
+    /// circle around its furthest vertex, which is tighter for round shapes.
+    #[must_use]
+    pub fn world_aabb(&self, position: Vec2, rotation: f32, scale: Vec2) -> (Vec2, Vec2) {
+        let rotation = Vec2::from_angle(rotation);
+        let corners = [
+            self.min,
+            Vec2::new(self.max.x, self.min.y),
+            self.max,
+            Vec2::new(self.min.x, self.max.y),
+        ]
+        .map(|corner| rotation.rotate(corner * scale));
+        let (min, max) = corners
+            .iter()
+            .fold((corners[0], corners[0]), |(min, max), &p| {
+                (min.min(p), max.max(p))
+            });
+        let reach = Vec2::splat(self.radius * scale.abs().max_element());
+        (position + min.max(-reach), position + max.min(reach))
+    }
+}
+
 pub(crate) trait DrawShape2D<'a> {
     fn draw_shape2d(&mut self, shape: &'a Shape2D);
     fn draw_shape2d_instanced(&mut self, shape: &'a Shape2D, instances: Range<u32>);
@@ -91,7 +256,7 @@ where
 }
 
 /// Passed into an `InitializeCommand` by the implementor to create a new shape.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct InitializeShape {
     /// The name to reference the shape by when retrieving its ID.
     pub name: String,
@@ -102,6 +267,10 @@ pub struct InitializeShape {
     /// Vertices within each triangle should be specified in a counter-clockwise direction
     /// otherwise the face will be culled by the GPU.
     pub indices: Vec<u32>,
+    /// An optional color for each vertex, in the same order as `vertices`.
+    ///
+    /// Colors are blended across each triangle and multiplied with the instance color.
+    pub colors: Option<Vec<Vec4>>,
 }
 
 impl InitializeShape {
@@ -112,6 +281,193 @@ impl InitializeShape {
             name,
             vertices,
             indices,
+            colors: None,
         }
     }
+
+    /// Create a new shape initializer from the outline of a polygon, which may be concave.
+    ///
+    /// The outline can run in either direction, and is triangulated so every triangle faces
+    /// forward.
+    ///
+    /// # Errors
+    /// Fails and returns an [`std::io::ErrorKind::InvalidInput`] if the outline has fewer than
+    /// three distinct points, has no area, or crosses itself.
+    pub fn from_outline(name: String, outline: Vec<Vec2>) -> std::io::Result<Self> {
+        Self::from_polygon(name, outline, Vec::new())
+    }
+
+    /// Create a new shape initializer from the outline of a polygon with holes cut out of it.
+    ///
+    /// The outline and holes can run in either direction, and are triangulated so every triangle
+    /// faces forward. Points in the middle of straight edges may not end up in any triangle.
+    ///
+    /// ```
+    /// # use libprim::{shape::InitializeShape, Vec2};
+    /// let square = |size: f32| {
+    ///     [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+    ///         .map(|(x, y)| Vec2::new(x, y) * size)
+    ///         .to_vec()
+    /// };
+    /// let frame =
+    ///     InitializeShape::from_polygon("Frame".to_string(), square(0.5), vec![square(0.3)])
+    ///         .unwrap();
+    /// assert_eq!(frame.indices.len(), 8 * 3);
+    /// ```
+    ///
+    /// # Errors
+    /// Fails and returns an [`std::io::ErrorKind::InvalidInput`] if the outline or a hole has fewer
+    /// than three distinct points or no area, if any edges cross or touch, or if a hole isn't
+    /// inside the outline or is inside another hole.
+    pub fn from_polygon(
+        name: String,
+        outline: Vec<Vec2>,
+        holes: Vec<Vec<Vec2>>,
+    ) -> std::io::Result<Self> {
+        let (vertices, indices) = triangulate(&outline, &holes).map_err(|err| {
+            std::io::Error::new(err.kind(), format!("Invalid shape {name:?}: {err}"))
+        })?;
+        Ok(Self::new(name, vertices, indices))
+    }
+
+    /// Gives each vertex of the shape a color, in the same order as the vertices.
+    #[must_use]
+    pub fn with_colors(mut self, colors: Vec<Vec4>) -> Self {
+        self.colors = Some(colors);
+        self
+    }
+
+    /// Checks that the shape's indices and colors match its vertices.
+    ///
+    /// # Errors
+    /// Fails and returns an [`std::io::ErrorKind::InvalidInput`] if the number of indices isn't a
+    /// multiple of three, if an index is out of bounds, or if there isn't one color per vertex.
+    pub fn validate(&self) -> std::io::Result<()> {
+        self.check().map_err(|message| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Invalid shape {:?}: {message}", self.name),
+            )
+        })
+    }
+
+    /// Checks that the shape's indices and colors match its vertices, describing the first
+    /// problem found.
+    pub(crate) fn check(&self) -> std::result::Result<(), String> {
+        if self.indices.len() % 3 != 0 {
+            return Err(format!(
+                "there are {} indices, which isn't a multiple of 3",
+                self.indices.len()
+            ));
+        }
+        if let Some(index) = self
+            .indices
+            .iter()
+            .find(|&&i| i as usize >= self.vertices.len())
+        {
+            return Err(format!(
+                "index {index} is out of bounds for {} vertices",
+                self.vertices.len()
+            ));
+        }
+        if let Some(colors) = self.colors.as_ref() {
+            if colors.len() != self.vertices.len() {
+                return Err(format!(
+                    "there are {} colors for {} vertices",
+                    colors.len(),
+                    self.vertices.len()
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec2;
+
+    use std::f32::consts::FRAC_PI_4;
+
+    use super::{boundary_edges, boundary_loops, ShapeBounds};
+
+    #[test]
+    fn test_boundary_edges() {
+        let points = [
+            Vec2::new(0.5, 0.5),
+            Vec2::new(-0.5, 0.5),
+            Vec2::new(-0.5, -0.5),
+            Vec2::new(0.5, -0.5),
+        ];
+        let edges = boundary_edges(&points, &[0, 1, 2, 0, 2, 3]);
+
+        // The shared diagonal between the two triangles is not part of the outline.
+        assert_eq!(edges.len(), 4);
+        assert!(!edges.contains(&[points[2], points[0]]));
+        assert!(edges.contains(&[points[0], points[1]]));
+        assert!(edges.contains(&[points[3], points[0]]));
+    }
+
+    #[test]
+    fn test_shape_bounds() {
+        // An off-center rectangle, reaching from x = 1 to x = 3.
+        let bounds = ShapeBounds::from_points(&[
+            Vec2::new(1.0, -0.5),
+            Vec2::new(3.0, -0.5),
+            Vec2::new(3.0, 0.5),
+            Vec2::new(1.0, 0.5),
+        ]);
+        assert_eq!(bounds.min, Vec2::new(1.0, -0.5));
+        assert_eq!(bounds.max, Vec2::new(3.0, 0.5));
+        assert!((bounds.radius - 9.25_f32.sqrt()).abs() < 0.0001);
+
+        let (min, max) = bounds.world_aabb(Vec2::new(10.0, 0.0), 0.0, Vec2::splat(2.0));
+        assert_eq!((min, max), (Vec2::new(12.0, -1.0), Vec2::new(16.0, 1.0)));
+        // A quarter turn points the rectangle up.
+        let (min, max) = bounds.world_aabb(Vec2::ZERO, FRAC_PI_4 * 2.0, Vec2::ONE);
+        assert!((min - Vec2::new(-0.5, 1.0)).length() < 0.0001);
+        assert!((max - Vec2::new(0.5, 3.0)).length() < 0.0001);
+
+        // The corners of a rotated circle's box reach further than the circle itself.
+        let circle = ShapeBounds {
+            min: Vec2::splat(-0.5),
+            max: Vec2::splat(0.5),
+            radius: 0.5,
+        };
+        let (min, max) = circle.world_aabb(Vec2::ZERO, FRAC_PI_4, Vec2::splat(10.0));
+        assert_eq!((min, max), (Vec2::splat(-5.0), Vec2::splat(5.0)));
+        assert_eq!(ShapeBounds::from_points(&[]), ShapeBounds::default());
+    }
+
+    #[test]
+    fn test_boundary_loops() {
+        // A square with a square hole, made of eight triangles.
+        let points = [
+            Vec2::new(-1.0, -1.0),
+            Vec2::new(1.0, -1.0),
+            Vec2::new(1.0, 1.0),
+            Vec2::new(-1.0, 1.0),
+            Vec2::new(-0.5, -0.5),
+            Vec2::new(0.5, -0.5),
+            Vec2::new(0.5, 0.5),
+            Vec2::new(-0.5, 0.5),
+        ];
+        let indices = [
+            0, 1, 5, 0, 5, 4, 1, 2, 6, 1, 6, 5, 2, 3, 7, 2, 7, 6, 3, 0, 4, 3, 4, 7,
+        ];
+        let loops = boundary_loops(&points, &indices);
+        assert_eq!(loops.len(), 2);
+
+        let area = |path: &Vec<Vec2>| {
+            path.iter()
+                .zip(path.iter().cycle().skip(1))
+                .map(|(a, b)| a.perp_dot(*b))
+                .sum::<f32>()
+                / 2.0
+        };
+        // The outside runs counter-clockwise, and the hole clockwise.
+        assert_eq!(loops[0], points[..4].to_vec());
+        assert!((area(&loops[0]) - 4.0).abs() < 0.001);
+        assert!((area(&loops[1]) + 1.0).abs() < 0.001);
+    }
 }
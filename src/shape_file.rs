@@ -0,0 +1,307 @@
+use std::{
+    collections::HashSet,
+    io::{Error, ErrorKind, Result},
+    path::PathBuf,
+};
+
+use glam::{Vec2, Vec4};
+use serde::Deserialize;
+
+use crate::{shape::InitializeShape, triangulation::triangulate, util::AssetSource};
+
+/// An initializer struct for loading every shape in a shape file into the
+/// [`crate::shape_registry::ShapeRegistry`].
+///
+/// Shape files are written in [RON](https://github.com/ron-rs/ron), and hold a list of named
+/// shapes:
+///
+/// ```ron
+/// ShapeFile(
+///     shapes: [
+///         // Each triangle is given by three indices into the vertices, counter-clockwise.
+///         (
+///             name: "Ship",
+///             vertices: [(0.0, 0.5), (-0.5, -0.5), (0.0, -0.25), (0.5, -0.5)],
+///             indices: [0, 1, 2, 0, 2, 3],
+///             colors: [(0.9, 0.9, 1.0, 1.0), (0.2, 0.3, 0.6, 1.0), (0.5, 0.6, 0.9, 1.0), (0.2, 0.3, 0.6, 1.0)],
+///         ),
+///         // Without indices, the vertices are the outline of the shape and are triangulated.
+///         (
+///             name: "Frame",
+///             vertices: [(-0.5, -0.5), (0.5, -0.5), (0.5, 0.5), (-0.5, 0.5)],
+///             holes: [[(-0.3, -0.3), (0.3, -0.3), (0.3, 0.3), (-0.3, 0.3)]],
+///         ),
+///     ],
+/// )
+/// ```
+///
+/// Each shape has:
+/// - `name`: the name to reference the shape by, which must be unique within the file.
+/// - `vertices`: the `(x, y)` position of each vertex.
+/// - `indices` (optional): the triangles of the shape. When left out, `vertices` is treated as an
+///   outline in either direction, and is triangulated along with any `holes`.
+/// - `holes` (optional): loops of points to cut out of an outline. Only used without `indices`.
+/// - `colors` (optional): an `(r, g, b, a)` color for each vertex, followed by one for each point
+///   of the holes in order.
+#[derive(Debug, Clone)]
+pub struct InitializeShapeFile {
+    /// Where to read the shape file from.
+    pub source: AssetSource,
+}
+
+impl InitializeShapeFile {
+    /// Create a new shape file initializer which reads the file at the given path.
+    ///
+    /// The file is read when the initializer runs, so it can be edited without recompiling.
+    #[must_use]
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self {
+            source: AssetSource::Path(path.into()),
+        }
+    }
+
+    /// Create a new shape file initializer from the text of a shape file.
+    ///
+    /// ## Example
+    /// ```
+    /// # use libprim::shape_file::InitializeShapeFile;
+    /// InitializeShapeFile::from_text(
+    ///     r#"ShapeFile(shapes: [(name: "Kite", vertices: [(0.0, 0.5), (-0.3, 0.0), (0.0, -0.5), (0.3, 0.0)])])"#
+    ///         .to_string(),
+    /// );
+    /// ```
+    #[must_use]
+    pub fn from_text(text: String) -> Self {
+        Self {
+            source: AssetSource::Text(text),
+        }
+    }
+
+    /// Reads and parses the shape file into shape initializers.
+    ///
+    /// # Errors
+    /// Returns an [`std::io::Error`] if the file can't be read, or an
+    /// [`std::io::ErrorKind::InvalidData`] if the file is malformed or describes an invalid shape.
+    pub fn load(&self) -> Result<Vec<InitializeShape>> {
+        self.source.parse(parse_shape_file)
+    }
+
+    /// A description of where the shape file comes from, for logging.
+    pub(crate) fn describe(&self) -> String {
+        self.source.describe()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ShapeFile {
+    shapes: Vec<ShapeDef>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ShapeDef {
+    name: String,
+    vertices: Vec<(f32, f32)>,
+    #[serde(default)]
+    indices: Option<Vec<u32>>,
+    #[serde(default)]
+    holes: Vec<Vec<(f32, f32)>>,
+    #[serde(default)]
+    colors: Option<Vec<(f32, f32, f32, f32)>>,
+}
+
+/// Parses the text of a shape file into shape initializers.
+///
+/// # Errors
+/// Returns an [`std::io::ErrorKind::InvalidData`] if the text isn't a valid shape file, or any
+/// shape in it is invalid.
+pub fn parse_shape_file(text: &str) -> Result<Vec<InitializeShape>> {
+    let file = ron::Options::default()
+        .with_default_extension(ron::extensions::Extensions::IMPLICIT_SOME)
+        .from_str::<ShapeFile>(text)
+        .map_err(|err| Error::new(ErrorKind::InvalidData, err.to_string()))?;
+
+    let mut names = HashSet::with_capacity(file.shapes.len());
+    file.shapes
+        .into_iter()
+        .enumerate()
+        .map(|(i, def)| {
+            if !names.insert(def.name.clone()) {
+                return Err(shape_error(i, &def.name, "the name is used more than once"));
+            }
+            let name = def.name.clone();
+            build_shape(def).map_err(|message| shape_error(i, &name, &message))
+        })
+        .collect()
+}
+
+fn shape_error(index: usize, name: &str, message: &str) -> Error {
+    Error::new(
+        ErrorKind::InvalidData,
+        format!("shape {index} ({name:?}): {message}"),
+    )
+}
+
+fn to_vec2(points: &[(f32, f32)]) -> Vec<Vec2> {
+    points.iter().map(|&(x, y)| Vec2::new(x, y)).collect()
+}
+
+/// Checks a shape's definition and turns it into an initializer.
+fn build_shape(def: ShapeDef) -> std::result::Result<InitializeShape, String> {
+    if def.name.is_empty() {
+        return Err("the name is empty".to_string());
+    }
+    let vertices = to_vec2(&def.vertices);
+    let holes = def
+        .holes
+        .iter()
+        .map(|hole| to_vec2(hole))
+        .collect::<Vec<_>>();
+    let points = vertices.len() + holes.iter().map(Vec::len).sum::<usize>();
+    let colors = def
+        .colors
+        .map(|colors| colors.into_iter().map(Vec4::from).collect::<Vec<_>>());
+    if let Some(colors) = &colors {
+        if colors.len() != points {
+            return Err(format!(
+                "there are {} colors for {points} points",
+                colors.len()
+            ));
+        }
+    }
+
+    let Some(indices) = def.indices else {
+        let (points, indices) = triangulate(&vertices, &holes).map_err(|err| err.to_string())?;
+        let shape = InitializeShape::new(def.name, points, indices);
+        let Some(colors) = colors else {
+            return Ok(shape);
+        };
+        // Triangulation may drop repeated points, so colors are matched to the points by position.
+        let colored = vertices
+            .into_iter()
+            .chain(holes.into_iter().flatten())
+            .zip(colors)
+            .collect::<Vec<_>>();
+        let colors = shape
+            .vertices
+            .iter()
+            .map(|vertex| {
+                colored
+                    .iter()
+                    .find(|(point, _)| point == vertex)
+                    .map_or(Vec4::ONE, |(_, color)| *color)
+            })
+            .collect();
+        return Ok(shape.with_colors(colors));
+    };
+
+    if !holes.is_empty() {
+        return Err("holes can only be used without indices".to_string());
+    }
+    let shape = InitializeShape::new(def.name, vertices, indices);
+    let shape = match colors {
+        Some(colors) => shape.with_colors(colors),
+        None => shape,
+    };
+    shape.check()?;
+    Ok(shape)
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{Vec2, Vec4};
+
+    use super::parse_shape_file;
+
+    #[test]
+    fn test_parse_shape_file() {
+        let shapes = parse_shape_file(
+            r#"
+            // Comments are allowed.
+            ShapeFile(
+                shapes: [
+                    (
+                        name: "Ship",
+                        vertices: [(0.0, 0.5), (-0.5, -0.5), (0.5, -0.5)],
+                        indices: [0, 1, 2],
+                        colors: [(1.0, 0.0, 0.0, 1.0), (0.0, 1.0, 0.0, 1.0), (0.0, 0.0, 1.0, 1.0)],
+                    ),
+                    (
+                        name: "Frame",
+                        vertices: [(-0.5, -0.5), (0.5, -0.5), (0.5, 0.5), (-0.5, 0.5)],
+                        holes: [[(-0.3, -0.3), (0.3, -0.3), (0.3, 0.3), (-0.3, 0.3)]],
+                        colors: [
+                            (1.0, 1.0, 1.0, 1.0), (1.0, 1.0, 1.0, 1.0), (1.0, 1.0, 1.0, 1.0),
+                            (1.0, 1.0, 1.0, 1.0), (0.0, 0.0, 0.0, 1.0), (0.0, 0.0, 0.0, 1.0),
+                            (0.0, 0.0, 0.0, 1.0), (0.0, 0.0, 0.0, 1.0),
+                        ],
+                    ),
+                ],
+            )
+            "#,
+        )
+        .unwrap();
+        assert_eq!(shapes.len(), 2);
+        assert_eq!(shapes[0].name, "Ship");
+        assert_eq!(shapes[0].indices, vec![0, 1, 2]);
+        assert_eq!(
+            shapes[0].colors.as_ref().unwrap()[1],
+            Vec4::new(0.0, 1.0, 0.0, 1.0)
+        );
+
+        let frame = &shapes[1];
+        assert_eq!(frame.indices.len(), 8 * 3);
+        let colors = frame.colors.as_ref().unwrap();
+        for (vertex, color) in frame.vertices.iter().zip(colors) {
+            let inner = vertex.abs().max_element() < 0.4;
+            assert_eq!(color.x, if inner { 0.0 } else { 1.0 });
+        }
+        assert!(frame.vertices.contains(&Vec2::new(0.3, 0.3)));
+    }
+
+    #[test]
+    fn test_example_shape_file() {
+        let shapes = parse_shape_file(include_str!("../assets/shapes/ships.ron")).unwrap();
+        let names = shapes
+            .iter()
+            .map(|shape| shape.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["Fighter", "Cruiser", "Station"]);
+    }
+
+    #[test]
+    fn test_shape_file_errors() {
+        let error = |text: &str| parse_shape_file(text).unwrap_err().to_string();
+
+        assert!(error("ShapeFile(shapes: [(name: \"A\")])").contains("vertices"));
+        assert!(
+            error("ShapeFile(shapes: [(name: \"A\", vertices: [], size: 2)])").contains("size")
+        );
+        let triangle = "vertices: [(0.0, 0.5), (-0.5, -0.5), (0.5, -0.5)]";
+        assert_eq!(
+            error(&format!(
+                "ShapeFile(shapes: [(name: \"A\", {triangle}), (name: \"A\", {triangle})])"
+            )),
+            "shape 1 (\"A\"): the name is used more than once"
+        );
+        assert_eq!(
+            error(&format!(
+                "ShapeFile(shapes: [(name: \"A\", {triangle}, indices: [0, 1, 3])])"
+            )),
+            "shape 0 (\"A\"): index 3 is out of bounds for 3 vertices"
+        );
+        assert!(error(&format!(
+            "ShapeFile(shapes: [(name: \"A\", {triangle}, indices: [0, 1])])"
+        ))
+        .contains("multiple of 3"));
+        assert!(error(&format!(
+            "ShapeFile(shapes: [(name: \"A\", {triangle}, colors: [(1.0, 1.0, 1.0, 1.0)])])"
+        ))
+        .contains("1 colors for 3 points"));
+        assert!(error(
+            "ShapeFile(shapes: [(name: \"Bowtie\", vertices: [(0.0, 0.0), (2.0, 2.0), (2.0, 0.0), (0.0, 1.0)])])"
+        )
+        .contains("intersect"));
+    }
+}
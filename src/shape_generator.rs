@@ -0,0 +1,297 @@
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+
+use glam::Vec2;
+
+use crate::shape::InitializeShape;
+
+/// The fewest sides or segments a generated shape is built from.
+const MIN_SEGMENTS: u32 = 3;
+
+/// Points closer together than this are merged when building a shape's outline.
+const MERGE_DISTANCE: f32 = 1e-5;
+
+/// Creates a regular polygon with the given number of sides, fitting inside a circle of radius
+/// 0.5 with its first corner pointing straight up.
+///
+/// Polygons have at least three sides.
+#[must_use]
+pub fn regular_polygon(name: String, sides: u32) -> InitializeShape {
+    let sides = sides.max(MIN_SEGMENTS);
+    let outline = (0..sides)
+        .map(|i| Vec2::from_angle(FRAC_PI_2 + TAU * fraction(i, sides)) * 0.5)
+        .collect();
+    fan(name, Vec2::ZERO, outline, true)
+}
+
+/// Creates a circle of radius 0.5 made from the given number of segments.
+#[must_use]
+pub fn circle(name: String, segments: u32) -> InitializeShape {
+    ellipse(name, Vec2::splat(0.5), segments)
+}
+
+/// Creates an ellipse with the given horizontal and vertical radii, made from the given number of
+/// segments.
+///
+/// Ellipses have at least three segments.
+#[must_use]
+pub fn ellipse(name: String, radii: Vec2, segments: u32) -> InitializeShape {
+    let segments = segments.max(MIN_SEGMENTS);
+    let outline = (0..segments)
+        .map(|i| Vec2::from_angle(TAU * fraction(i, segments)) * radii)
+        .collect();
+    fan(name, Vec2::ZERO, outline, true)
+}
+
+/// Creates a star with the given number of points, reaching out to a radius of 0.5 with its first
+/// point straight up.
+///
+/// `inner_radius` is the distance from the center to the corners between the points, and is
+/// clamped between 0 and 0.5. Stars have at least three points.
+#[must_use]
+pub fn star(name: String, points: u32, inner_radius: f32) -> InitializeShape {
+    let corners = points.max(MIN_SEGMENTS) * 2;
+    let inner_radius = inner_radius.clamp(0.0, 0.5);
+    let outline = (0..corners)
+        .map(|i| {
+            let radius = if i % 2 == 0 { 0.5 } else { inner_radius };
+            Vec2::from_angle(FRAC_PI_2 + TAU * fraction(i, corners)) * radius
+        })
+        .collect();
+    fan(name, Vec2::ZERO, outline, true)
+}
+
+/// Creates a ring with an outer radius of 0.5, made from the given number of segments.
+///
+/// `inner_radius` is the radius of the hole in the middle, and is clamped between 0 and 0.5. A ring
+/// without a hole is a circle.
+#[must_use]
+pub fn ring(name: String, inner_radius: f32, segments: u32) -> InitializeShape {
+    let segments = segments.max(MIN_SEGMENTS);
+    let inner_radius = inner_radius.clamp(0.0, 0.5);
+    if inner_radius <= 0.0 {
+        return circle(name, segments);
+    }
+    let mut vertices = Vec::with_capacity(segments as usize * 2);
+    for i in 0..segments {
+        let direction = Vec2::from_angle(TAU * fraction(i, segments));
+        vertices.extend([direction * 0.5, direction * inner_radius]);
+    }
+    let mut indices = Vec::with_capacity(segments as usize * 6);
+    for i in 0..segments {
+        let (outer, inner) = (i * 2, i * 2 + 1);
+        let (next_outer, next_inner) = ((outer + 2) % (segments * 2), (inner + 2) % (segments * 2));
+        indices.extend([outer, next_outer, next_inner, outer, next_inner, inner]);
+    }
+    InitializeShape::new(name, vertices, indices)
+}
+
+/// Creates a section of a ring with an outer radius of 0.5, starting at `start_angle` and sweeping
+/// counter-clockwise by `sweep_angle`, in radians from the positive x axis.
+///
+/// An `inner_radius` of 0 creates a pie slice. The inner radius is clamped between 0 and 0.5, and
+/// the sweep between -2π and 2π. Negative sweeps run clockwise from the start angle.
+#[must_use]
+pub fn arc(
+    name: String,
+    inner_radius: f32,
+    start_angle: f32,
+    sweep_angle: f32,
+    segments: u32,
+) -> InitializeShape {
+    let segments = segments.max(1);
+    let inner_radius = inner_radius.clamp(0.0, 0.5);
+    let sweep_angle = sweep_angle.clamp(-TAU, TAU);
+    // Always walk the arc counter-clockwise so the triangles face the camera.
+    let start_angle = start_angle + sweep_angle.min(0.0);
+    let sweep_angle = sweep_angle.abs();
+    let directions = (0..=segments)
+        .map(|i| Vec2::from_angle(start_angle + sweep_angle * fraction(i, segments)))
+        .collect::<Vec<_>>();
+
+    if inner_radius <= 0.0 {
+        let outline = directions
+            .iter()
+            .map(|direction| *direction * 0.5)
+            .collect();
+        return fan(name, Vec2::ZERO, outline, false);
+    }
+
+    let mut vertices = Vec::with_capacity(directions.len() * 2);
+    for direction in &directions {
+        vertices.extend([*direction * 0.5, *direction * inner_radius]);
+    }
+    let mut indices = Vec::with_capacity(segments as usize * 6);
+    for i in 0..segments {
+        let (outer, inner) = (i * 2, i * 2 + 1);
+        indices.extend([outer, outer + 2, inner + 2, outer, inner + 2, inner]);
+    }
+    InitializeShape::new(name, vertices, indices)
+}
+
+/// Creates a rectangle of the given size centered on the origin, with its corners rounded to the
+/// given radius.
+///
+/// The radius is clamped between 0 and half the shortest side. Each corner is made from
+/// `corner_segments` segments.
+#[must_use]
+pub fn rounded_rectangle(
+    name: String,
+    size: Vec2,
+    radius: f32,
+    corner_segments: u32,
+) -> InitializeShape {
+    let half_size = size.abs() / 2.0;
+    let radius = radius.clamp(0.0, half_size.min_element());
+    let corner_segments = corner_segments.max(1);
+    let inset = half_size - radius;
+    let mut outline = Vec::with_capacity((corner_segments as usize + 1) * 4);
+    for (sign, start_angle) in [
+        (Vec2::new(1.0, 1.0), 0.0),
+        (Vec2::new(-1.0, 1.0), FRAC_PI_2),
+        (Vec2::new(-1.0, -1.0), PI),
+        (Vec2::new(1.0, -1.0), PI + FRAC_PI_2),
+    ] {
+        outline.extend((0..=corner_segments).map(|i| {
+            let angle = start_angle + FRAC_PI_2 * fraction(i, corner_segments);
+            inset * sign + Vec2::from_angle(angle) * radius
+        }));
+    }
+    fan(name, Vec2::ZERO, outline, true)
+}
+
+/// Creates a capsule of the given size centered on the origin, with its shortest sides rounded
+/// into half circles.
+///
+/// Each half circle is made from `segments` segments.
+#[must_use]
+pub fn capsule(name: String, size: Vec2, segments: u32) -> InitializeShape {
+    let radius = size.abs().min_element() / 2.0;
+    rounded_rectangle(name, size, radius, segments.max(2).div_ceil(2))
+}
+
+/// How far `i` is through `count` steps.
+#[allow(clippy::cast_precision_loss)]
+fn fraction(i: u32, count: u32) -> f32 {
+    i as f32 / count as f32
+}
+
+/// Triangulates a shape as a fan of triangles from `hub` to each edge of the outline.
+///
+/// The outline should run counter-clockwise, and every point of it should be visible from the hub.
+/// A `closed` outline goes all the way around the hub, while an open one starts and ends on either
+/// side of it. Points which land on top of their neighbours are merged, and an open outline which
+/// ends where it started is closed.
+fn fan(name: String, hub: Vec2, outline: Vec<Vec2>, closed: bool) -> InitializeShape {
+    let mut vertices = Vec::with_capacity(outline.len() + 1);
+    vertices.push(hub);
+    for point in outline {
+        if vertices[1..]
+            .last()
+            .map_or(true, |last| last.distance(point) > MERGE_DISTANCE)
+        {
+            vertices.push(point);
+        }
+    }
+    let wraps =
+        vertices.len() > 2 && vertices[1].distance(vertices[vertices.len() - 1]) <= MERGE_DISTANCE;
+    if wraps {
+        vertices.pop();
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    let points = vertices.len() as u32 - 1;
+    let edges = if closed || wraps {
+        points
+    } else {
+        points.saturating_sub(1)
+    };
+    let indices = (0..edges)
+        .flat_map(|i| [0, i + 1, (i + 1) % points + 1])
+        .collect();
+    InitializeShape::new(name, vertices, indices)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::{FRAC_PI_2, PI};
+
+    use glam::Vec2;
+
+    use super::{arc, capsule, circle, regular_polygon, ring, rounded_rectangle, star};
+    use crate::shape::InitializeShape;
+
+    /// The area of each triangle of the shape, which is negative for clockwise triangles.
+    fn triangle_areas(shape: &InitializeShape) -> Vec<f32> {
+        shape
+            .indices
+            .chunks_exact(3)
+            .map(|t| {
+                let [a, b, c] = [0, 1, 2].map(|i| shape.vertices[t[i] as usize]);
+                (b - a).perp_dot(c - a) / 2.0
+            })
+            .collect()
+    }
+
+    fn area(shape: &InitializeShape) -> f32 {
+        triangle_areas(shape).iter().sum()
+    }
+
+    #[test]
+    fn test_triangles_face_forward() {
+        let shapes = [
+            regular_polygon("Hexagon".to_string(), 6),
+            circle("Circle".to_string(), 32),
+            star("Star".to_string(), 5, 0.2),
+            ring("Ring".to_string(), 0.3, 16),
+            arc("Arc".to_string(), 0.2, 1.0, -4.0, 12),
+            arc("Pie".to_string(), 0.0, 0.0, 7.0, 12),
+            rounded_rectangle("Rounded".to_string(), Vec2::new(1.0, 0.5), 0.1, 4),
+            capsule("Capsule".to_string(), Vec2::new(1.0, 0.4), 8),
+        ];
+        for shape in &shapes {
+            for triangle_area in triangle_areas(shape) {
+                assert!(
+                    triangle_area > 0.0,
+                    "{} has a back facing triangle",
+                    shape.name
+                );
+            }
+            for vertex in &shape.vertices {
+                assert!(vertex.abs().max_element() <= 0.5 + f32::EPSILON);
+            }
+        }
+    }
+
+    #[test]
+    fn test_areas() {
+        let circle = circle("Circle".to_string(), 256);
+        assert!((area(&circle) - PI * 0.25).abs() < 0.001);
+        let ring = ring("Ring".to_string(), 0.25, 256);
+        assert!((area(&ring) - PI * (0.25 - 0.0625)).abs() < 0.001);
+        let quarter = arc("Quarter".to_string(), 0.0, 0.0, FRAC_PI_2, 64);
+        assert!((area(&quarter) - PI * 0.25 / 4.0).abs() < 0.001);
+        let square = rounded_rectangle("Square".to_string(), Vec2::ONE, 0.0, 4);
+        assert_eq!(square.vertices.len(), 5);
+        assert!((area(&square) - 1.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_merges_touching_corners() {
+        // The straight sides of a round capsule have no length, so its corners meet.
+        let capsule = capsule("Capsule".to_string(), Vec2::ONE, 8);
+        assert_eq!(capsule.vertices.len(), 17);
+        assert_eq!(capsule.indices.len(), 16 * 3);
+        // A full pie closes back on its first point.
+        let pie = arc("Pie".to_string(), 0.0, 0.0, 10.0, 8);
+        assert_eq!(pie.vertices.len(), 9);
+        assert_eq!(pie.indices.len(), 8 * 3);
+    }
+
+    #[test]
+    fn test_star_points() {
+        let star = star("Star".to_string(), 5, 0.2);
+        assert_eq!(star.vertices.len(), 11);
+        assert!((star.vertices[1] - Vec2::new(0.0, 0.5)).length() < 0.001);
+        assert!((star.vertices[2].length() - 0.2).abs() < 0.001);
+    }
+}
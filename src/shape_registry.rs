@@ -1,9 +1,16 @@
-use std::{collections::HashMap, hash::BuildHasherDefault};
+use std::{
+    collections::HashMap,
+    hash::BuildHasherDefault,
+    io::{Error, ErrorKind, Result},
+};
 
-use glam::Vec2;
+use glam::{Vec2, Vec4};
 use hashers::fx_hash::FxHasher;
 
-use crate::shape::Shape2D;
+use crate::{
+    shape::{InitializeShape, Shape2D, ShapeBounds},
+    shape_generator,
+};
 
 /// A registry of renderable shapes.
 ///
@@ -11,20 +18,44 @@ use crate::shape::Shape2D;
 /// which they can then be referenced by.
 ///
 /// All shapes of the same ID are drawn using GPU instancing.
+///
+/// Shapes can also be added, replaced and removed at runtime by any system with a
+/// `ResMut<ShapeRegistry>`. Their buffers are created once game logic has run, at the start of the
+/// [`libprim::state::CoreStages::Collect`] stage, so instances of a shape added during a frame are
+/// drawn in that same frame. An ID is never reused once its shape is
+/// removed, and instances still using it are skipped when drawing.
+///
+/// ## Example
+/// ```
+/// # use bevy_ecs::system::ResMut;
+/// # use libprim::{shape_generator, shape_registry::ShapeRegistry};
+/// fn grow_polygon(mut shape_registry: ResMut<ShapeRegistry>) {
+///     let sides = 7;
+///     let polygon = shape_generator::regular_polygon("Polygon".to_string(), sides);
+///     // Instances keep the same shape ID, and are drawn with the new geometry from now on.
+///     if shape_registry.replace_shape(polygon.clone()).is_err() {
+///         shape_registry.add_shape(polygon).unwrap();
+///     }
+/// }
+/// ```
 #[derive(Debug)]
 pub struct ShapeRegistry {
-    shapes: Vec<Shape2D>,
+    shapes: Vec<Option<Shape2D>>,
+    bounds: Vec<Option<ShapeBounds>>,
     index: HashMap<String, u32, BuildHasherDefault<FxHasher>>,
+    pending: Vec<(u32, InitializeShape)>,
 }
 
 impl Default for ShapeRegistry {
     fn default() -> Self {
         Self {
             shapes: Vec::with_capacity(100),
+            bounds: Vec::with_capacity(100),
             index: HashMap::with_capacity_and_hasher(
                 100,
                 BuildHasherDefault::<FxHasher>::default(),
             ),
+            pending: Vec::new(),
         }
     }
 }
@@ -35,11 +66,14 @@ impl ShapeRegistry {
         Self::default()
     }
 
-    /// Registers a shape by name with the given points and indices.
+    /// Registers a shape by name with the given points, optional per-point colors and indices.
     ///
     /// Creates and stores a vertex and index buffer for the given shape to be used
     /// by all instances of the shape.
     ///
+    /// Registering a name which is already taken replaces the geometry of that shape, and returns
+    /// its existing ID.
+    ///
     /// # Panics
     /// This will panic under the following conditions:
     /// - if more than `u32::MAX` indices are passed in.
@@ -48,29 +82,121 @@ impl ShapeRegistry {
         &mut self,
         name: String,
         points: Vec<Vec2>,
+        colors: Option<Vec<Vec4>>,
         indices: Vec<u32>,
         device: &wgpu::Device,
     ) -> u32 {
-        self.shapes.push(Shape2D::create_from_points(
-            name.clone(),
-            points,
-            indices,
-            device,
+        let id = match self.get_id(&name) {
+            Some(id) => {
+                self.pending.retain(|(pending, _)| *pending != id);
+                id
+            }
+            None => self.reserve_id(name.clone()),
+        };
+        self.bounds[id as usize] = Some(ShapeBounds::from_points(&points));
+        self.shapes[id as usize] = Some(Shape2D::create_from_points(
+            name, points, colors, indices, device,
         ));
+        id
+    }
 
-        assert!(
-            u32::try_from(self.shapes.len()).is_ok(),
-            "Cannot register more than {} shapes",
-            u32::MAX
-        );
+    /// Adds a new shape, returning the ID it can be drawn with.
+    ///
+    /// The shape's buffers are created before the next frame is drawn, so this can be called from
+    /// any system.
+    ///
+    /// # Errors
+    /// Returns an error if a shape with the same name already exists, or if the shape's indices
+    /// or colors don't match its vertices.
+    ///
+    /// # Panics
+    /// This will panic if more than `u32::MAX` total shapes are registered.
+    pub fn add_shape(&mut self, shape: InitializeShape) -> Result<u32> {
+        shape.validate()?;
+        if self.index.contains_key(&shape.name) {
+            return Err(Error::new(
+                ErrorKind::AlreadyExists,
+                format!("Shape {:?} already exists", shape.name),
+            ));
+        }
+        let id = self.reserve_id(shape.name.clone());
+        self.bounds[id as usize] = Some(ShapeBounds::from_points(&shape.vertices));
+        self.pending.push((id, shape));
+        Ok(id)
+    }
 
-        #[allow(clippy::cast_possible_truncation)]
-        let id = (self.shapes.len() - 1) as u32;
-        self.index.insert(name, id);
+    /// Replaces the geometry of the shape with the same name, returning its ID.
+    ///
+    /// Instances of the shape keep their ID, and are drawn with the new geometry from the next
+    /// frame on.
+    ///
+    /// # Errors
+    /// Returns an error if there's no shape with the same name, or if the shape's indices or colors
+    /// don't match its vertices.
+    pub fn replace_shape(&mut self, shape: InitializeShape) -> Result<u32> {
+        shape.validate()?;
+        let Some(id) = self.get_id(&shape.name) else {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                format!("Shape {:?} doesn't exist", shape.name),
+            ));
+        };
+        self.pending.retain(|(pending, _)| *pending != id);
+        self.bounds[id as usize] = Some(ShapeBounds::from_points(&shape.vertices));
+        self.pending.push((id, shape));
+        Ok(id)
+    }
+
+    /// Removes the shape with the given name and frees its buffers, returning the ID it had.
+    ///
+    /// The ID isn't given to any later shape, and instances still using it aren't drawn. Returns
+    /// `None` if there's no shape with the name.
+    pub fn remove_shape(&mut self, name: &str) -> Option<u32> {
+        let id = self.index.remove(name)?;
+        self.shapes[id as usize] = None;
+        self.bounds[id as usize] = None;
+        self.pending.retain(|(pending, _)| *pending != id);
+        Some(id)
+    }
+
+    /// Creates the buffers of every shape added or replaced since the last call.
+    pub(crate) fn upload_pending_shapes(&mut self, device: &wgpu::Device) {
+        for (id, shape) in self.pending.drain(..) {
+            self.shapes[id as usize] = Some(Shape2D::create_from_points(
+                shape.name,
+                shape.vertices,
+                shape.colors,
+                shape.indices,
+                device,
+            ));
+        }
+    }
 
+    /// Takes the next ID for a shape of the given name, without any geometry yet.
+    fn reserve_id(&mut self, name: String) -> u32 {
+        let id = u32::try_from(self.shapes.len())
+            .unwrap_or_else(|_| panic!("Cannot register more than {} shapes", u32::MAX));
+        self.shapes.push(None);
+        self.bounds.push(None);
+        self.index.insert(name, id);
         id
     }
 
+    /// Registers a shape from its initializer.
+    pub(crate) fn register_initialize_shape(
+        &mut self,
+        shape: InitializeShape,
+        device: &wgpu::Device,
+    ) -> u32 {
+        self.register_shape(
+            shape.name,
+            shape.vertices,
+            shape.colors,
+            shape.indices,
+            device,
+        )
+    }
+
     /// Gets the ID of a specified shape by the name it was registered with.
     #[inline(always)]
     #[must_use]
@@ -79,22 +205,40 @@ impl ShapeRegistry {
     }
 
     /// Get the shape data for the specified ID.
+    ///
+    /// Returns `None` if the shape was removed or its buffers haven't been created yet.
     #[inline(always)]
     #[must_use]
-    pub(crate) fn get_shape(&self, id: u32) -> &Shape2D {
-        &self.shapes[id as usize]
+    pub(crate) fn get_shape(&self, id: u32) -> Option<&Shape2D> {
+        self.shapes.get(id as usize)?.as_ref()
+    }
+
+    /// Gets the bounds of the shape with the specified ID, in its own space.
+    ///
+    /// Bounds are computed when a shape is registered, added or replaced, so they're available
+    /// before its buffers are created. Returns `None` if there's no shape with the ID.
+    #[inline(always)]
+    #[must_use]
+    pub fn get_bounds(&self, id: u32) -> Option<ShapeBounds> {
+        self.bounds.get(id as usize).copied().flatten()
     }
 
     /// Seeds the registry with some default primitives for convenience.
+    ///
+    /// The predefined shapes come first so they keep their IDs, followed by the generated ones.
     pub(crate) fn register_builtin_shapes(&mut self, device: &wgpu::Device) {
         for shape in &SHAPE_PREDEFS {
             self.register_shape(
                 shape.name.to_string(),
                 shape.points.to_vec(),
+                None,
                 shape.indices.to_vec(),
                 device,
             );
         }
+        for shape in generated_builtin_shapes() {
+            self.register_initialize_shape(shape, device);
+        }
     }
 }
 
@@ -147,3 +291,89 @@ const SQUARE_PREDEF: ShapePredef = ShapePredef::new(
 );
 
 const SHAPE_PREDEFS: [ShapePredef; 3] = [LINE_PREDEF, TRIANGLE_PREDEF, SQUARE_PREDEF];
+
+/// The built-in shapes made by the [`shape_generator`] functions.
+fn generated_builtin_shapes() -> [InitializeShape; 6] {
+    [
+        shape_generator::circle("Circle".to_string(), 32),
+        shape_generator::regular_polygon("Pentagon".to_string(), 5),
+        shape_generator::regular_polygon("Hexagon".to_string(), 6),
+        shape_generator::regular_polygon("Octagon".to_string(), 8),
+        shape_generator::star("Star".to_string(), 5, 0.2),
+        shape_generator::ring("Ring".to_string(), 0.35, 32),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::ErrorKind;
+
+    use glam::Vec2;
+
+    use super::ShapeRegistry;
+    use crate::{shape::InitializeShape, shape_generator};
+
+    fn triangle(name: &str) -> InitializeShape {
+        InitializeShape::new(
+            name.to_string(),
+            vec![Vec2::new(0.0, 0.5), Vec2::splat(-0.5), Vec2::new(0.5, -0.5)],
+            vec![0, 1, 2],
+        )
+    }
+
+    #[test]
+    fn test_add_and_replace() {
+        let mut registry = ShapeRegistry::new();
+        let a = registry.add_shape(triangle("A")).unwrap();
+        let b = registry.add_shape(triangle("B")).unwrap();
+        assert_eq!((a, b), (0, 1));
+        assert_eq!(registry.get_id("B"), Some(b));
+        // Nothing is drawable until the buffers are created.
+        assert!(registry.get_shape(a).is_none());
+
+        let err = registry.add_shape(triangle("A")).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+        let err = registry.replace_shape(triangle("C")).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+
+        // Replacing a shape that hasn't been uploaded yet only keeps the latest geometry.
+        let square = shape_generator::regular_polygon("A".to_string(), 4);
+        assert_eq!(registry.replace_shape(square).unwrap(), a);
+        assert_eq!(registry.pending.len(), 2);
+        assert_eq!(registry.pending[1].0, a);
+        assert_eq!(registry.pending[1].1.vertices.len(), 5);
+        // Bounds follow the latest geometry straight away.
+        assert!((registry.get_bounds(a).unwrap().radius - 0.5).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut registry = ShapeRegistry::new();
+        let a = registry.add_shape(triangle("A")).unwrap();
+        assert_eq!(registry.remove_shape("A"), Some(a));
+        assert_eq!(registry.remove_shape("A"), None);
+        assert_eq!(registry.get_id("A"), None);
+        assert!(registry.pending.is_empty());
+        assert_eq!(registry.get_bounds(a), None);
+
+        // Removed IDs aren't reused, so stale instances don't pick up a different shape.
+        assert_eq!(registry.add_shape(triangle("A")).unwrap(), a + 1);
+        assert!(registry.get_shape(a).is_none());
+    }
+
+    #[test]
+    fn test_invalid_shapes() {
+        let mut registry = ShapeRegistry::new();
+        let mut shape = triangle("A");
+        shape.indices = vec![0, 1, 3];
+        let err = registry.add_shape(shape).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        assert_eq!(registry.get_id("A"), None);
+
+        let mut shape = triangle("B");
+        shape.indices.push(0);
+        assert!(registry.add_shape(shape).is_err());
+        let shape = triangle("C").with_colors(vec![]);
+        assert!(registry.add_shape(shape).is_err());
+    }
+}
@@ -1,5 +1,6 @@
 use bevy_ecs::{
-    prelude::{Bundle, Component, DetectChanges, Events},
+    change_detection::DetectChanges,
+    prelude::{Bundle, Component, Entity, Events},
     query::{Changed, With},
     schedule::{
         IntoSystemDescriptor, ParallelSystemDescriptorCoercion, Schedule, ShouldRun, Stage,
@@ -9,26 +10,43 @@ use bevy_ecs::{
     world::{Mut, World},
 };
 use glam::{Vec2, Vec3, Vec4};
-use log::{error, info};
+use log::{debug, error, info};
+use std::ops::Range;
 use wgpu_text::section::{OwnedText, Section, Text};
 use winit::{
     event::{ElementState, KeyboardInput, WindowEvent},
     window::Window,
 };
 
+#[cfg(all(feature = "hot_reload", not(target_arch = "wasm32")))]
+use crate::hot_reload::{self, HotReload, WatchedAsset};
 use crate::{
-    camera::Camera2D,
+    camera::{Camera2D, CameraClear, CameraFeed, PixelRect, RenderTarget, ViewMatrix},
+    capture::{self, FrameCapture, FrameRecorder, Recording, Screenshot},
+    collision,
+    debug_draw::DebugDraw,
+    headless::HeadlessOptions,
     initialization::{InitializeCommand, InitializerQueue},
     input::{Keyboard, Mouse},
-    instance::{Inst, Instance2D},
+    instance::{Gradient, Inst, Instance2D, InstanceData, Layer, ShaderEffect},
+    light::{self, AmbientLight, ShadowCaster, Unlit},
+    material::{BlendMode, Material, MaterialRegistry},
+    particle_system::effects::{InitializeParticleEffectFile, ParticleEffects},
     pipeline::{
-        PrimBindGroupLayouts, PrimBindGroups, PrimBuffers, PrimPipelines, PrimShaderModules,
-        PrimTargets,
+        Globals, OffscreenTarget, PrimBindGroupLayouts, PrimBindGroups, PrimBuffers, PrimOutput,
+        PrimPipelineLayouts, PrimTargets, RenderTexture,
     },
-    shape::DrawShape2D,
+    polyline::Polyline,
+    post_process::{PostProcessChain, PostProcessor},
+    render_settings::{supported_sample_count, RenderSettings},
+    shape::{DrawShape2D, InitializeShape, Shape2DVertex},
+    shape_file::InitializeShapeFile,
     shape_registry::ShapeRegistry,
+    stroke::{stroke_path, LineCap, LineJoin, StrokeStyle},
+    svg::InitializeSvg,
     text::{FontRegistry, TextSection},
     time::Time,
+    util::FxHashMap,
     window::{PrimWindow, PrimWindowResized},
 };
 
@@ -93,7 +111,89 @@ impl State {
         .unwrap();
         info!("Starting with backend: {:?}", adapter.get_info().backend);
 
-        let (device, queue) = pollster::block_on(adapter.request_device(
+        let (device, queue) = Self::request_device(&adapter).unwrap();
+
+        let settings = RenderSettings::default()
+            .with_clear_color(clear_color)
+            .with_sample_count(sample_count)
+            .with_vsync(vsync);
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: surface.get_supported_formats(&adapter)[0],
+            width: size.width,
+            height: size.height,
+            present_mode: settings.present_mode(),
+            alpha_mode: wgpu::CompositeAlphaMode::PostMultiplied,
+        };
+        surface.configure(&device, &config);
+
+        Self::from_device(
+            size,
+            PrimOutput::Surface(surface),
+            config,
+            &adapter,
+            device,
+            queue,
+            settings,
+        )
+    }
+
+    /// Start a new application state which renders into an offscreen texture instead of a window.
+    ///
+    /// Frames are rendered by calling [`State::render_frame`] and can be read back with
+    /// [`State::capture_frame`].
+    ///
+    /// Returns `None` if wgpu fails to find an adapter with the specified options, or if it is
+    /// unable to initialize the device and queue.
+    #[must_use]
+    pub fn new_headless(options: &HeadlessOptions) -> Option<Self> {
+        let instance = wgpu::Instance::new(wgpu::Backends::all());
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            compatible_surface: None,
+            force_fallback_adapter: options.force_fallback_adapter,
+        }))?;
+        info!(
+            "Starting headless with adapter: {} ({:?})",
+            adapter.get_info().name,
+            adapter.get_info().backend
+        );
+
+        let (device, queue) = match Self::request_device(&adapter) {
+            Ok(device_queue) => device_queue,
+            Err(err) => {
+                error!("Error requesting device: {err:?}");
+                return None;
+            }
+        };
+
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            width: options.size.0.max(1),
+            height: options.size.1.max(1),
+            present_mode: wgpu::PresentMode::Fifo,
+            alpha_mode: wgpu::CompositeAlphaMode::Opaque,
+        };
+        let output = PrimOutput::Offscreen(OffscreenTarget::new(&device, &config));
+
+        Some(Self::from_device(
+            winit::dpi::PhysicalSize::new(config.width, config.height),
+            output,
+            config,
+            &adapter,
+            device,
+            queue,
+            RenderSettings::default()
+                .with_clear_color(options.clear_color)
+                .with_sample_count(options.sample_count),
+        ))
+    }
+
+    fn request_device(
+        adapter: &wgpu::Adapter,
+    ) -> Result<(wgpu::Device, wgpu::Queue), wgpu::RequestDeviceError> {
+        pollster::block_on(adapter.request_device(
             &wgpu::DeviceDescriptor {
                 features: wgpu::Features::empty(),
                 limits: if cfg!(target_arch = "wasm32") {
@@ -105,22 +205,20 @@ impl State {
             },
             None,
         ))
-        .unwrap();
-
-        let config = wgpu::SurfaceConfiguration {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            format: surface.get_supported_formats(&adapter)[0],
-            width: size.width,
-            height: size.height,
-            present_mode: if vsync {
-                wgpu::PresentMode::AutoVsync
-            } else {
-                wgpu::PresentMode::AutoNoVsync
-            },
-            alpha_mode: wgpu::CompositeAlphaMode::PostMultiplied,
-        };
-        surface.configure(&device, &config);
+    }
 
+    /// Sets up the world, schedule and render state once a device and output have been created.
+    ///
+    /// An unsupported sample count in the `settings` falls back to one the adapter supports.
+    fn from_device(
+        size: winit::dpi::PhysicalSize<u32>,
+        output: PrimOutput,
+        config: wgpu::SurfaceConfiguration,
+        adapter: &wgpu::Adapter,
+        device: wgpu::Device,
+        queue: wgpu::Queue,
+        mut settings: RenderSettings,
+    ) -> Self {
         #[allow(clippy::cast_precision_loss)]
         let camera2d = Camera2D::new(
             Vec2::new(0.0, 0.0),
@@ -134,14 +232,32 @@ impl State {
         let keyboard = Keyboard::new();
         let mouse = Mouse::new();
 
+        // The multisampled pass resolves into the output, so the format must support both.
+        let multisample_supported = adapter
+            .get_texture_format_features(config.format)
+            .flags
+            .contains(
+                wgpu::TextureFormatFeatureFlags::MULTISAMPLE
+                    | wgpu::TextureFormatFeatureFlags::MULTISAMPLE_RESOLVE,
+            );
+        settings.sample_count =
+            supported_sample_count(settings.sample_count, multisample_supported);
+
         let render_state = Self::create_render_state(
             config,
-            surface,
+            output,
             device,
             queue,
-            &camera2d,
-            clear_color,
-            sample_count,
+            &settings,
+            multisample_supported,
+        );
+        let mut material_registry = MaterialRegistry::new();
+        material_registry.register_builtin_materials(
+            &render_state.device,
+            &render_state.config,
+            &render_state.bind_group_layouts,
+            &render_state.pipeline_layouts,
+            render_state.sample_count,
         );
 
         let mut world = World::default();
@@ -150,8 +266,10 @@ impl State {
             &mut world,
             camera2d,
             render_state,
+            settings,
             time,
             shape_registry,
+            material_registry,
             keyboard.clone(),
             mouse.clone(),
         );
@@ -176,6 +294,73 @@ impl State {
         self.initializer_queue.queue.push_back(command);
     }
 
+    /// Registers shapes loaded by an initializer into the [`ShapeRegistry`].
+    fn register_shapes(world: &mut World, shapes: Vec<InitializeShape>) {
+        world.resource_scope(|world, mut shape_registry: Mut<ShapeRegistry>| {
+            if let Some(render_state) = world.get_resource::<RenderState>() {
+                for shape in shapes {
+                    shape_registry.register_initialize_shape(shape, &render_state.device);
+                }
+            }
+        });
+    }
+
+    fn load_shape_file(world: &mut World, initialize_shape_file: &InitializeShapeFile) {
+        #[cfg(all(feature = "hot_reload", not(target_arch = "wasm32")))]
+        world
+            .resource_mut::<HotReload>()
+            .watch(WatchedAsset::ShapeFile(initialize_shape_file.clone()));
+        match initialize_shape_file.load() {
+            Ok(shapes) => Self::register_shapes(world, shapes),
+            Err(err) => error!(
+                "Error loading shape file {}: {}",
+                initialize_shape_file.describe(),
+                err
+            ),
+        }
+    }
+
+    fn load_svg(world: &mut World, initialize_svg: &InitializeSvg) {
+        #[cfg(all(feature = "hot_reload", not(target_arch = "wasm32")))]
+        world
+            .resource_mut::<HotReload>()
+            .watch(WatchedAsset::Svg(initialize_svg.clone()));
+        match initialize_svg.load() {
+            Ok(shapes) => Self::register_shapes(world, shapes),
+            Err(err) => error!("Error loading SVG {}: {}", initialize_svg.describe(), err),
+        }
+    }
+
+    /// Loads the effects of an effect file into the [`ParticleEffects`], looking up their shapes
+    /// in the [`ShapeRegistry`].
+    fn load_particle_effect_file(
+        world: &mut World,
+        initialize_effect_file: &InitializeParticleEffectFile,
+    ) {
+        #[cfg(all(feature = "hot_reload", not(target_arch = "wasm32")))]
+        world
+            .resource_mut::<HotReload>()
+            .watch(WatchedAsset::ParticleEffectFile(
+                initialize_effect_file.clone(),
+            ));
+        match initialize_effect_file.load() {
+            Ok(definitions) => {
+                world.resource_scope(|world, mut effects: Mut<ParticleEffects>| {
+                    effects.insert_definitions(
+                        definitions,
+                        world.resource::<ShapeRegistry>(),
+                        &initialize_effect_file.describe(),
+                    );
+                });
+            }
+            Err(err) => error!(
+                "Error loading particle effect file {}: {}",
+                initialize_effect_file.describe(),
+                err
+            ),
+        }
+    }
+
     pub(crate) fn run_initializer_queue(&mut self) {
         for cmd in &self.initializer_queue.queue {
             match cmd {
@@ -202,25 +387,65 @@ impl State {
                     self.world
                         .resource_scope(|world, mut shape_registry: Mut<ShapeRegistry>| {
                             if let Some(render_state) = world.get_resource::<RenderState>() {
-                                shape_registry.register_shape(
-                                    initialize_shape.name.clone(),
-                                    initialize_shape.vertices.clone(),
-                                    initialize_shape.indices.clone(),
+                                shape_registry.register_initialize_shape(
+                                    initialize_shape.clone(),
                                     &render_state.device,
                                 );
                             }
                         });
                 }
+                InitializeCommand::InitializeShapeFile(initialize_shape_file) => {
+                    Self::load_shape_file(&mut self.world, initialize_shape_file);
+                }
+                InitializeCommand::InitializeSvg(initialize_svg) => {
+                    Self::load_svg(&mut self.world, initialize_svg);
+                }
+                InitializeCommand::InitializeParticleEffectFile(initialize_effect_file) => {
+                    Self::load_particle_effect_file(&mut self.world, initialize_effect_file);
+                }
                 InitializeCommand::InitializeCamera(initialize_camera) => {
-                    if let Some(mut camera) = self.world.get_resource_mut::<Camera2D>() {
-                        *camera = Camera2D::new(initialize_camera.position, initialize_camera.size);
-                    } else {
-                        self.world.insert_resource(Camera2D::new(
+                    // This sets up the camera spawned on startup, keeping any other settings.
+                    let initialized = self
+                        .world
+                        .query::<&mut Camera2D>()
+                        .iter_mut(&mut self.world)
+                        .next()
+                        .map(|mut camera| {
+                            camera.position = initialize_camera.position;
+                            camera.rescale(initialize_camera.size);
+                            camera.update();
+                        })
+                        .is_some();
+                    if !initialized {
+                        self.world.spawn().insert(Camera2D::new(
                             initialize_camera.position,
                             initialize_camera.size,
                         ));
                     }
                 }
+                InitializeCommand::InitializeMaterial(initialize_material) => {
+                    self.world.resource_scope(
+                        |world, mut material_registry: Mut<MaterialRegistry>| {
+                            if let Some(render_state) = world.get_resource::<RenderState>() {
+                                if let Err(err) = material_registry.register_material(
+                                    initialize_material.name.clone(),
+                                    &initialize_material.source,
+                                    initialize_material.uniforms.as_deref(),
+                                    &render_state.device,
+                                    &render_state.config,
+                                    &render_state.bind_group_layouts,
+                                    &render_state.pipeline_layouts,
+                                    render_state.sample_count,
+                                ) {
+                                    error!(
+                                        "Error loading material {}: {}",
+                                        &initialize_material.name, err
+                                    );
+                                }
+                            }
+                        },
+                    );
+                }
             }
         }
 
@@ -232,47 +457,35 @@ impl State {
 
     fn create_render_state(
         config: wgpu::SurfaceConfiguration,
-        surface: wgpu::Surface,
+        output: PrimOutput,
         device: wgpu::Device,
         queue: wgpu::Queue,
-        camera2d: &Camera2D,
-        clear_color: Vec3,
-        sample_count: u32,
+        settings: &RenderSettings,
+        multisample_supported: bool,
     ) -> RenderState {
-        let shaders = PrimShaderModules::new(&device);
         let bind_group_layouts = PrimBindGroupLayouts::new(&device);
-        let pipelines = PrimPipelines::new(
-            &device,
-            &config,
-            &bind_group_layouts,
-            &shaders,
-            sample_count,
-        );
-        let targets = PrimTargets::new(&device, &config, sample_count);
-        let buffers = PrimBuffers::new(&device, &config, camera2d);
+        let pipeline_layouts = PrimPipelineLayouts::new(&device, &bind_group_layouts);
+        let targets = PrimTargets::new(&device, &config, settings.sample_count);
+        let buffers = PrimBuffers::new(&device, &config);
         let bind_groups = PrimBindGroups::new(&device, &config, &bind_group_layouts, &buffers);
+        let post_processor = PostProcessor::new(&device, &config);
 
         RenderState {
             config,
-            surface,
+            output,
             queue,
             device,
-            // TODO: Make configurable
-            sort_renderables: false,
-            clear_color: wgpu::Color {
-                r: f64::from(clear_color.x),
-                g: f64::from(clear_color.y),
-                b: f64::from(clear_color.z),
-                a: 1.0,
-            },
-            sample_count,
+            sort_renderables: settings.sort_renderables,
+            clear_color: settings.wgpu_clear_color(),
+            sample_count: settings.sample_count,
+            multisample_supported,
             recreate_framebuffer: false,
-            shaders,
             bind_group_layouts,
-            pipelines,
+            pipeline_layouts,
             targets,
             buffers,
             bind_groups,
+            post_processor,
         }
     }
 
@@ -281,22 +494,35 @@ impl State {
         world: &mut World,
         camera2d: Camera2D,
         render_state: RenderState,
+        render_settings: RenderSettings,
         time: Time,
         shape_registry: ShapeRegistry,
+        material_registry: MaterialRegistry,
         keyboard: Keyboard,
         mouse: Mouse,
     ) {
         //world.insert_resource(HasRunMarker::<Setup>(false, Setup));
         world.insert_resource(Events::<PrimWindowResized>::default());
         world.insert_resource(PrimWindow::new(&render_state.config));
-        world.insert_resource(camera2d);
+        world.spawn().insert(camera2d);
         world.insert_resource(render_state);
+        world.insert_resource(render_settings);
         world.insert_resource(time);
         world.insert_resource(shape_registry);
+        world.insert_resource(material_registry);
         world.insert_resource(keyboard);
         world.insert_resource(mouse);
         world.insert_resource(FontRegistry::new());
-        world.insert_resource(Renderables(Vec::with_capacity(1000)));
+        world.insert_resource(AmbientLight::default());
+        world.insert_resource(PostProcessChain::default());
+        world.insert_resource(DebugDraw::default());
+        world.insert_resource(ParticleEffects::default());
+        #[cfg(all(feature = "hot_reload", not(target_arch = "wasm32")))]
+        world.insert_resource(HotReload::default());
+        world.insert_resource(FrameRecorder::new());
+        world.insert_resource(Events::<Screenshot>::default());
+        world.insert_resource(Events::<Recording>::default());
+        world.insert_resource(Renderables::with_capacity(1000));
         world.insert_resource(RenderResult(Ok(())));
         world.insert_resource(FpsCounter::new());
     }
@@ -307,7 +533,7 @@ impl State {
     /// - `pre_updated`: Used for updating items that need to be consistent for the duration of any parallel systems for the frame.
     /// - `update`: Used for any game logic.
     /// - `post_update`: Used to sync any computations necessary after game logic executes, such as view and transformation matrices.
-    /// - `collect`: Finds all renderable instances and their matrices.
+    /// - `collect`: Creates the buffers of shapes added at runtime, then finds all renderable instances and their matrices, and all visible lights.
     /// - `render`: Sends instance information to the GPU and presents.
     fn setup_schedule(schedule: &mut Schedule) {
         schedule.add_stage(
@@ -318,8 +544,12 @@ impl State {
             CoreStages::PreUpdate,
             SystemStage::parallel()
                 .with_system(update_time)
-                .with_system(update_events::<PrimWindowResized>),
+                .with_system(update_events::<PrimWindowResized>)
+                .with_system(update_events::<Screenshot>)
+                .with_system(update_events::<Recording>),
         );
+        #[cfg(all(feature = "hot_reload", not(target_arch = "wasm32")))]
+        schedule.add_system_to_stage(CoreStages::PreUpdate, hot_reload::reload_changed_assets);
         schedule.add_stage(
             CoreStages::Update,
             SystemStage::parallel().with_system(fps_counter),
@@ -328,22 +558,30 @@ impl State {
             CoreStages::PostUpdate,
             SystemStage::parallel()
                 .with_system(update_camera)
-                .with_system(sync_matrix),
+                .with_system(sync_matrix)
+                .with_system(collision::draw_collision_debug),
         );
         schedule.add_stage(
             CoreStages::Collect,
-            SystemStage::single_threaded().with_system(collect_instances),
+            SystemStage::single_threaded()
+                .with_system(upload_shapes.label("upload_shapes"))
+                .with_system(collect_instances.after("upload_shapes"))
+                .with_system(light::collect_lights.after("upload_shapes")),
         );
         schedule.add_stage(
             CoreStages::Render,
             SystemStage::parallel()
                 .with_system(main_render_pass.label("render"))
+                .with_system(capture::write_captures.after("render"))
                 .with_system_set(
                     SystemSet::new()
                         .before("render")
-                        .with_system(update_time_buffer)
-                        .with_system(update_camera_buffer)
-                        .with_system(check_framebuffer),
+                        .with_system(update_globals_buffer)
+                        .with_system(update_material_uniforms)
+                        .with_system(apply_render_settings.before("framebuffer"))
+                        .with_system(check_framebuffer.label("framebuffer"))
+                        .with_system(prepare_post_process.after("framebuffer"))
+                        .with_system(capture::request_captures),
                 ),
         );
     }
@@ -376,9 +614,13 @@ impl State {
                     render_state.config.width = new_size.width;
                     render_state.config.height = new_size.height;
                     render_state.recreate_framebuffer = true;
-                    render_state
-                        .surface
-                        .configure(&render_state.device, &render_state.config);
+                    let RenderState {
+                        output,
+                        device,
+                        config,
+                        ..
+                    } = &mut *render_state;
+                    output.configure(device, config);
                     world.send_event(PrimWindowResized::from_size(
                         new_size.width,
                         new_size.height,
@@ -459,6 +701,39 @@ impl State {
         }
     }
 
+    /// Runs a single frame of the schedule, rendering it to the output.
+    ///
+    /// This is called automatically by the event loop when running with a window, and is meant to
+    /// be called manually for states created with [`State::new_headless`].
+    ///
+    /// # Errors
+    /// Returns a `wgpu::SurfaceError` if there were any issues during rendering.
+    pub fn render_frame(&mut self) -> Result<(), wgpu::SurfaceError> {
+        self.update();
+        self.render_result()
+    }
+
+    /// Reads the most recently rendered frame back from the GPU.
+    ///
+    /// This blocks until the GPU has finished rendering, and is only available for states created
+    /// with [`State::new_headless`]. Returns `None` when rendering to a window or if the frame could
+    /// not be read.
+    #[must_use]
+    pub fn capture_frame(&self) -> Option<FrameCapture> {
+        let render_state = self.world.get_resource::<RenderState>()?;
+        match &render_state.output {
+            PrimOutput::Offscreen(target) => capture::read_texture(
+                &render_state.device,
+                &render_state.queue,
+                &target.texture,
+                render_state.config.width,
+                render_state.config.height,
+                render_state.config.format,
+            ),
+            PrimOutput::Surface(_) => None,
+        }
+    }
+
     /// Borrows the wold object mutably.
     ///
     /// This can be used to add resources to the world during the Initializer execution.
@@ -487,15 +762,187 @@ where
     events.update();
 }
 
-/// Run in the `post_update` stage, recomputes the view matrix if the camera transform has changed.
-fn update_camera(mut camera2d: ResMut<Camera2D>) {
-    if camera2d.is_changed() {
+/// Run in the `post_update` stage, recomputes the view matrix of any camera whose transform has
+/// changed.
+fn update_camera(mut cameras: Query<&mut Camera2D, Changed<Camera2D>>) {
+    for mut camera2d in &mut cameras {
         camera2d.update();
     }
 }
 
-/// Contains the collected list of renderable items.
-struct Renderables(Vec<(Instance2D, Inst)>);
+/// The geometry a renderable is drawn with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Geometry {
+    /// A shape from the [`ShapeRegistry`], by ID.
+    Shape(u32),
+    /// Triangles built this frame, by their range of vertices in the mesh buffer.
+    Mesh {
+        first_vertex: u32,
+        vertex_count: u32,
+    },
+}
+
+/// What renderables are grouped by within a layer when sorting by shape: their material, blend
+/// mode, geometry and camera feed.
+type SortKey = (u32, BlendMode, Geometry, Option<Entity>);
+
+/// A single instance collected for rendering this frame.
+#[derive(Debug, Clone, Copy)]
+struct Renderable {
+    layer: Layer,
+    material: u32,
+    blend_mode: BlendMode,
+    geometry: Geometry,
+    /// The camera whose texture is shown, for instances with a [`CameraFeed`].
+    feed: Option<Entity>,
+    /// For outlines, the sort key of the instance they belong to, so sorting keeps them drawn
+    /// straight under it.
+    outline_of: Option<SortKey>,
+    inst: Inst,
+}
+
+impl Renderable {
+    /// The key the renderable is grouped by when sorting by shape.
+    fn sort_key(&self) -> SortKey {
+        self.outline_of
+            .unwrap_or((self.material, self.blend_mode, self.geometry, self.feed))
+    }
+}
+
+/// A contiguous run of renderables with the same material, blend mode and geometry, drawn with one
+/// instanced draw call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RenderBatch {
+    material: u32,
+    blend_mode: BlendMode,
+    geometry: Geometry,
+    feed: Option<Entity>,
+    instances: Range<u32>,
+}
+
+/// A camera's view of the world, drawn into its viewport.
+#[derive(Debug, Clone)]
+struct RenderView {
+    /// The camera whose texture is drawn into, or `None` when drawing to the window.
+    target: Option<Entity>,
+    /// The camera's view projection matrix.
+    view: ViewMatrix,
+    /// The area of the output the camera draws into.
+    viewport: PixelRect,
+    /// The batches drawn in the main pass.
+    main_batches: Range<usize>,
+    /// The batches drawn over the finished frame, after any post-processing.
+    overlay_batches: Range<usize>,
+}
+
+/// Contains the collected list of renderable items, in draw order, and the batches they are drawn in.
+///
+/// Items are collected once for each camera, so each [`RenderView`] has its own run of batches.
+struct Renderables {
+    items: Vec<Renderable>,
+    batches: Vec<RenderBatch>,
+    /// The world space triangles of any geometry built this frame.
+    mesh: Vec<Vec2>,
+    /// Each camera's view of the frame, in the order they are drawn.
+    views: Vec<RenderView>,
+}
+
+impl Renderables {
+    fn with_capacity(capacity: usize) -> Self {
+        Self {
+            items: Vec::with_capacity(capacity),
+            batches: Vec::new(),
+            mesh: Vec::new(),
+            views: Vec::new(),
+        }
+    }
+
+    /// Removes everything collected for the last frame.
+    fn clear(&mut self) {
+        self.items.clear();
+        self.batches.clear();
+        self.mesh.clear();
+        self.views.clear();
+    }
+
+    /// Adds a renderable drawn with the triangles built by `build`, which appends them to the
+    /// frame's mesh, and returns it.
+    ///
+    /// Nothing is added if no triangles are built.
+    #[allow(clippy::cast_possible_truncation)]
+    fn push_mesh(
+        &mut self,
+        layer: Layer,
+        inst: Inst,
+        build: impl FnOnce(&mut Vec<Vec2>),
+    ) -> Option<&mut Renderable> {
+        let first_vertex = self.mesh.len();
+        build(&mut self.mesh);
+        let vertex_count = self.mesh.len() - first_vertex;
+        if vertex_count == 0 {
+            return None;
+        }
+        self.items.push(Renderable {
+            layer,
+            material: Material::DEFAULT.0,
+            blend_mode: BlendMode::Alpha,
+            geometry: Geometry::Mesh {
+                first_vertex: first_vertex as u32,
+                vertex_count: vertex_count as u32,
+            },
+            feed: None,
+            outline_of: None,
+            inst,
+        });
+        self.items.last_mut()
+    }
+
+    /// Orders the items collected from `start` onwards for drawing.
+    ///
+    /// Items are always ordered by [`Layer`]. The sort is stable, so within a layer items keep the
+    /// order they were collected in unless `sort_by_shape` is set, in which case they are also
+    /// grouped by material, blend mode and then shape ID. Outlines are grouped with the instance
+    /// they belong to.
+    fn sort(&mut self, start: usize, sort_by_shape: bool) {
+        let items = &mut self.items[start..];
+        if sort_by_shape {
+            items.sort_by_key(|r| (r.layer, r.sort_key()));
+        } else {
+            items.sort_by_key(|r| r.layer);
+        }
+    }
+
+    /// Splits a range of ordered items into runs of the same material, blend mode and geometry
+    /// which can each be drawn in a single draw call.
+    ///
+    /// The batches are added after any built before, and their range is returned. Batches never
+    /// span more than one range of items.
+    #[allow(clippy::cast_possible_truncation)]
+    fn build_batches(&mut self, items: Range<usize>) -> Range<usize> {
+        let first_batch = self.batches.len();
+        for (i, renderable) in self.items[items.clone()].iter().enumerate() {
+            let i = (items.start + i) as u32;
+            match self.batches[first_batch..].last_mut() {
+                Some(batch)
+                    if batch.material == renderable.material
+                        && batch.blend_mode == renderable.blend_mode
+                        && batch.geometry == renderable.geometry
+                        && batch.feed == renderable.feed =>
+                {
+                    batch.instances.end = i + 1;
+                }
+                _ => self.batches.push(RenderBatch {
+                    material: renderable.material,
+                    blend_mode: renderable.blend_mode,
+                    geometry: renderable.geometry,
+                    feed: renderable.feed,
+                    instances: i..i + 1,
+                }),
+            }
+        }
+        first_batch..self.batches.len()
+    }
+}
 
 /// Run in the `post_update` stage, syncs any changes from the transform values to the transformation matrix that'll be
 /// passed to the instance buffer.
@@ -505,171 +952,557 @@ fn sync_matrix(mut instances: Query<(&Instance2D, &mut Inst), Changed<Instance2D
     });
 }
 
-/// Collects instances current visible by the camera and writes their data to the instance buffer.
+/// Collects the instances visible to each camera and writes their data to the instance buffer.
+#[allow(clippy::type_complexity)]
 fn collect_instances(
-    instance_query: Query<(&Instance2D, &mut Inst)>,
+    instance_query: Query<(
+        &Instance2D,
+        &Inst,
+        Option<&Layer>,
+        Option<&Material>,
+        Option<&Gradient>,
+        Option<&Unlit>,
+        Option<&ShadowCaster>,
+        Option<&CameraFeed>,
+        Option<&BlendMode>,
+        Option<&InstanceData>,
+        Option<&ShaderEffect>,
+    )>,
+    polyline_query: Query<(&Polyline, Option<&Layer>, Option<&Unlit>)>,
+    cameras: Query<(Entity, &Camera2D)>,
     mut renderables: ResMut<Renderables>,
-    render_state: Res<RenderState>,
-    camera2d: Res<Camera2D>,
+    mut render_state: ResMut<RenderState>,
+    shape_registry: Res<ShapeRegistry>,
+    mut debug_draw: ResMut<DebugDraw>,
+) {
+    renderables.clear();
+    let mut cameras = cameras.iter().collect::<Vec<_>>();
+    // The sort is stable, so cameras with the same priority are drawn in ECS iteration order.
+    cameras.sort_by_key(|(_, camera2d)| camera2d.priority);
+    prepare_render_textures(&mut render_state, &cameras);
+
+    for (entity, camera2d) in cameras {
+        let (target, target_size) = match camera2d.target {
+            RenderTarget::Window => (
+                None,
+                (render_state.config.width, render_state.config.height),
+            ),
+            RenderTarget::Texture { width, height } => (Some(entity), (width, height)),
+        };
+        let Some(viewport) = camera2d.viewport.to_pixels(target_size.0, target_size.1) else {
+            continue;
+        };
+        #[allow(clippy::cast_precision_loss)]
+        let pixel_size = camera2d.scale.x / viewport.width as f32;
+
+        let start = renderables.items.len();
+        if let CameraClear::Color(color) = camera2d.clear {
+            renderables.push_mesh(
+                Layer::BACKGROUND,
+                Inst::world_space(color).with_flags(Inst::UNLIT),
+                |triangles| camera2d.view_triangles(triangles),
+            );
+        }
+        let sorted_start = renderables.items.len();
+        collect_camera_instances(
+            &mut renderables,
+            (entity, camera2d),
+            pixel_size,
+            &instance_query,
+            &polyline_query,
+            &shape_registry,
+            &render_state.targets.render_textures,
+        );
+        // Renderables are always sorted by layer so that they stack predictably. If shape sorting is
+        // enabled, they are also sorted by their material and shape ID within each layer.
+        // When shape sorting is enabled, the number of draw calls will be equal to the number of discrete material and
+        // shape pairs visible in each layer. This can be used to trade off CPU (list sorting) and GPU (draw calls).
+        renderables.sort(sorted_start, render_state.sort_renderables);
+
+        let overlay_start = renderables.items.len();
+        if target.is_none() {
+            collect_debug_draw(&mut renderables, &debug_draw, pixel_size);
+        }
+        let end = renderables.items.len();
+        let main_batches = renderables.build_batches(start..overlay_start);
+        let overlay_batches = renderables.build_batches(overlay_start..end);
+        renderables.views.push(RenderView {
+            target,
+            view: camera2d.get_view(),
+            viewport,
+            main_batches,
+            overlay_batches,
+        });
+    }
+
+    debug_draw.clear();
+    write_renderables(&mut render_state, &renderables);
+}
+
+/// Makes sure each camera with a texture target has a texture to draw into, and drops the textures
+/// of any which no longer do.
+fn prepare_render_textures(render_state: &mut RenderState, cameras: &[(Entity, &Camera2D)]) {
+    let RenderState {
+        device,
+        config,
+        bind_group_layouts,
+        targets,
+        sample_count,
+        ..
+    } = render_state;
+    let texture_size = |camera2d: &Camera2D| match camera2d.target {
+        RenderTarget::Texture { width, height } if width > 0 && height > 0 => Some((width, height)),
+        _ => None,
+    };
+    targets.render_textures.retain(|camera, _| {
+        cameras
+            .iter()
+            .any(|(entity, camera2d)| entity == camera && texture_size(camera2d).is_some())
+    });
+    for (entity, camera2d) in cameras {
+        if let Some(size) = texture_size(camera2d) {
+            targets.fit_render_texture(
+                device,
+                config,
+                bind_group_layouts,
+                *sample_count,
+                *entity,
+                size,
+            );
+        }
+    }
+}
+
+/// Collects the instances and polylines visible to a camera.
+///
+/// `pixel_size` is the size of a pixel of the camera's viewport in world units.
+#[allow(clippy::type_complexity)]
+fn collect_camera_instances(
+    renderables: &mut Renderables,
+    (camera, camera2d): (Entity, &Camera2D),
+    pixel_size: f32,
+    instance_query: &Query<(
+        &Instance2D,
+        &Inst,
+        Option<&Layer>,
+        Option<&Material>,
+        Option<&Gradient>,
+        Option<&Unlit>,
+        Option<&ShadowCaster>,
+        Option<&CameraFeed>,
+        Option<&BlendMode>,
+        Option<&InstanceData>,
+        Option<&ShaderEffect>,
+    )>,
+    polyline_query: &Query<(&Polyline, Option<&Layer>, Option<&Unlit>)>,
+    shape_registry: &ShapeRegistry,
+    render_textures: &FxHashMap<Entity, RenderTexture>,
 ) {
-    renderables.0.clear();
-
-    for (inst, render_inst) in &instance_query {
-        // Do a basic filter for where their position is within their maximum radius of the edge of the camera.
-        // This only works correctly if a shape is defined with all vertices using normalized positions between (-1.0, 1.0)
-        if inst.position.x - inst.scale.x < camera2d.position.x + camera2d.scale.x
-            && inst.position.x + inst.scale.x > camera2d.position.x - camera2d.scale.x
-            && inst.position.y - inst.scale.y < camera2d.position.y + camera2d.scale.y
-            && inst.position.y + inst.scale.y > camera2d.position.y - camera2d.scale.y
-        {
-            if let Some(outline_inst) = inst.outline_matrix() {
-                renderables.0.push((*inst, outline_inst));
+    for (
+        inst,
+        render_inst,
+        layer,
+        material,
+        gradient,
+        unlit,
+        shadow_caster,
+        camera_feed,
+        blend_mode,
+        data,
+        effect,
+    ) in instance_query
+    {
+        // Instances of removed shapes have no bounds, and aren't drawn anyway.
+        let Some(bounds) = shape_registry.get_bounds(inst.shape) else {
+            continue;
+        };
+        let (min, max) = inst.world_bounds(&bounds, pixel_size);
+        // Effects which move vertices can carry the shape past its bounds.
+        let reach = effect.map_or(0.0, |effect| {
+            effect.reach() * inst.scale.abs().max_element()
+        });
+        if camera2d.overlaps(min - reach, max + reach) {
+            let layer = layer.copied().unwrap_or_default();
+            let mut flags = 0;
+            if unlit.is_some() {
+                flags |= Inst::UNLIT;
+            }
+            if shadow_caster.is_some() {
+                flags |= Inst::SHADOW_CASTER;
+            }
+            // A camera can't draw its own texture, so the feed is left out of its own view.
+            let feed = camera_feed
+                .map(|feed| feed.0)
+                .filter(|&feed| feed != camera && render_textures.contains_key(&feed));
+            let material = match (feed, material.copied().unwrap_or_default()) {
+                (Some(_), _) => Material::CAMERA_FEED,
+                (None, Material::CAMERA_FEED) => Material::DEFAULT,
+                (None, material) => material,
+            };
+            let blend_mode = blend_mode.copied().unwrap_or_default();
+            // Outlines are always a flat color, whatever material the instance itself uses.
+            if let (Some(outline_inst), Some(shape)) =
+                (inst.outline_inst(), shape_registry.get_shape(inst.shape))
+            {
+                let boundary = &shape.boundary;
+                if let Some(outline) =
+                    renderables.push_mesh(layer, outline_inst.with_flags(flags), |triangles| {
+                        inst.outline_triangles(boundary, pixel_size, triangles);
+                    })
+                {
+                    outline.outline_of =
+                        Some((material.0, blend_mode, Geometry::Shape(inst.shape), feed));
+                }
+            }
+            if inst.outline.map_or(true, |outline| !outline.outline_only) {
+                let mut shape_inst = match gradient {
+                    Some(gradient) => render_inst.with_gradient(gradient),
+                    None => *render_inst,
+                };
+                if let Some(data) = data {
+                    shape_inst = shape_inst.with_data(data);
+                }
+                if let Some(effect) = effect {
+                    shape_inst = shape_inst.with_effect(effect);
+                }
+                renderables.items.push(Renderable {
+                    layer,
+                    material: material.0,
+                    blend_mode,
+                    geometry: Geometry::Shape(inst.shape),
+                    feed,
+                    outline_of: None,
+                    inst: shape_inst.with_flags(flags),
+                });
             }
-            renderables.0.push((*inst, *render_inst));
         }
     }
-    // If sorting is enabled, sort the shapes by their shape ID.
-    // When sorting is enabled, the number of draw calls will be equal to the number of discrete shapes visible to the
-    // camera. This can be used to trade off CPU (list sorting) and GPU (draw calls).
-    if render_state.sort_renderables {
-        renderables.0.sort_by(|a, b| a.0.shape.cmp(&b.0.shape));
+    for (polyline, layer, unlit) in polyline_query {
+        let Some((min, max)) = polyline.bounds(pixel_size) else {
+            continue;
+        };
+        if camera2d.overlaps(min, max) {
+            let flags = if unlit.is_some() { Inst::UNLIT } else { 0 };
+            renderables.push_mesh(
+                layer.copied().unwrap_or_default(),
+                Inst::world_space(polyline.color).with_flags(flags),
+                |triangles| polyline.triangles(pixel_size, triangles),
+            );
+        }
+    }
+}
+
+/// Builds the lines drawn with the [`DebugDraw`] this frame, as seen by a camera.
+///
+/// `pixel_size` is the size of a pixel of the camera's viewport in world units.
+fn collect_debug_draw(renderables: &mut Renderables, debug_draw: &DebugDraw, pixel_size: f32) {
+    let style = StrokeStyle {
+        width: debug_draw.line_width * pixel_size,
+        offset: 0.0,
+        join: LineJoin::Miter,
+        cap: LineCap::Butt,
+    };
+    for (points, closed, color) in debug_draw.paths() {
+        renderables.push_mesh(
+            Layer::DEFAULT,
+            Inst::world_space(color).with_flags(Inst::UNLIT),
+            |triangles| stroke_path(points, closed, &style, triangles),
+        );
+    }
+}
+
+/// Writes the collected camera views, instances and mesh vertices to their GPU buffers.
+fn write_renderables(render_state: &mut RenderState, renderables: &Renderables) {
+    // Each camera's view matrix is written at its own aligned offset, which is selected with a
+    // dynamic offset when the camera is drawn.
+    if render_state
+        .buffers
+        .fit_camera_buffer(&render_state.device, renderables.views.len())
+    {
+        render_state.bind_groups.camera_bind_group = PrimBindGroups::create_camera_bind_group(
+            &render_state.device,
+            &render_state.bind_group_layouts,
+            &render_state.buffers,
+        );
+    }
+    let stride = render_state.buffers.camera_stride as usize;
+    let mut camera_data = vec![0_u8; stride * renderables.views.len()];
+    for (data, view) in camera_data.chunks_exact_mut(stride).zip(&renderables.views) {
+        let matrix = bytemuck::bytes_of(&view.view);
+        data[..matrix.len()].copy_from_slice(matrix);
+    }
+    if !camera_data.is_empty() {
+        render_state
+            .queue
+            .write_buffer(&render_state.buffers.camera_buffer, 0, &camera_data);
+    }
+
+    let shape2d_instances_data = renderables.items.iter().map(|r| r.inst).collect::<Vec<_>>();
+
+    // Make sure the instance buffer can hold everything visible this frame. The render pass binds
+    // the buffer each frame, so a reallocated buffer is picked up automatically.
+    if render_state
+        .buffers
+        .fit_instance_buffer(&render_state.device, shape2d_instances_data.len())
+    {
+        debug!(
+            "Resized instance buffer to {} instances",
+            render_state.buffers.instance_capacity
+        );
     }
-    let shape2d_instances_data = renderables.0.iter().map(|(_a, b)| *b).collect::<Vec<_>>();
 
     render_state.queue.write_buffer(
         &render_state.buffers.instance_buffer,
         0,
         bytemuck::cast_slice(&shape2d_instances_data),
     );
+
+    if renderables.mesh.is_empty() {
+        return;
+    }
+    if render_state
+        .buffers
+        .fit_mesh_buffer(&render_state.device, renderables.mesh.len())
+    {
+        debug!(
+            "Resized mesh buffer to {} vertices",
+            render_state.buffers.mesh_capacity
+        );
+    }
+    let vertices = renderables
+        .mesh
+        .iter()
+        .map(|&position| Shape2DVertex {
+            position,
+            color: [1.0; 4],
+        })
+        .collect::<Vec<_>>();
+    render_state.queue.write_buffer(
+        &render_state.buffers.mesh_buffer,
+        0,
+        bytemuck::cast_slice(&vertices),
+    );
 }
 
 pub(crate) struct RenderState {
     pub config: wgpu::SurfaceConfiguration,
-    pub surface: wgpu::Surface,
+    pub output: PrimOutput,
     pub queue: wgpu::Queue,
-    #[allow(unused)]
-    pub shaders: PrimShaderModules,
-    #[allow(unused)]
     pub bind_group_layouts: PrimBindGroupLayouts,
-    pub pipelines: PrimPipelines,
+    pub pipeline_layouts: PrimPipelineLayouts,
     pub targets: PrimTargets,
     pub buffers: PrimBuffers,
     pub bind_groups: PrimBindGroups,
+    pub post_processor: PostProcessor,
 
     pub device: wgpu::Device,
     pub sort_renderables: bool,
     pub clear_color: wgpu::Color,
     pub sample_count: u32,
+    /// Whether the output format can be drawn with more than one sample.
+    pub multisample_supported: bool,
     pub recreate_framebuffer: bool,
 }
 
+/// Applies any changes to the [`RenderSettings`] made since the last frame, rebuilding the targets
+/// and pipelines which depend on the sample count.
+fn apply_render_settings(
+    mut settings: ResMut<RenderSettings>,
+    mut render_state: ResMut<RenderState>,
+    mut material_registry: ResMut<MaterialRegistry>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    let render_state = &mut *render_state;
+    render_state.clear_color = settings.wgpu_clear_color();
+    render_state.sort_renderables = settings.sort_renderables;
+
+    // Headless outputs have nothing to present to, so only a window's surface is reconfigured.
+    let present_mode = settings.present_mode();
+    if matches!(render_state.output, PrimOutput::Surface(_))
+        && render_state.config.present_mode != present_mode
+    {
+        render_state.config.present_mode = present_mode;
+        render_state
+            .output
+            .configure(&render_state.device, &render_state.config);
+    }
+
+    let sample_count =
+        supported_sample_count(settings.sample_count, render_state.multisample_supported);
+    if settings.sample_count != sample_count {
+        settings.sample_count = sample_count;
+    }
+    if render_state.sample_count != sample_count {
+        debug!("Rebuilding render targets and pipelines with {sample_count} samples");
+        render_state.sample_count = sample_count;
+        render_state.targets.set_sample_count(
+            &render_state.device,
+            &render_state.config,
+            &render_state.bind_group_layouts,
+            sample_count,
+        );
+        material_registry.rebuild_pipelines(
+            &render_state.device,
+            &render_state.config,
+            &render_state.pipeline_layouts,
+            sample_count,
+        );
+    }
+}
+
 fn check_framebuffer(mut render_state: ResMut<RenderState>) {
     if render_state.recreate_framebuffer {
-        render_state.targets = PrimTargets::new(
+        let render_state = &mut *render_state;
+        render_state.targets.resize(
             &render_state.device,
             &render_state.config,
             render_state.sample_count,
         );
+        render_state
+            .post_processor
+            .resize(&render_state.device, &render_state.config);
         render_state.recreate_framebuffer = false;
     }
 }
 
-fn update_camera_buffer(render_state: Res<RenderState>, camera2d: Res<Camera2D>) {
-    if camera2d.is_changed() {
-        render_state.queue.write_buffer(
-            &render_state.buffers.camera_buffer,
-            0,
-            bytemuck::cast_slice(&[camera2d.get_view()]),
-        );
-    }
+fn prepare_post_process(
+    mut render_state: ResMut<RenderState>,
+    post_process_chain: Option<Res<PostProcessChain>>,
+) {
+    let render_state = &mut *render_state;
+    let chain = post_process_chain.as_deref().cloned().unwrap_or_default();
+    render_state.post_processor.prepare(
+        &render_state.device,
+        &render_state.queue,
+        &render_state.config,
+        &chain,
+    );
+}
+
+fn update_material_uniforms(
+    render_state: Res<RenderState>,
+    mut material_registry: ResMut<MaterialRegistry>,
+) {
+    material_registry.write_pending_uniforms(&render_state.queue);
 }
 
-fn update_time_buffer(render_state: Res<RenderState>, time: Res<Time>) {
+fn upload_shapes(mut shape_registry: ResMut<ShapeRegistry>, render_state: Res<RenderState>) {
+    shape_registry.upload_pending_shapes(&render_state.device);
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn update_globals_buffer(render_state: Res<RenderState>, time: Res<Time>) {
+    let globals = Globals::new(
+        time.total_seconds(),
+        time.delta_seconds(),
+        time.frame_count(),
+        Vec2::new(
+            render_state.config.width as f32,
+            render_state.config.height as f32,
+        ),
+    );
     render_state.queue.write_buffer(
-        &render_state.buffers.time_buffer,
+        &render_state.buffers.globals_buffer,
         0,
-        bytemuck::cast_slice(&[time.total_seconds()]),
+        bytemuck::bytes_of(&globals),
     );
 }
 
 fn main_render_pass(
     render_state: Res<RenderState>,
     shape_registry: Res<ShapeRegistry>,
+    material_registry: Res<MaterialRegistry>,
     renderables: Res<Renderables>,
     mut font_registry: ResMut<FontRegistry>,
     mut text_sections: Query<&mut TextSection>,
     mut render_result: ResMut<RenderResult>,
+    mut frame_recorder: ResMut<FrameRecorder>,
 ) {
-    let output = match render_state.surface.get_current_texture() {
-        Ok(texture) => texture,
+    let (output, output_view) = match render_state.output.acquire() {
+        Ok(frame) => frame,
         Err(err) => {
             *render_result = RenderResult(Err(err));
             return;
         }
     };
-    let view = output
-        .texture
-        .create_view(&wgpu::TextureViewDescriptor::default());
+    *render_result = RenderResult(Ok(()));
+    // Frames being captured are drawn into a texture which can be read back, and then copied to
+    // the output.
+    let view = frame_recorder.capture_view().unwrap_or(&output_view);
 
     let mut encoder = render_state
         .device
         .create_command_encoder(&wgpu::CommandEncoderDescriptor {
             label: Some("Render Encoder"),
         });
+    render_texture_passes(
+        &render_state,
+        &mut encoder,
+        &shape_registry,
+        &material_registry,
+        &renderables,
+    );
+
+    // With post-processing active, the world is drawn into an intermediate target which the
+    // effects then read from, with the last effect writing to the output.
+    let post_processor = &render_state.post_processor;
+    let scene_view = post_processor
+        .scene_view()
+        .filter(|_| post_processor.is_active())
+        .unwrap_or(view);
+    let (attachment_view, resolve_target) = if render_state.sample_count == 1 {
+        (scene_view, None)
+    } else {
+        (&render_state.targets.multisample_buffer, Some(scene_view))
+    };
     {
         let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: Some("Render Pass"),
-            color_attachments: &[if render_state.sample_count == 1 {
-                Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(render_state.clear_color),
-                        store: true,
-                    },
-                })
-            } else {
-                Some(wgpu::RenderPassColorAttachment {
-                    view: &render_state.targets.multisample_buffer,
-                    resolve_target: Some(&view),
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(render_state.clear_color),
-                        store: true,
-                    },
-                })
-            }],
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: attachment_view,
+                resolve_target,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(render_state.clear_color),
+                    store: true,
+                },
+            })],
             depth_stencil_attachment: None,
         });
 
-        render_pass.set_pipeline(&render_state.pipelines.shape_pipeline);
-        render_pass.set_bind_group(0, &render_state.bind_groups.camera_bind_group, &[]);
-
-        if let Some((first_renderable, _)) = renderables.0.first() {
-            let mut s = first_renderable.shape;
-            let mut start: u32 = 0;
-
-            #[allow(clippy::cast_possible_truncation)]
-            let total_len = renderables.0.len() as u32;
+        render_pass.set_bind_group(1, &render_state.bind_groups.light_bind_group, &[]);
 
-            render_pass.set_vertex_buffer(1, render_state.buffers.instance_buffer.slice(..));
-
-            // Loop through the renderables and render all contiguous items of the same shape in one draw call.
-            // Sorting the list by setting [`RenderState::sort_renderables`] will make sure this list is entirely unfragmented
-            // and all visible shape types will have exactly one draw call. This may be disadvantageous in some senarios due to the
-            // CPU requirements of sorting large numbers of renderables.
-            for i in 0..total_len {
-                if renderables.0[i as usize].0.shape == s && i != total_len - 1 {
-                    continue;
-                }
-
-                let end = if i == total_len - 1 { total_len } else { i };
-                render_pass.draw_shape2d_instanced(shape_registry.get_shape(s), start..end);
-                s = renderables.0[i as usize].0.shape;
-                start = i;
+        for (index, render_view) in renderables.views.iter().enumerate() {
+            if render_view.target.is_some() || render_view.main_batches.is_empty() {
+                continue;
             }
+            set_render_view(&mut render_pass, &render_state, index, render_view);
+            draw_batches(
+                &mut render_pass,
+                &render_state,
+                &shape_registry,
+                &material_registry,
+                &renderables.batches[render_view.main_batches.clone()],
+            );
         }
     }
 
+    if post_processor.is_active() {
+        post_processor.encode(&render_state.device, &mut encoder, view);
+    }
+
+    // Debug drawing goes on top of the finished frame, so it isn't changed by any effects.
+    if let (true, Some(pipeline)) = (
+        renderables
+            .views
+            .iter()
+            .any(|render_view| !render_view.overlay_batches.is_empty()),
+        material_registry.overlay_pipeline(),
+    ) {
+        overlay_render_pass(&render_state, &mut encoder, view, pipeline, &renderables);
+    }
+
     for ts in &mut text_sections {
         font_registry.get_font_mut(ts.font_id).queue(&ts.section);
     }
@@ -677,12 +1510,184 @@ fn main_render_pass(
     let buffers = font_registry
         .fonts_mut()
         .iter_mut()
-        .map(|f| f.draw(&render_state.device, &view, &render_state.queue));
+        .map(|f| f.draw(&render_state.device, view, &render_state.queue));
 
     render_state
         .queue
         .submit(std::iter::once(encoder.finish()).chain(buffers));
-    output.present();
+    frame_recorder.finish_frame(
+        &render_state.device,
+        &render_state.queue,
+        &render_state.post_processor,
+        &output_view,
+    );
+    if let Some(output) = output {
+        output.present();
+    }
+}
+
+/// Points the render pass at the viewport and view matrix of the camera at `index`.
+#[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+fn set_render_view<'a>(
+    render_pass: &mut wgpu::RenderPass<'a>,
+    render_state: &'a RenderState,
+    index: usize,
+    render_view: &RenderView,
+) {
+    let PixelRect {
+        x,
+        y,
+        width,
+        height,
+    } = render_view.viewport;
+    render_pass.set_viewport(x as f32, y as f32, width as f32, height as f32, 0.0, 1.0);
+    render_pass.set_scissor_rect(x, y, width, height);
+    render_pass.set_bind_group(
+        0,
+        &render_state.bind_groups.camera_bind_group,
+        &[index as u32 * render_state.buffers.camera_stride],
+    );
+}
+
+/// Draws each batch of the main render pass, switching pipelines as the material changes.
+fn draw_batches<'a>(
+    render_pass: &mut wgpu::RenderPass<'a>,
+    render_state: &'a RenderState,
+    shape_registry: &'a ShapeRegistry,
+    material_registry: &'a MaterialRegistry,
+    batches: &[RenderBatch],
+) {
+    render_pass.set_vertex_buffer(1, render_state.buffers.instance_buffer.slice(..));
+
+    // Each batch is a contiguous run of renderables with the same material and shape, drawn in one draw
+    // call. Sorting the list by setting [`RenderSettings::sort_renderables`] will make sure each layer is
+    // entirely unfragmented and all visible material and shape pairs will have exactly one draw call per
+    // layer. This may be disadvantageous in some senarios due to the CPU requirements of sorting large
+    // numbers of renderables.
+    let mut current_pipeline = None;
+    let mut current_feed = None;
+    for batch in batches {
+        if current_pipeline != Some((batch.material, batch.blend_mode)) {
+            let material = material_registry.get_material(batch.material);
+            render_pass.set_pipeline(material.pipeline(batch.blend_mode));
+            if let Some(uniforms) = &material.uniforms {
+                render_pass.set_bind_group(2, &uniforms.bind_group, &[]);
+            }
+            current_pipeline = Some((batch.material, batch.blend_mode));
+            current_feed = None;
+        }
+        if let Some(feed) = batch.feed.filter(|&feed| current_feed != Some(feed)) {
+            if let Some(texture) = render_state.targets.render_textures.get(&feed) {
+                render_pass.set_bind_group(2, &texture.bind_group, &[]);
+            }
+            current_feed = Some(feed);
+        }
+        match batch.geometry {
+            Geometry::Shape(shape) => {
+                // Instances of removed shapes are skipped.
+                if let Some(shape) = shape_registry.get_shape(shape) {
+                    render_pass.draw_shape2d_instanced(shape, batch.instances.clone());
+                }
+            }
+            Geometry::Mesh {
+                first_vertex,
+                vertex_count,
+            } => {
+                render_pass.set_vertex_buffer(0, render_state.buffers.mesh_buffer.slice(..));
+                render_pass.draw(
+                    first_vertex..first_vertex + vertex_count,
+                    batch.instances.clone(),
+                );
+            }
+        }
+    }
+}
+
+/// Draws each camera with a texture target into its texture, before the window is drawn.
+fn render_texture_passes(
+    render_state: &RenderState,
+    encoder: &mut wgpu::CommandEncoder,
+    shape_registry: &ShapeRegistry,
+    material_registry: &MaterialRegistry,
+    renderables: &Renderables,
+) {
+    for (index, render_view) in renderables.views.iter().enumerate() {
+        let Some(texture) = render_view
+            .target
+            .and_then(|camera| render_state.targets.render_textures.get(&camera))
+        else {
+            continue;
+        };
+        let (view, resolve_target) = match &texture.multisample_view {
+            Some(multisample_view) => (multisample_view, Some(&texture.view)),
+            None => (&texture.view, None),
+        };
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Camera Texture Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(render_state.clear_color),
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+        render_pass.set_bind_group(1, &render_state.bind_groups.light_bind_group, &[]);
+        set_render_view(&mut render_pass, render_state, index, render_view);
+        draw_batches(
+            &mut render_pass,
+            render_state,
+            shape_registry,
+            material_registry,
+            &renderables.batches[render_view.main_batches.clone()],
+        );
+    }
+}
+
+/// Draws the overlay batches directly onto the output.
+fn overlay_render_pass(
+    render_state: &RenderState,
+    encoder: &mut wgpu::CommandEncoder,
+    view: &wgpu::TextureView,
+    pipeline: &wgpu::RenderPipeline,
+    renderables: &Renderables,
+) {
+    let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some("Overlay Render Pass"),
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Load,
+                store: true,
+            },
+        })],
+        depth_stencil_attachment: None,
+    });
+    render_pass.set_pipeline(pipeline);
+    render_pass.set_bind_group(1, &render_state.bind_groups.light_bind_group, &[]);
+    render_pass.set_vertex_buffer(0, render_state.buffers.mesh_buffer.slice(..));
+    render_pass.set_vertex_buffer(1, render_state.buffers.instance_buffer.slice(..));
+    for (index, render_view) in renderables.views.iter().enumerate() {
+        if render_view.overlay_batches.is_empty() {
+            continue;
+        }
+        set_render_view(&mut render_pass, render_state, index, render_view);
+        for batch in &renderables.batches[render_view.overlay_batches.clone()] {
+            if let Geometry::Mesh {
+                first_vertex,
+                vertex_count,
+            } = batch.geometry
+            {
+                render_pass.draw(
+                    first_vertex..first_vertex + vertex_count,
+                    batch.instances.clone(),
+                );
+            }
+        }
+    }
 }
 
 pub(crate) struct RenderResult(Result<(), wgpu::SurfaceError>);
@@ -770,3 +1775,258 @@ impl Default for FpsDisplayBundle {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::world::World;
+    use glam::Vec2;
+
+    use crate::{
+        instance::Layer,
+        material::{BlendMode, Material},
+    };
+
+    use super::{Geometry, RenderBatch, Renderable, Renderables};
+
+    fn renderables(items: &[(i32, u32)]) -> Renderables {
+        let mut renderables = Renderables::with_capacity(items.len());
+        renderables
+            .items
+            .extend(items.iter().map(|&(layer, shape)| Renderable {
+                layer: Layer(layer),
+                material: 0,
+                blend_mode: BlendMode::Alpha,
+                geometry: Geometry::Shape(shape),
+                feed: None,
+                outline_of: None,
+                inst: bytemuck::Zeroable::zeroed(),
+            }));
+        renderables
+    }
+
+    fn order(renderables: &Renderables) -> Vec<(i32, u32)> {
+        renderables
+            .items
+            .iter()
+            .map(|r| match r.geometry {
+                Geometry::Shape(shape) => (r.layer.0, shape),
+                Geometry::Mesh { .. } => unreachable!(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_sort_by_layer_is_stable() {
+        let mut r = renderables(&[(1, 2), (0, 1), (1, 0), (-1, 2), (0, 0)]);
+        r.sort(0, false);
+        assert_eq!(order(&r), vec![(-1, 2), (0, 1), (0, 0), (1, 2), (1, 0)]);
+    }
+
+    #[test]
+    fn test_sort_by_layer_and_shape() {
+        let mut r = renderables(&[(1, 2), (0, 1), (1, 0), (-1, 2), (0, 0)]);
+        r.sort(0, true);
+        assert_eq!(order(&r), vec![(-1, 2), (0, 0), (0, 1), (1, 0), (1, 2)]);
+        // Items collected for an earlier camera are left where they are.
+        let mut r = renderables(&[(1, 2), (0, 1), (1, 0), (-1, 2), (0, 0)]);
+        r.sort(2, true);
+        assert_eq!(order(&r), vec![(1, 2), (0, 1), (-1, 2), (0, 0), (1, 0)]);
+    }
+
+    #[test]
+    fn test_outlines_sort_with_their_shape() {
+        // An outline collected under shape 2, which is then moved after shape 1 when sorting.
+        let mut r = renderables(&[(0, 2), (0, 1)]);
+        let outline = r
+            .push_mesh(Layer(0), bytemuck::Zeroable::zeroed(), |triangles| {
+                triangles.extend_from_slice(&[Vec2::ZERO, Vec2::X, Vec2::Y]);
+            })
+            .unwrap();
+        outline.outline_of = Some((0, BlendMode::Alpha, Geometry::Shape(2), None));
+        r.items.rotate_right(1);
+
+        r.sort(0, true);
+        let geometry: Vec<_> = r.items.iter().map(|r| r.geometry).collect();
+        assert_eq!(
+            geometry,
+            [
+                Geometry::Shape(1),
+                Geometry::Mesh {
+                    first_vertex: 0,
+                    vertex_count: 3
+                },
+                Geometry::Shape(2),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_build_batches() {
+        let mut r = renderables(&[(0, 1), (0, 1), (0, 2), (1, 2), (1, 1)]);
+        assert_eq!(r.build_batches(0..r.items.len()), 0..3);
+        assert_eq!(
+            r.batches,
+            vec![
+                RenderBatch {
+                    material: 0,
+                    blend_mode: BlendMode::Alpha,
+                    geometry: Geometry::Shape(1),
+                    feed: None,
+                    instances: 0..2
+                },
+                RenderBatch {
+                    material: 0,
+                    blend_mode: BlendMode::Alpha,
+                    geometry: Geometry::Shape(2),
+                    feed: None,
+                    instances: 2..4
+                },
+                RenderBatch {
+                    material: 0,
+                    blend_mode: BlendMode::Alpha,
+                    geometry: Geometry::Shape(1),
+                    feed: None,
+                    instances: 4..5
+                },
+            ]
+        );
+
+        r.clear();
+        assert_eq!(r.build_batches(0..0), 0..0);
+        assert!(r.batches.is_empty());
+    }
+
+    #[test]
+    fn test_batches_split_by_material() {
+        let mut r = renderables(&[(0, 1), (0, 1), (0, 1), (0, 1)]);
+        r.items[1].material = 1;
+        r.items[3].material = 1;
+        r.sort(0, true);
+        r.build_batches(0..r.items.len());
+        assert_eq!(
+            r.batches,
+            vec![
+                RenderBatch {
+                    material: 0,
+                    blend_mode: BlendMode::Alpha,
+                    geometry: Geometry::Shape(1),
+                    feed: None,
+                    instances: 0..2
+                },
+                RenderBatch {
+                    material: 1,
+                    blend_mode: BlendMode::Alpha,
+                    geometry: Geometry::Shape(1),
+                    feed: None,
+                    instances: 2..4
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_batches_split_by_blend_mode() {
+        let mut r = renderables(&[(0, 1), (0, 1), (0, 1), (1, 1)]);
+        r.items[0].blend_mode = BlendMode::Additive;
+        r.items[2].blend_mode = BlendMode::Additive;
+        r.sort(0, true);
+        r.build_batches(0..r.items.len());
+        let modes = r
+            .batches
+            .iter()
+            .map(|b| (b.blend_mode, b.instances.clone()));
+        // Sorting never moves items between layers, so the item on layer 1 is drawn last.
+        assert_eq!(
+            modes.collect::<Vec<_>>(),
+            vec![
+                (BlendMode::Alpha, 0..1),
+                (BlendMode::Additive, 1..3),
+                (BlendMode::Alpha, 3..4)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_batches_split_by_feed() {
+        let mut world = World::new();
+        let (first, second) = (world.spawn().id(), world.spawn().id());
+        let mut r = renderables(&[(0, 1), (0, 1), (0, 1), (0, 1)]);
+        for (item, feed) in r.items.iter_mut().zip([second, first, second, first]) {
+            item.material = Material::CAMERA_FEED.0;
+            item.feed = Some(feed);
+        }
+        r.sort(0, true);
+        r.build_batches(0..r.items.len());
+        let feeds = r.batches.iter().map(|b| (b.feed, b.instances.clone()));
+        assert_eq!(
+            feeds.collect::<Vec<_>>(),
+            vec![(Some(first), 0..2), (Some(second), 2..4)]
+        );
+    }
+
+    #[test]
+    fn test_meshes_are_not_batched() {
+        let mut r = renderables(&[(0, 1)]);
+        for _ in 0..2 {
+            r.push_mesh(Layer(0), bytemuck::Zeroable::zeroed(), |triangles| {
+                triangles.extend_from_slice(&[Vec2::ZERO, Vec2::X, Vec2::Y]);
+            });
+        }
+        // Meshes without any triangles are skipped.
+        r.push_mesh(Layer(0), bytemuck::Zeroable::zeroed(), |_| {});
+        r.build_batches(0..r.items.len());
+
+        assert_eq!(r.items.len(), 3);
+        assert_eq!(r.mesh.len(), 6);
+        assert_eq!(
+            r.batches[1..],
+            [
+                RenderBatch {
+                    material: 0,
+                    blend_mode: BlendMode::Alpha,
+                    geometry: Geometry::Mesh {
+                        first_vertex: 0,
+                        vertex_count: 3
+                    },
+                    feed: None,
+                    instances: 1..2
+                },
+                RenderBatch {
+                    material: 0,
+                    blend_mode: BlendMode::Alpha,
+                    geometry: Geometry::Mesh {
+                        first_vertex: 3,
+                        vertex_count: 3
+                    },
+                    feed: None,
+                    instances: 2..3
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_overlay_batches_split() {
+        let mut r = renderables(&[(0, 1), (0, 2)]);
+        let overlay_start = r.items.len();
+        r.push_mesh(Layer(0), bytemuck::Zeroable::zeroed(), |triangles| {
+            triangles.extend_from_slice(&[Vec2::ZERO, Vec2::X, Vec2::Y]);
+        });
+
+        let main = r.build_batches(0..overlay_start);
+        let overlay = r.build_batches(overlay_start..r.items.len());
+        assert_eq!(main, 0..2);
+        assert_eq!(overlay, 2..3);
+        assert_eq!(r.batches[2].instances, 2..3);
+    }
+
+    #[test]
+    fn test_camera_batches_split() {
+        // The same shape seen by two cameras is drawn in a batch for each.
+        let mut r = renderables(&[(0, 1), (0, 1), (0, 1)]);
+        assert_eq!(r.build_batches(0..2), 0..1);
+        assert_eq!(r.build_batches(2..3), 1..2);
+        assert_eq!(r.batches[0].instances, 0..2);
+        assert_eq!(r.batches[1].instances, 2..3);
+    }
+}
@@ -0,0 +1,386 @@
+use std::f32::consts::PI;
+
+use glam::Vec2;
+
+/// How long a miter join may grow, relative to the distance from the path to the edge of the
+/// stroke, before it's replaced with a bevel.
+pub(crate) const MITER_LIMIT: f32 = 4.0;
+/// The largest angle covered by a single triangle of a round join or cap.
+const ROUND_STEP: f32 = PI / 12.0;
+/// Points closer together than this are merged when building a stroke.
+const EPSILON: f32 = 0.0001;
+
+/// How the corners of a stroke are drawn where two of its segments meet.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LineJoin {
+    /// The edges of the segments are extended until they meet in a sharp point.
+    ///
+    /// Very sharp corners fall back to [`LineJoin::Bevel`] so they don't extend too far.
+    #[default]
+    Miter,
+    /// The corner is cut off flat.
+    Bevel,
+    /// The corner is rounded off.
+    Round,
+}
+
+/// How the ends of an open stroke are drawn.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LineCap {
+    /// The stroke ends exactly at its end points.
+    #[default]
+    Butt,
+    /// The stroke is extended past its end points by half its width.
+    Square,
+    /// The ends of the stroke are rounded off.
+    Round,
+}
+
+/// The units the width of a stroke is measured in.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StrokeUnits {
+    /// The width is in world units, growing and shrinking with the camera.
+    #[default]
+    World,
+    /// The width is in pixels, staying the same size on screen however the camera is scaled.
+    Screen,
+}
+
+impl StrokeUnits {
+    /// Converts a width in these units to world units, given the size of a pixel in world units.
+    #[inline(always)]
+    #[must_use]
+    pub(crate) fn to_world(self, width: f32, pixel_size: f32) -> f32 {
+        match self {
+            Self::World => width,
+            Self::Screen => width * pixel_size,
+        }
+    }
+}
+
+/// Describes the shape of a stroke built by [`stroke_path`].
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct StrokeStyle {
+    /// The width of the stroke in world units.
+    pub width: f32,
+    /// How far the center of the stroke is moved from the path, towards its right hand side.
+    ///
+    /// The right hand side of a counter-clockwise loop is its outside.
+    pub offset: f32,
+    pub join: LineJoin,
+    pub cap: LineCap,
+}
+
+/// Where a stroke crosses one of the points of its path.
+///
+/// Segments end at the `incoming` pair of left and right points, and start from the `outgoing`
+/// pair. The two only differ on the outside of a corner, where the gap is filled by the join.
+#[derive(Debug, Clone, Copy)]
+struct Crossing {
+    incoming: [Vec2; 2],
+    outgoing: [Vec2; 2],
+}
+
+/// Tessellates a stroke along the given path, appending its triangles to `triangles`.
+///
+/// Each group of three points is one counter-clockwise triangle. `closed` paths join their last
+/// point back to the first, while open paths have a [`LineCap`] at each end.
+pub(crate) fn stroke_path(
+    points: &[Vec2],
+    closed: bool,
+    style: &StrokeStyle,
+    triangles: &mut Vec<Vec2>,
+) {
+    let mut path = Vec::with_capacity(points.len());
+    for &point in points {
+        if path
+            .last()
+            .map_or(true, |last: &Vec2| last.distance(point) > EPSILON)
+        {
+            path.push(point);
+        }
+    }
+    if closed && path.len() > 1 && path[0].distance(path[path.len() - 1]) <= EPSILON {
+        path.pop();
+    }
+    let closed = closed && path.len() > 2;
+    if path.len() < 2 || style.width <= 0.0 {
+        return;
+    }
+
+    let count = path.len();
+    let segment_count = if closed { count } else { count - 1 };
+    let directions = (0..segment_count)
+        .map(|i| (path[(i + 1) % count] - path[i]).normalize())
+        .collect::<Vec<_>>();
+    let lengths = (0..segment_count)
+        .map(|i| path[(i + 1) % count].distance(path[i]))
+        .collect::<Vec<_>>();
+    let sides = [
+        style.offset - style.width / 2.0,
+        style.offset + style.width / 2.0,
+    ];
+
+    let crossings = (0..count)
+        .map(|i| {
+            if !closed && i == 0 {
+                cap(path[i], directions[0], sides, style, true, triangles)
+            } else if !closed && i == count - 1 {
+                cap(path[i], directions[i - 1], sides, style, false, triangles)
+            } else {
+                let previous = (i + segment_count - 1) % segment_count;
+                join(
+                    path[i],
+                    [directions[previous], directions[i]],
+                    lengths[previous].min(lengths[i]),
+                    sides,
+                    style.join,
+                    triangles,
+                )
+            }
+        })
+        .collect::<Vec<_>>();
+
+    for i in 0..segment_count {
+        let [start_left, start_right] = crossings[i].outgoing;
+        let [end_left, end_right] = crossings[(i + 1) % count].incoming;
+        push_triangle(triangles, start_left, start_right, end_right);
+        push_triangle(triangles, start_left, end_right, end_left);
+    }
+}
+
+/// The normal to the right hand side of a direction.
+#[inline(always)]
+fn right(direction: Vec2) -> Vec2 {
+    Vec2::new(direction.y, -direction.x)
+}
+
+/// Builds the corner where a stroke turns from one direction to another.
+fn join(
+    point: Vec2,
+    [incoming, outgoing]: [Vec2; 2],
+    shortest_segment: f32,
+    sides: [f32; 2],
+    style: LineJoin,
+    triangles: &mut Vec<Vec2>,
+) -> Crossing {
+    let normals = [right(incoming), right(outgoing)];
+    let turn = incoming.perp_dot(outgoing);
+    // Scaled by a side's offset, this reaches the point where the edges of both segments meet.
+    let cos = normals[0].dot(normals[1]);
+    let miter = (normals[0] + normals[1]) / (1.0 + cos).max(EPSILON);
+
+    // The side on the outside of the turn opens up a gap which needs filling, while the edges on
+    // the inside overlap and can meet at the miter point.
+    let outside = sides.map(|offset| offset * turn > 0.0 || (turn.abs() <= EPSILON && cos < 0.0));
+    let mut crossing = Crossing {
+        incoming: sides.map(|offset| point + normals[0] * offset),
+        outgoing: sides.map(|offset| point + normals[1] * offset),
+    };
+    // Joins are filled in from where the inside edges meet, or the path itself if they don't.
+    let mut center = point;
+    for (i, offset) in sides.into_iter().enumerate() {
+        if !outside[i] && turn.abs() > EPSILON && miter.length() * offset.abs() <= shortest_segment
+        {
+            center = point + miter * offset;
+            crossing.incoming[i] = center;
+            crossing.outgoing[i] = center;
+        }
+    }
+
+    for (i, offset) in sides.into_iter().enumerate() {
+        if !outside[i] {
+            continue;
+        }
+        let [before, after] = [crossing.incoming[i], crossing.outgoing[i]];
+        match style {
+            LineJoin::Miter if miter.length() <= MITER_LIMIT => {
+                let tip = point + miter * offset;
+                push_triangle(triangles, center, before, tip);
+                push_triangle(triangles, center, tip, after);
+            }
+            LineJoin::Round => {
+                push_triangle(triangles, center, before, point);
+                arc(point, before, incoming.angle_between(outgoing), triangles);
+                push_triangle(triangles, center, point, after);
+            }
+            LineJoin::Miter | LineJoin::Bevel => push_triangle(triangles, center, before, after),
+        }
+    }
+    crossing
+}
+
+/// Builds the end of an open stroke.
+fn cap(
+    point: Vec2,
+    direction: Vec2,
+    sides: [f32; 2],
+    style: &StrokeStyle,
+    start: bool,
+    triangles: &mut Vec<Vec2>,
+) -> Crossing {
+    let normal = right(direction);
+    // Caps extend backwards from the start of the stroke, and forwards from its end.
+    let outwards = if start { -direction } else { direction };
+    let point = match style.cap {
+        LineCap::Square => point + outwards * style.width / 2.0,
+        LineCap::Butt | LineCap::Round => point,
+    };
+    let ends = sides.map(|offset| point + normal * offset);
+
+    if style.cap == LineCap::Round {
+        let center = point + normal * style.offset;
+        // Sweep from the right hand side, around the end, to the left hand side.
+        let angle = if start { -PI } else { PI };
+        arc(center, ends[1], angle, triangles);
+    }
+
+    Crossing {
+        incoming: ends,
+        outgoing: ends,
+    }
+}
+
+/// Fills a circular arc around `center`, starting at `from` and sweeping through `angle` radians.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn arc(center: Vec2, from: Vec2, angle: f32, triangles: &mut Vec<Vec2>) {
+    let steps = (angle.abs() / ROUND_STEP).ceil().max(1.0) as u32;
+    let start = from - center;
+    let mut previous = from;
+    for step in 1..=steps {
+        #[allow(clippy::cast_precision_loss)]
+        let rotation = Vec2::from_angle(angle * step as f32 / steps as f32);
+        let next = center + rotation.rotate(start);
+        push_triangle(triangles, center, previous, next);
+        previous = next;
+    }
+}
+
+/// Adds a triangle, flipping it if needed so that it's counter-clockwise and won't be culled.
+///
+/// Triangles with no area are skipped.
+fn push_triangle(triangles: &mut Vec<Vec2>, a: Vec2, b: Vec2, c: Vec2) {
+    let area = (b - a).perp_dot(c - a);
+    if area > EPSILON * EPSILON {
+        triangles.extend_from_slice(&[a, b, c]);
+    } else if area < -EPSILON * EPSILON {
+        triangles.extend_from_slice(&[a, c, b]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use glam::Vec2;
+
+    use super::{stroke_path, LineCap, LineJoin, StrokeStyle};
+
+    fn area(triangles: &[Vec2]) -> f32 {
+        triangles
+            .chunks_exact(3)
+            .map(|t| {
+                let area = (t[1] - t[0]).perp_dot(t[2] - t[0]) / 2.0;
+                // Every triangle must face the camera.
+                assert!(area > 0.0);
+                area
+            })
+            .sum()
+    }
+
+    fn stroke(points: &[Vec2], closed: bool, style: StrokeStyle) -> f32 {
+        let mut triangles = Vec::new();
+        stroke_path(points, closed, &style, &mut triangles);
+        area(&triangles)
+    }
+
+    fn style(width: f32, offset: f32, join: LineJoin, cap: LineCap) -> StrokeStyle {
+        StrokeStyle {
+            width,
+            offset,
+            join,
+            cap,
+        }
+    }
+
+    #[test]
+    fn test_line_caps() {
+        let line = [Vec2::ZERO, Vec2::new(10.0, 0.0)];
+        let butt = stroke(
+            &line,
+            false,
+            style(2.0, 0.0, LineJoin::Miter, LineCap::Butt),
+        );
+        assert!((butt - 20.0).abs() < 0.001);
+
+        let square = stroke(
+            &line,
+            false,
+            style(2.0, 0.0, LineJoin::Miter, LineCap::Square),
+        );
+        assert!((square - 24.0).abs() < 0.001);
+
+        // The round caps make up a circle, slightly smaller than a true one.
+        let round = stroke(
+            &line,
+            false,
+            style(2.0, 0.0, LineJoin::Miter, LineCap::Round),
+        );
+        assert!(round < 20.0 + PI && round > 20.0 + PI * 0.98);
+
+        // Degenerate paths produce nothing.
+        assert_eq!(
+            stroke(
+                &[Vec2::ZERO, Vec2::ZERO],
+                false,
+                style(2.0, 0.0, LineJoin::Miter, LineCap::Round)
+            ),
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_outline_joins() {
+        // A counter-clockwise unit square, stroked entirely on its outside.
+        let square = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(1.0, 0.0),
+            Vec2::new(1.0, 1.0),
+            Vec2::new(0.0, 1.0),
+        ];
+        let width = 0.25;
+        let outline = |join| {
+            stroke(
+                &square,
+                true,
+                style(width, width / 2.0, join, LineCap::Butt),
+            )
+        };
+
+        let miter = outline(LineJoin::Miter);
+        assert!((miter - ((1.0 + 2.0 * width).powi(2) - 1.0)).abs() < 0.001);
+
+        let bevel = outline(LineJoin::Bevel);
+        assert!((bevel - (miter - 2.0 * width * width)).abs() < 0.001);
+
+        let round = outline(LineJoin::Round);
+        assert!(round > bevel && round < miter);
+    }
+
+    #[test]
+    fn test_concave_corner_does_not_overlap() {
+        // An L shaped path turning right, then left.
+        let path = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(10.0, 0.0),
+            Vec2::new(10.0, -10.0),
+        ];
+        let area = stroke(
+            &path,
+            false,
+            style(2.0, 0.0, LineJoin::Miter, LineCap::Butt),
+        );
+        // Two 10x2 segments sharing a mitered corner.
+        assert!((area - 40.0).abs() < 0.001);
+    }
+}
@@ -0,0 +1,678 @@
+use std::{
+    collections::HashSet,
+    io::{Error, ErrorKind, Result},
+    path::PathBuf,
+    str::FromStr,
+};
+
+use glam::{Affine2, Vec2, Vec4};
+use roxmltree::Node;
+use svgtypes::{Paint, PaintFallback, PointsParser, SimplePathSegment, SimplifyingPathParser};
+
+use crate::{
+    shape::InitializeShape,
+    triangulation::{triangulate_loops, FillRule},
+    util::AssetSource,
+};
+
+/// How far flattened curves may stray from the real curve by default, in the units of the SVG.
+pub const DEFAULT_TOLERANCE: f32 = 0.1;
+
+/// The most line segments a single curve is flattened into.
+const MAX_CURVE_SEGMENTS: u32 = 256;
+
+/// The distance of the control points of a cubic curve approximating a quarter circle of radius 1.
+const KAPPA: f32 = 0.552_284_8;
+
+/// Elements whose contents are never drawn directly.
+const SKIPPED_ELEMENTS: [&str; 8] = [
+    "defs",
+    "clipPath",
+    "mask",
+    "marker",
+    "pattern",
+    "symbol",
+    "linearGradient",
+    "radialGradient",
+];
+
+/// An initializer struct for importing the filled shapes of an SVG into the
+/// [`crate::shape_registry::ShapeRegistry`].
+///
+/// Every `<path>`, `<polygon>`, `<polyline>`, `<rect>`, `<circle>` and `<ellipse>` with an `id` is
+/// registered as a shape by that name. A `<g>` with an `id` is registered as a single shape made of
+/// everything inside it. Curves are flattened into lines, and fills are triangulated using their
+/// `fill-rule`, which is `nonzero` unless set otherwise. Subpaths which cross each other can't be
+/// triangulated, but may be nested inside each other. Strokes, gradients, text and `<use>` elements aren't supported.
+///
+/// The fill color of each element becomes the color of its vertices, which is multiplied with the
+/// color of each instance, so groups can have several colors. Elements without a fill are left out,
+/// and fills which aren't a plain color are drawn white.
+///
+/// Each shape is centered on the origin and scaled so it fits between -0.5 and 0.5 on both axes
+/// like the built-in shapes, keeping its aspect ratio. The y axis is flipped, so shapes look the
+/// same as in the editor they were drawn in.
+#[derive(Debug, Clone)]
+pub struct InitializeSvg {
+    /// Where to read the SVG from.
+    pub source: AssetSource,
+    /// How far flattened curves may stray from the real curve, in the units of the SVG after
+    /// any transforms are applied.
+    pub tolerance: f32,
+}
+
+impl InitializeSvg {
+    /// Create a new SVG initializer which reads the file at the given path.
+    ///
+    /// The file is read when the initializer runs, so it can be edited without recompiling.
+    #[must_use]
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self {
+            source: AssetSource::Path(path.into()),
+            tolerance: DEFAULT_TOLERANCE,
+        }
+    }
+
+    /// Create a new SVG initializer from the text of an SVG.
+    ///
+    /// ## Example
+    /// ```
+    /// # use libprim::svg::InitializeSvg;
+    /// InitializeSvg::from_text(
+    ///     r#"<svg xmlns="http://www.w3.org/2000/svg"><circle id="Dot" r="10" fill="red"/></svg>"#
+    ///         .to_string(),
+    /// );
+    /// ```
+    #[must_use]
+    pub fn from_text(text: String) -> Self {
+        Self {
+            source: AssetSource::Text(text),
+            tolerance: DEFAULT_TOLERANCE,
+        }
+    }
+
+    /// Sets how far flattened curves may stray from the real curve. Smaller values make smoother
+    /// curves out of more triangles.
+    #[must_use]
+    pub fn with_tolerance(mut self, tolerance: f32) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Reads the SVG and turns its elements into shape initializers.
+    ///
+    /// # Errors
+    /// Returns an [`std::io::Error`] if the file can't be read, or an
+    /// [`std::io::ErrorKind::InvalidData`] if the SVG is malformed or an element can't be
+    /// triangulated.
+    pub fn load(&self) -> Result<Vec<InitializeShape>> {
+        self.source.parse(|text| parse_svg(text, self.tolerance))
+    }
+
+    /// A description of where the SVG comes from, for logging.
+    pub(crate) fn describe(&self) -> String {
+        self.source.describe()
+    }
+}
+
+/// Parses the text of an SVG into shape initializers, as described on [`InitializeSvg`].
+///
+/// # Errors
+/// Returns an [`std::io::ErrorKind::InvalidData`] if the text isn't a valid SVG, or any of its
+/// elements can't be read or triangulated.
+pub fn parse_svg(text: &str, tolerance: f32) -> Result<Vec<InitializeShape>> {
+    let document = roxmltree::Document::parse(text).map_err(|err| invalid(err.to_string()))?;
+    let root = document.root_element();
+    if root.tag_name().name() != "svg" {
+        return Err(invalid(format!(
+            "expected an <svg> element, but found <{}>",
+            root.tag_name().name()
+        )));
+    }
+
+    let mut collector = Collector {
+        tolerance: tolerance.max(f32::EPSILON),
+        shapes: Vec::new(),
+        open: Vec::new(),
+    };
+    let style = Style::default().inherit(root)?;
+    collector.collect_children(root, &style)?;
+
+    let mut names = HashSet::with_capacity(collector.shapes.len());
+    let mut shapes = Vec::with_capacity(collector.shapes.len());
+    for (name, fills) in collector.shapes {
+        if !names.insert(name.clone()) {
+            return Err(invalid(format!("the id {name:?} is used more than once")));
+        }
+        if fills.is_empty() {
+            continue;
+        }
+        let shape = build_shape(name.clone(), &fills)
+            .map_err(|message| invalid(format!("element {name:?}: {message}")))?;
+        shapes.push(shape);
+    }
+    Ok(shapes)
+}
+
+fn invalid(message: String) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
+
+/// The filled area of an element, as loops of points in the coordinates of the document.
+#[derive(Clone)]
+struct Fill {
+    loops: Vec<Vec<Vec2>>,
+    color: Vec4,
+    rule: FillRule,
+}
+
+/// The styles of an element which it passes down to its children.
+#[derive(Clone, Copy)]
+struct Style {
+    transform: Affine2,
+    fill: Option<Vec4>,
+    fill_opacity: f32,
+    fill_rule: FillRule,
+    opacity: f32,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            transform: Affine2::IDENTITY,
+            // Shapes are filled black unless they say otherwise.
+            fill: Some(Vec4::new(0.0, 0.0, 0.0, 1.0)),
+            fill_opacity: 1.0,
+            fill_rule: FillRule::NonZero,
+            opacity: 1.0,
+        }
+    }
+}
+
+impl Style {
+    /// The style of an element, given the style of its parent.
+    fn inherit(&self, node: Node) -> Result<Self> {
+        let id = node.attribute("id").unwrap_or_default();
+        let transform = match node.attribute("transform") {
+            Some(value) => {
+                let t = svgtypes::Transform::from_str(value)
+                    .map_err(|err| invalid(format!("invalid transform on {id:?}: {err}")))?;
+                #[allow(clippy::cast_possible_truncation)]
+                let matrix = [t.a, t.b, t.c, t.d, t.e, t.f].map(|value| value as f32);
+                self.transform * Affine2::from_cols_array(&matrix)
+            }
+            None => self.transform,
+        };
+        let fill = match property(node, "fill") {
+            None | Some("inherit") => self.fill,
+            Some(value) => match Paint::from_str(value) {
+                Ok(Paint::None | Paint::FuncIRI(_, Some(PaintFallback::None))) => None,
+                Ok(Paint::Color(color) | Paint::FuncIRI(_, Some(PaintFallback::Color(color)))) => {
+                    Some(
+                        Vec4::new(
+                            f32::from(color.red),
+                            f32::from(color.green),
+                            f32::from(color.blue),
+                            f32::from(color.alpha),
+                        ) / 255.0,
+                    )
+                }
+                Ok(_) => Some(Vec4::ONE),
+                Err(err) => return Err(invalid(format!("invalid fill on {id:?}: {err}"))),
+            },
+        };
+        let fill_rule = match property(node, "fill-rule") {
+            None | Some("inherit") => self.fill_rule,
+            Some("nonzero") => FillRule::NonZero,
+            Some("evenodd") => FillRule::EvenOdd,
+            Some(value) => return Err(invalid(format!("invalid fill-rule on {id:?}: {value:?}"))),
+        };
+        Ok(Self {
+            transform,
+            fill,
+            fill_opacity: number_property(node, "fill-opacity").unwrap_or(self.fill_opacity),
+            fill_rule,
+            opacity: self.opacity * number_property(node, "opacity").unwrap_or(1.0),
+        })
+    }
+
+    /// The color to fill the element with, if it's filled at all.
+    fn fill_color(&self) -> Option<Vec4> {
+        self.fill.map(|color| {
+            color
+                * Vec4::new(
+                    1.0,
+                    1.0,
+                    1.0,
+                    self.fill_opacity.clamp(0.0, 1.0) * self.opacity,
+                )
+        })
+    }
+}
+
+/// Finds a presentation property of an element, preferring its `style` attribute.
+fn property<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.attribute("style")
+        .and_then(|style| {
+            style.split(';').find_map(|declaration| {
+                let (key, value) = declaration.split_once(':')?;
+                (key.trim() == name).then_some(value.trim())
+            })
+        })
+        .or_else(|| node.attribute(name))
+}
+
+fn number_property(node: Node, name: &str) -> Option<f32> {
+    property(node, name).and_then(|value| value.parse().ok())
+}
+
+/// Reads a length attribute as a number, ignoring its units.
+#[allow(clippy::cast_possible_truncation)]
+fn length(node: Node, name: &str) -> f32 {
+    node.attribute(name)
+        .and_then(|value| svgtypes::Length::from_str(value).ok())
+        .map_or(0.0, |length| length.number as f32)
+}
+
+/// Walks the document, gathering the fills of each shape to register.
+struct Collector {
+    tolerance: f32,
+    /// The name and fills of each shape to register, in document order.
+    shapes: Vec<(String, Vec<Fill>)>,
+    /// The shapes of the groups the walk is currently inside.
+    open: Vec<usize>,
+}
+
+impl Collector {
+    fn collect_children(&mut self, node: Node, style: &Style) -> Result<()> {
+        for child in node.children().filter(Node::is_element) {
+            let name = child.tag_name().name();
+            if SKIPPED_ELEMENTS.contains(&name) || property(child, "display") == Some("none") {
+                continue;
+            }
+            let style = style.inherit(child)?;
+            let id = child.attribute("id").filter(|id| !id.is_empty());
+            match name {
+                "g" | "svg" | "a" | "switch" => {
+                    if let Some(id) = id {
+                        self.open.push(self.shapes.len());
+                        self.shapes.push((id.to_string(), Vec::new()));
+                    }
+                    self.collect_children(child, &style)?;
+                    if id.is_some() {
+                        self.open.pop();
+                    }
+                }
+                _ => {
+                    let Some(segments) = element_segments(child)? else {
+                        continue;
+                    };
+                    let fill = style.fill_color().map(|color| Fill {
+                        loops: flatten(&segments, style.transform, self.tolerance),
+                        color,
+                        rule: style.fill_rule,
+                    });
+                    if let Some(id) = id {
+                        self.shapes
+                            .push((id.to_string(), fill.iter().cloned().collect()));
+                    }
+                    for &open in &self.open {
+                        self.shapes[open].1.extend(fill.iter().cloned());
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A piece of an outline, with absolute coordinates.
+enum Segment {
+    MoveTo(Vec2),
+    LineTo(Vec2),
+    Quadratic(Vec2, Vec2),
+    Cubic(Vec2, Vec2, Vec2),
+    Close,
+}
+
+/// The outline of a drawable element, or `None` if the element isn't one which can be drawn.
+fn element_segments(node: Node) -> Result<Option<Vec<Segment>>> {
+    let point = |x: f64, y: f64| {
+        #[allow(clippy::cast_possible_truncation)]
+        Vec2::new(x as f32, y as f32)
+    };
+    let segments = match node.tag_name().name() {
+        "path" => SimplifyingPathParser::from(node.attribute("d").unwrap_or_default())
+            .map(|segment| {
+                Ok(match segment? {
+                    SimplePathSegment::MoveTo { x, y } => Segment::MoveTo(point(x, y)),
+                    SimplePathSegment::LineTo { x, y } => Segment::LineTo(point(x, y)),
+                    SimplePathSegment::Quadratic { x1, y1, x, y } => {
+                        Segment::Quadratic(point(x1, y1), point(x, y))
+                    }
+                    SimplePathSegment::CurveTo {
+                        x1,
+                        y1,
+                        x2,
+                        y2,
+                        x,
+                        y,
+                    } => Segment::Cubic(point(x1, y1), point(x2, y2), point(x, y)),
+                    SimplePathSegment::ClosePath => Segment::Close,
+                })
+            })
+            .collect::<std::result::Result<Vec<_>, svgtypes::Error>>()
+            .map_err(|err| {
+                let id = node.attribute("id").unwrap_or_default();
+                invalid(format!("invalid path data on {id:?}: {err}"))
+            })?,
+        "polygon" | "polyline" => {
+            let points = PointsParser::from(node.attribute("points").unwrap_or_default())
+                .map(|(x, y)| point(x, y));
+            let mut segments = points
+                .enumerate()
+                .map(|(i, p)| {
+                    if i == 0 {
+                        Segment::MoveTo(p)
+                    } else {
+                        Segment::LineTo(p)
+                    }
+                })
+                .collect::<Vec<_>>();
+            segments.push(Segment::Close);
+            segments
+        }
+        "rect" => {
+            let position = Vec2::new(length(node, "x"), length(node, "y"));
+            let size = Vec2::new(length(node, "width"), length(node, "height"));
+            // A missing corner radius takes the value of the other one.
+            let (rx, ry) = match (node.attribute("rx"), node.attribute("ry")) {
+                (None, None) => (0.0, 0.0),
+                (Some(_), None) => (length(node, "rx"), length(node, "rx")),
+                (None, Some(_)) => (length(node, "ry"), length(node, "ry")),
+                (Some(_), Some(_)) => (length(node, "rx"), length(node, "ry")),
+            };
+            let radius = Vec2::new(rx, ry).clamp(Vec2::ZERO, size / 2.0);
+            rounded_rect_segments(position, size, radius)
+        }
+        "circle" => {
+            let r = length(node, "r");
+            ellipse_segments(
+                Vec2::new(length(node, "cx"), length(node, "cy")),
+                Vec2::splat(r),
+            )
+        }
+        "ellipse" => ellipse_segments(
+            Vec2::new(length(node, "cx"), length(node, "cy")),
+            Vec2::new(length(node, "rx"), length(node, "ry")),
+        ),
+        _ => return Ok(None),
+    };
+    Ok(Some(segments))
+}
+
+fn ellipse_segments(center: Vec2, radius: Vec2) -> Vec<Segment> {
+    let (x, y) = (Vec2::X * radius, Vec2::Y * radius);
+    let mut segments = vec![Segment::MoveTo(center + x)];
+    for (from, to) in [(x, y), (y, -x), (-x, -y), (-y, x)] {
+        segments.push(Segment::Cubic(
+            center + from + to * KAPPA,
+            center + to + from * KAPPA,
+            center + to,
+        ));
+    }
+    segments.push(Segment::Close);
+    segments
+}
+
+fn rounded_rect_segments(position: Vec2, size: Vec2, radius: Vec2) -> Vec<Segment> {
+    let (min, max) = (position, position + size);
+    // Each corner, and the directions of the sides leading into and out of it.
+    let corners = [
+        (Vec2::new(max.x, min.y), Vec2::X, Vec2::Y),
+        (max, Vec2::Y, Vec2::NEG_X),
+        (Vec2::new(min.x, max.y), Vec2::NEG_X, Vec2::NEG_Y),
+        (min, Vec2::NEG_Y, Vec2::X),
+    ];
+    let mut segments = vec![Segment::MoveTo(min + Vec2::X * radius.x)];
+    for (corner, into, out) in corners {
+        let (into, out) = (into * radius, out * radius);
+        let (start, end) = (corner - into, corner + out);
+        segments.push(Segment::LineTo(start));
+        segments.push(Segment::Cubic(start + into * KAPPA, end - out * KAPPA, end));
+    }
+    segments.push(Segment::Close);
+    segments
+}
+
+/// Transforms an outline and flattens its curves into lines, giving a loop of points for each
+/// closed or unclosed part of the outline.
+fn flatten(segments: &[Segment], transform: Affine2, tolerance: f32) -> Vec<Vec<Vec2>> {
+    let mut loops = Vec::new();
+    let mut current = Vec::<Vec2>::new();
+    let finish = |current: &mut Vec<Vec2>, loops: &mut Vec<Vec<Vec2>>| {
+        if current.len() >= 3 {
+            loops.push(std::mem::take(current));
+        }
+        current.clear();
+    };
+    for segment in segments {
+        let last = current.last().copied().unwrap_or_default();
+        let point = |p: &Vec2| transform.transform_point2(*p);
+        match segment {
+            Segment::MoveTo(to) => {
+                finish(&mut current, &mut loops);
+                current.push(point(to));
+            }
+            Segment::LineTo(to) => current.push(point(to)),
+            Segment::Quadratic(control, to) => {
+                let (control, to) = (point(control), point(to));
+                let steps = curve_steps((last - control * 2.0 + to).length() / 4.0, tolerance);
+                current.extend((1..=steps).map(|i| {
+                    let t = fraction(i, steps);
+                    last.lerp(control, t).lerp(control.lerp(to, t), t)
+                }));
+            }
+            Segment::Cubic(first, second, to) => {
+                let (first, second, to) = (point(first), point(second), point(to));
+                let curvature = (last - first * 2.0 + second)
+                    .length()
+                    .max((first - second * 2.0 + to).length());
+                let steps = curve_steps(curvature * 0.75, tolerance);
+                current.extend((1..=steps).map(|i| {
+                    let t = fraction(i, steps);
+                    let (a, b, c) = (
+                        last.lerp(first, t),
+                        first.lerp(second, t),
+                        second.lerp(to, t),
+                    );
+                    a.lerp(b, t).lerp(b.lerp(c, t), t)
+                }));
+            }
+            Segment::Close => finish(&mut current, &mut loops),
+        }
+    }
+    finish(&mut current, &mut loops);
+    loops
+}
+
+/// How many lines to flatten a curve into so it strays no further than the tolerance, given the
+/// furthest the curve strays from a single line.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn curve_steps(deviation: f32, tolerance: f32) -> u32 {
+    ((deviation / tolerance).sqrt().ceil() as u32).clamp(1, MAX_CURVE_SEGMENTS)
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn fraction(i: u32, count: u32) -> f32 {
+    i as f32 / count as f32
+}
+
+/// Centers and scales the fills of a shape into the unit box, and triangulates them.
+fn build_shape(name: String, fills: &[Fill]) -> std::result::Result<InitializeShape, String> {
+    let points = fills.iter().flat_map(|fill| fill.loops.iter().flatten());
+    let (min, max) = points.fold(
+        (Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY)),
+        |(min, max), &point| (min.min(point), max.max(point)),
+    );
+    let extent = (max - min).max_element();
+    if !extent.is_finite() || extent <= 0.0 {
+        return Err("there's nothing to fill".to_string());
+    }
+    let center = (min + max) / 2.0;
+    // SVG's y axis points down, so it's flipped to match the world.
+    let normalize = |point: &Vec2| (*point - center) / extent * Vec2::new(1.0, -1.0);
+
+    let (mut vertices, mut indices, mut colors) = (Vec::new(), Vec::new(), Vec::new());
+    for fill in fills {
+        let loops = fill
+            .loops
+            .iter()
+            .map(|points| points.iter().map(normalize).collect())
+            .collect::<Vec<_>>();
+        let (points, fill_indices) =
+            triangulate_loops(&loops, fill.rule).map_err(|err| err.to_string())?;
+        #[allow(clippy::cast_possible_truncation)]
+        let offset = vertices.len() as u32;
+        colors.extend(std::iter::repeat(fill.color).take(points.len()));
+        vertices.extend(points);
+        indices.extend(fill_indices.into_iter().map(|index| index + offset));
+    }
+    Ok(InitializeShape::new(name, vertices, indices).with_colors(colors))
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{Vec2, Vec4};
+
+    use super::parse_svg;
+    use crate::shape::InitializeShape;
+
+    fn area(shape: &InitializeShape) -> f32 {
+        shape
+            .indices
+            .chunks_exact(3)
+            .map(|t| {
+                let [a, b, c] = [0, 1, 2].map(|i| shape.vertices[t[i] as usize]);
+                (b - a).perp_dot(c - a) / 2.0
+            })
+            .sum()
+    }
+
+    fn bounds(shape: &InitializeShape) -> (Vec2, Vec2) {
+        shape.vertices.iter().fold(
+            (Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY)),
+            |(min, max), &point| (min.min(point), max.max(point)),
+        )
+    }
+
+    #[test]
+    fn test_parse_svg() {
+        let shapes = parse_svg(
+            r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 100 100">
+                <defs><rect id="Hidden" width="10" height="10"/></defs>
+                <rect id="Wide" x="10" y="10" width="40" height="20" fill="#ff0000"/>
+                <circle id="Dot" cx="50" cy="50" r="10" style="fill: blue; fill-opacity: 0.5"/>
+                <path id="Frame" d="M 0 0 H 10 V 10 H 0 Z M 2 2 H 8 V 8 H 2 Z" fill-rule="evenodd"/>
+                <path id="Arrow" d="M 0 0 L 10 0 L 5 -10 Z" fill="white"/>
+                <g id="Flag" transform="translate(100 0)">
+                    <rect width="10" height="5" fill="red"/>
+                    <rect y="5" width="10" height="5" fill="white"/>
+                    <rect id="Guide" width="10" height="10" fill="none"/>
+                </g>
+            </svg>"##,
+            0.01,
+        )
+        .unwrap();
+        let names = shapes.iter().map(|s| s.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, ["Wide", "Dot", "Frame", "Arrow", "Flag"]);
+
+        // Shapes fit the unit box, keeping their aspect ratio.
+        let (min, max) = bounds(&shapes[0]);
+        assert!((min - Vec2::new(-0.5, -0.25)).length() < 0.001);
+        assert!((max - Vec2::new(0.5, 0.25)).length() < 0.001);
+        assert_eq!(
+            shapes[0].colors.as_ref().unwrap()[0],
+            Vec4::new(1.0, 0.0, 0.0, 1.0)
+        );
+
+        let dot = &shapes[1];
+        assert!((area(dot) - std::f32::consts::PI / 4.0).abs() < 0.01);
+        assert_eq!(
+            dot.colors.as_ref().unwrap()[0],
+            Vec4::new(0.0, 0.0, 1.0, 0.5)
+        );
+
+        // The inner square of the frame is a hole under the even-odd rule, and shapes are black by default.
+        let frame = &shapes[2];
+        assert!((area(frame) - 0.64).abs() < 0.001);
+        assert_eq!(
+            frame.colors.as_ref().unwrap()[0],
+            Vec4::new(0.0, 0.0, 0.0, 1.0)
+        );
+
+        // The arrow points up in the SVG, and still points up once flipped into the world.
+        let arrow = &shapes[3];
+        assert!(arrow.vertices.contains(&Vec2::new(0.0, 0.5)));
+        assert!(area(arrow) > 0.0);
+
+        let flag = &shapes[4];
+        assert_eq!(flag.vertices.len(), 8);
+        let colors = flag.colors.as_ref().unwrap();
+        // The red stripe is at the top of the SVG, so it's at the top in the world too.
+        for (vertex, color) in flag.vertices.iter().zip(colors) {
+            if *color == Vec4::new(1.0, 0.0, 0.0, 1.0) {
+                assert!(vertex.y >= 0.0);
+            } else {
+                assert!(vertex.y <= 0.0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_svg_fill_rule() {
+        let shapes = parse_svg(
+            r#"<svg xmlns="http://www.w3.org/2000/svg">
+                <path id="Solid" d="M 0 0 H 10 V 10 H 0 Z M 2 2 H 8 V 8 H 2 Z"/>
+                <g style="fill-rule: evenodd">
+                    <path id="Inherited" d="M 0 0 H 10 V 10 H 0 Z M 2 2 H 8 V 8 H 2 Z"/>
+                </g>
+                <path id="Reversed" d="M 0 0 H 10 V 10 H 0 Z M 2 2 V 8 H 8 V 2 Z"/>
+            </svg>"#,
+            0.01,
+        )
+        .unwrap();
+        // Under the default nonzero rule, subpaths winding the same way don't cut holes.
+        assert!((area(&shapes[0]) - 1.0).abs() < 0.001);
+        assert!((area(&shapes[1]) - 0.64).abs() < 0.001);
+        assert!((area(&shapes[2]) - 0.64).abs() < 0.001);
+
+        let error = parse_svg(
+            r#"<svg><rect id="A" width="1" height="1" fill-rule="odd"/></svg>"#,
+            0.1,
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("fill-rule"));
+    }
+
+    #[test]
+    fn test_svg_errors() {
+        assert!(parse_svg("<svg><path", 0.1).is_err());
+        assert!(parse_svg("<html/>", 0.1)
+            .unwrap_err()
+            .to_string()
+            .contains("<html>"));
+        let error = parse_svg(
+            r#"<svg><path id="Bowtie" d="M 0 0 L 10 10 L 10 0 L 0 5 Z"/></svg>"#,
+            0.1,
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("\"Bowtie\""));
+        let error = parse_svg(
+            r#"<svg><rect id="A" width="1" height="1"/><rect id="A" width="1" height="1"/></svg>"#,
+            0.1,
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("more than once"));
+    }
+}
@@ -23,6 +23,8 @@ pub struct Time {
     previous_instant: instant::Instant,
     /// The number of seconds as a float between the current frame and the previous frame.
     delta_seconds: f32,
+    /// The number of frames started before the current one.
+    frame_count: u32,
 }
 
 impl Time {
@@ -44,6 +46,7 @@ impl Time {
             .current_instant
             .duration_since(self.previous_instant)
             .as_secs_f32();
+        self.frame_count = self.frame_count.wrapping_add(1);
     }
 
     /// Get the amount of seconds between the previos frame and this frame.
@@ -53,6 +56,14 @@ impl Time {
         self.delta_seconds
     }
 
+    /// Get the number of frames started before the current one, which wraps around once it passes
+    /// `u32::MAX`.
+    #[inline(always)]
+    #[must_use]
+    pub fn frame_count(&self) -> u32 {
+        self.frame_count
+    }
+
     /// Get the total amount of seconds that the engine has been running.
     #[must_use]
     pub fn total_seconds(&self) -> f32 {
@@ -70,6 +81,7 @@ impl Default for Time {
             current_instant: now,
             previous_instant: now,
             delta_seconds: Default::default(),
+            frame_count: 0,
         }
     }
 }
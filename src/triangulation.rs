@@ -0,0 +1,510 @@
+use std::io::{Error, ErrorKind, Result};
+
+use glam::Vec2;
+
+/// Points closer together than this are treated as the same point.
+const EPSILON: f32 = 1e-6;
+
+/// How the areas inside several loops are decided to be filled or empty.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FillRule {
+    /// Areas are filled unless the loops around them wind in opposite directions as many times as
+    /// each other. This is the default in SVG.
+    #[default]
+    NonZero,
+    /// Areas inside an odd number of loops are filled.
+    EvenOdd,
+}
+
+/// Triangulates a simple polygon, which may be concave and may have holes, using ear clipping.
+///
+/// The outline and holes can be given in either winding order. The returned indices index into the
+/// returned points and describe counter-clockwise triangles. Repeated points are removed from each
+/// loop first, and points in the middle of straight edges may be left out of the triangles.
+///
+/// Fails and returns an [`ErrorKind::InvalidInput`] if a loop has fewer than three distinct
+/// points or no area, if any edges cross or touch each other, or if a hole isn't inside the
+/// outline or is inside another hole.
+pub(crate) fn triangulate(outline: &[Vec2], holes: &[Vec<Vec2>]) -> Result<(Vec<Vec2>, Vec<u32>)> {
+    let mut loops = Vec::with_capacity(holes.len() + 1);
+    loops.push(clean_loop(outline, "outline")?);
+    for (i, hole) in holes.iter().enumerate() {
+        loops.push(clean_loop(hole, &format!("hole {i}"))?);
+    }
+
+    // The outline runs counter-clockwise and holes clockwise, so the filled area is always on
+    // the left of each edge.
+    for (i, points) in loops.iter_mut().enumerate() {
+        if (signed_area(points) > 0.0) != (i == 0) {
+            points.reverse();
+        }
+    }
+
+    check_edges(&loops)?;
+    for (i, hole) in loops.iter().enumerate().skip(1) {
+        if !contains(&loops[0], hole[0]) {
+            return Err(invalid(format!("hole {} is outside the outline", i - 1)));
+        }
+        if let Some(other) = (1..loops.len()).find(|&j| j != i && contains(&loops[j], hole[0])) {
+            return Err(invalid(format!(
+                "hole {} is inside hole {}",
+                i - 1,
+                other - 1
+            )));
+        }
+    }
+
+    let points = loops.concat();
+    let polygon = bridge_holes(&points, &loops)?;
+    let indices = clip_ears(&points, polygon)?;
+    Ok((points, indices))
+}
+
+/// Triangulates any number of loops, filled using the given rule.
+///
+/// The area inside each loop and outside the loops directly inside it is either filled or left
+/// empty as a whole. Filled areas are triangulated separately, with the loops directly inside them
+/// as holes. The points of every polygon are returned together, and the indices index into all of
+/// them.
+///
+/// Fails for the same reasons as [`triangulate`], so loops which cross each other can't be used.
+pub(crate) fn triangulate_loops(
+    loops: &[Vec<Vec2>],
+    fill_rule: FillRule,
+) -> Result<(Vec<Vec2>, Vec<u32>)> {
+    let signed_areas = loops
+        .iter()
+        .map(|points| signed_area(points))
+        .collect::<Vec<_>>();
+    let areas = signed_areas
+        .iter()
+        .map(|area| area.abs())
+        .collect::<Vec<_>>();
+    // The loops around each loop, smallest first.
+    let parents = loops
+        .iter()
+        .enumerate()
+        .map(|(i, points)| {
+            let mut parents = (0..loops.len())
+                .filter(|&j| j != i && points.first().is_some_and(|&p| contains(&loops[j], p)))
+                .collect::<Vec<_>>();
+            parents.sort_by(|&a, &b| areas[a].total_cmp(&areas[b]));
+            parents
+        })
+        .collect::<Vec<_>>();
+
+    let filled = |i: usize| match fill_rule {
+        FillRule::NonZero => {
+            // The winding number of the area just inside the loop, counting counter-clockwise
+            // loops as +1 and clockwise loops as -1.
+            let winding = parents[i]
+                .iter()
+                .chain(std::iter::once(&i))
+                .map(|&j| if signed_areas[j] > 0.0 { 1 } else { -1 })
+                .sum::<i32>();
+            winding != 0
+        }
+        FillRule::EvenOdd => parents[i].len() % 2 == 0,
+    };
+
+    let (mut points, mut indices) = (Vec::new(), Vec::new());
+    for (i, outline) in loops.iter().enumerate() {
+        if !filled(i) {
+            continue;
+        }
+        let holes = loops
+            .iter()
+            .enumerate()
+            .filter(|&(j, _)| parents[j].first() == Some(&i))
+            .map(|(_, hole)| hole.clone())
+            .collect::<Vec<_>>();
+        let (polygon_points, polygon_indices) = triangulate(outline, &holes)?;
+        let offset = to_u32(points.len());
+        points.extend(polygon_points);
+        indices.extend(polygon_indices.into_iter().map(|index| index + offset));
+    }
+    Ok((points, indices))
+}
+
+fn invalid(message: String) -> Error {
+    Error::new(ErrorKind::InvalidInput, message)
+}
+
+/// Removes repeated points from a loop, including a last point which repeats the first, and checks
+/// the loop is usable.
+fn clean_loop(points: &[Vec2], name: &str) -> Result<Vec<Vec2>> {
+    if points.iter().any(|point| !point.is_finite()) {
+        return Err(invalid(format!("{name} has a point which isn't finite")));
+    }
+    let mut cleaned = Vec::<Vec2>::with_capacity(points.len());
+    for &point in points {
+        if cleaned
+            .last()
+            .map_or(true, |last| last.distance(point) > EPSILON)
+        {
+            cleaned.push(point);
+        }
+    }
+    while cleaned.len() > 1 && cleaned[0].distance(cleaned[cleaned.len() - 1]) <= EPSILON {
+        cleaned.pop();
+    }
+    if cleaned.len() < 3 {
+        return Err(invalid(format!(
+            "{name} needs at least 3 distinct points, but has {}",
+            cleaned.len()
+        )));
+    }
+    if signed_area(&cleaned).abs() <= EPSILON {
+        return Err(invalid(format!("{name} has no area")));
+    }
+    Ok(cleaned)
+}
+
+/// The area of a loop, which is positive when it runs counter-clockwise.
+fn signed_area(points: &[Vec2]) -> f32 {
+    points
+        .iter()
+        .zip(points.iter().cycle().skip(1))
+        .map(|(a, b)| a.perp_dot(*b))
+        .sum::<f32>()
+        / 2.0
+}
+
+/// The edges of a loop, as pairs of points.
+fn edges(points: &[Vec2]) -> impl Iterator<Item = (Vec2, Vec2)> + '_ {
+    points
+        .iter()
+        .copied()
+        .zip(points.iter().copied().cycle().skip(1))
+}
+
+/// Checks that no two edges of any of the loops cross or touch, other than neighbouring edges of
+/// the same loop meeting at their shared point.
+fn check_edges(loops: &[Vec<Vec2>]) -> Result<()> {
+    for (i, first) in loops.iter().enumerate() {
+        for (a, edge_a) in edges(first).enumerate() {
+            for (j, second) in loops.iter().enumerate().skip(i) {
+                for (b, edge_b) in edges(second).enumerate() {
+                    if i == j {
+                        let len = first.len();
+                        if b <= a || b == a + 1 || (a == 0 && b == len - 1) {
+                            continue;
+                        }
+                    }
+                    if segments_touch(edge_a, edge_b) {
+                        return Err(invalid(format!(
+                            "edges intersect near ({}, {})",
+                            edge_a.0.x, edge_a.0.y
+                        )));
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Which side of the line through `a` and `b` the point `p` is on, or 0 if it's on the line.
+fn orientation(a: Vec2, b: Vec2, p: Vec2) -> i8 {
+    let cross = (b - a).perp_dot(p - a);
+    if cross > EPSILON {
+        1
+    } else if cross < -EPSILON {
+        -1
+    } else {
+        0
+    }
+}
+
+/// Whether `p`, which is on the line through `a` and `b`, lies within the segment between them.
+fn on_segment(a: Vec2, b: Vec2, p: Vec2) -> bool {
+    p.cmpge(a.min(b) - EPSILON).all() && p.cmple(a.max(b) + EPSILON).all()
+}
+
+/// Whether two segments cross or touch at any point.
+fn segments_touch((a, b): (Vec2, Vec2), (c, d): (Vec2, Vec2)) -> bool {
+    let (o1, o2) = (orientation(a, b, c), orientation(a, b, d));
+    let (o3, o4) = (orientation(c, d, a), orientation(c, d, b));
+    (o1 * o2 < 0 && o3 * o4 < 0)
+        || (o1 == 0 && on_segment(a, b, c))
+        || (o2 == 0 && on_segment(a, b, d))
+        || (o3 == 0 && on_segment(c, d, a))
+        || (o4 == 0 && on_segment(c, d, b))
+}
+
+/// Whether a point is inside a loop, using the even-odd rule.
+fn contains(points: &[Vec2], point: Vec2) -> bool {
+    let mut inside = false;
+    for (a, b) in edges(points) {
+        if (a.y > point.y) != (b.y > point.y) {
+            let x = a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x);
+            if point.x < x {
+                inside = !inside;
+            }
+        }
+    }
+    inside
+}
+
+/// Joins each hole to the outline with a pair of edges, giving a single loop of indices into
+/// `points` which visits the bridging points twice.
+///
+/// Holes are joined starting from the one furthest right, each from its rightmost point to the
+/// closest point of the loop so far which can be reached without crossing any edge.
+fn bridge_holes(points: &[Vec2], loops: &[Vec<Vec2>]) -> Result<Vec<u32>> {
+    let mut starts = Vec::with_capacity(loops.len());
+    let mut start = 0;
+    for points in loops {
+        starts.push(start);
+        start += points.len();
+    }
+    let ring = |i: usize| (starts[i]..starts[i] + loops[i].len()).collect::<Vec<_>>();
+    let rightmost = |i: usize| {
+        ring(i)
+            .into_iter()
+            .max_by(|&a, &b| points[a].x.total_cmp(&points[b].x))
+            .unwrap_or(starts[i])
+    };
+
+    let mut holes = (1..loops.len()).collect::<Vec<_>>();
+    holes.sort_by(|&a, &b| points[rightmost(b)].x.total_cmp(&points[rightmost(a)].x));
+
+    let mut polygon = ring(0);
+    for (n, &hole) in holes.iter().enumerate() {
+        let hole_point = rightmost(hole);
+        // Edges which a bridge mustn't cross: the loop so far and the holes still to be joined.
+        let mut blockers = edge_indices(&polygon);
+        for &other in &holes[n..] {
+            blockers.extend(edge_indices(&ring(other)));
+        }
+        let mut candidates = (0..polygon.len()).collect::<Vec<_>>();
+        candidates.sort_by(|&a, &b| {
+            let distance = |i: usize| points[polygon[i]].distance_squared(points[hole_point]);
+            distance(a).total_cmp(&distance(b))
+        });
+        let Some(bridge) = candidates.into_iter().find(|&i| {
+            let (a, b) = (points[polygon[i]], points[hole_point]);
+            blockers.iter().all(|&(c, d)| {
+                [c, d]
+                    .iter()
+                    .any(|&end| points[end] == a || points[end] == b)
+                    || !segments_touch((a, b), (points[c], points[d]))
+            }) && is_inside(points, &polygon, loops, a.lerp(b, 0.5))
+        }) else {
+            return Err(invalid(format!(
+                "hole {} couldn't be joined to the outline",
+                hole - 1
+            )));
+        };
+
+        // Walk the hole starting and ending at its bridging point.
+        let hole_ring = ring(hole);
+        let offset = hole_point - starts[hole];
+        let mut spliced = Vec::with_capacity(hole_ring.len() + 2);
+        spliced.extend(hole_ring[offset..].iter().chain(&hole_ring[..=offset]));
+        spliced.push(polygon[bridge]);
+        let at = bridge + 1;
+        polygon.splice(at..at, spliced);
+    }
+    Ok(polygon.into_iter().map(to_u32).collect())
+}
+
+/// Whether a point is inside the outline and outside every hole.
+fn is_inside(points: &[Vec2], polygon: &[usize], loops: &[Vec<Vec2>], point: Vec2) -> bool {
+    let outline = polygon.iter().map(|&i| points[i]).collect::<Vec<_>>();
+    contains(&outline, point) && loops[1..].iter().all(|hole| !contains(hole, point))
+}
+
+/// The edges of a loop of indices.
+fn edge_indices(ring: &[usize]) -> Vec<(usize, usize)> {
+    ring.iter()
+        .copied()
+        .zip(ring.iter().copied().cycle().skip(1))
+        .collect()
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn to_u32(index: usize) -> u32 {
+    index as u32
+}
+
+/// Cuts counter-clockwise triangles off a loop of indices until none are left.
+fn clip_ears(points: &[Vec2], mut polygon: Vec<u32>) -> Result<Vec<u32>> {
+    let mut indices = Vec::with_capacity(polygon.len().saturating_sub(2) * 3);
+    let mut i = 0;
+    let mut since_last_ear = 0;
+    while polygon.len() > 3 {
+        if since_last_ear > polygon.len() {
+            return Err(invalid("the polygon couldn't be triangulated".to_string()));
+        }
+        let len = polygon.len();
+        let (prev, current, next) = (
+            polygon[(i + len - 1) % len],
+            polygon[i % len],
+            polygon[(i + 1) % len],
+        );
+        let (a, b, c) = (
+            points[prev as usize],
+            points[current as usize],
+            points[next as usize],
+        );
+        match orientation(a, b, c) {
+            // A point in the middle of a straight edge, or a spike back along the same line.
+            0 => {}
+            1 if is_ear(points, &polygon, (a, b, c)) => indices.extend([prev, current, next]),
+            _ => {
+                i = (i + 1) % len;
+                since_last_ear += 1;
+                continue;
+            }
+        }
+        polygon.remove(i % len);
+        i %= polygon.len();
+        since_last_ear = 0;
+    }
+    let [a, b, c] = [0, 1, 2].map(|i| points[polygon[i] as usize]);
+    if orientation(a, b, c) > 0 {
+        indices.extend(&polygon);
+    }
+    Ok(indices)
+}
+
+/// Whether no other point of the loop is inside or on the edge of the triangle.
+fn is_ear(points: &[Vec2], polygon: &[u32], (a, b, c): (Vec2, Vec2, Vec2)) -> bool {
+    polygon.iter().all(|&i| {
+        let p = points[i as usize];
+        p == a
+            || p == b
+            || p == c
+            || orientation(a, b, p) < 0
+            || orientation(b, c, p) < 0
+            || orientation(c, a, p) < 0
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec2;
+
+    use super::{triangulate, triangulate_loops, FillRule};
+
+    /// The total area of the triangles, and whether they all run counter-clockwise.
+    fn area(points: &[Vec2], indices: &[u32]) -> (f32, bool) {
+        let areas = indices
+            .chunks_exact(3)
+            .map(|t| {
+                let [a, b, c] = [0, 1, 2].map(|i| points[t[i] as usize]);
+                (b - a).perp_dot(c - a) / 2.0
+            })
+            .collect::<Vec<_>>();
+        (areas.iter().sum(), areas.iter().all(|&area| area > 0.0))
+    }
+
+    fn square(size: f32, center: Vec2) -> Vec<Vec2> {
+        [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+            .map(|(x, y)| center + Vec2::new(x, y) * size / 2.0)
+            .to_vec()
+    }
+
+    #[test]
+    fn test_concave() {
+        // An L shape, given clockwise.
+        let outline = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(0.0, 2.0),
+            Vec2::new(1.0, 2.0),
+            Vec2::new(1.0, 1.0),
+            Vec2::new(2.0, 1.0),
+            Vec2::new(2.0, 0.0),
+        ];
+        let (points, indices) = triangulate(&outline, &[]).unwrap();
+        assert_eq!(indices.len(), 4 * 3);
+        let (area, counter_clockwise) = area(&points, &indices);
+        assert!((area - 3.0).abs() < 0.001);
+        assert!(counter_clockwise);
+    }
+
+    #[test]
+    fn test_holes() {
+        let holes = [
+            square(1.0, Vec2::new(-1.0, 0.0)),
+            square(1.0, Vec2::new(1.0, 0.5)),
+        ];
+        let (points, indices) = triangulate(&square(4.0, Vec2::ZERO), &holes).unwrap();
+        assert_eq!(points.len(), 12);
+        let (area, counter_clockwise) = area(&points, &indices);
+        assert!((area - 14.0).abs() < 0.001);
+        assert!(counter_clockwise);
+    }
+
+    #[test]
+    fn test_repeated_and_straight_points() {
+        let outline = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(1.0, 0.0),
+            Vec2::new(1.0, 0.0),
+            Vec2::new(2.0, 0.0),
+            Vec2::new(2.0, 2.0),
+            Vec2::new(0.0, 2.0),
+            Vec2::new(0.0, 0.0),
+        ];
+        let (points, indices) = triangulate(&outline, &[]).unwrap();
+        assert_eq!(points.len(), 5);
+        assert!((area(&points, &indices).0 - 4.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_loops() {
+        // Two squares with holes, one of which has an island inside its hole.
+        let loops = [
+            square(4.0, Vec2::ZERO),
+            square(2.0, Vec2::ZERO),
+            square(1.0, Vec2::ZERO),
+            square(1.0, Vec2::new(5.0, 0.0)),
+            square(0.5, Vec2::new(5.0, 0.0)),
+        ];
+        let (points, indices) = triangulate_loops(&loops, FillRule::EvenOdd).unwrap();
+        assert_eq!(points.len(), 20);
+        let (area, counter_clockwise) = area(&points, &indices);
+        assert!((area - (12.0 + 1.0 + 0.75)).abs() < 0.001);
+        assert!(counter_clockwise);
+    }
+
+    #[test]
+    fn test_nonzero_loops() {
+        // Loops winding the same way add up, so the inner square is filled too.
+        let same = [square(4.0, Vec2::ZERO), square(2.0, Vec2::ZERO)];
+        let (points, indices) = triangulate_loops(&same, FillRule::NonZero).unwrap();
+        let (filled, counter_clockwise) = area(&points, &indices);
+        assert!((filled - 16.0).abs() < 0.001);
+        assert!(counter_clockwise);
+        let (points, indices) = triangulate_loops(&same, FillRule::EvenOdd).unwrap();
+        assert!((area(&points, &indices).0 - 12.0).abs() < 0.001);
+
+        // A loop winding the other way cancels out, leaving a hole.
+        let mut reversed = square(2.0, Vec2::ZERO);
+        reversed.reverse();
+        let opposite = [square(4.0, Vec2::ZERO), reversed];
+        let (points, indices) = triangulate_loops(&opposite, FillRule::NonZero).unwrap();
+        assert!((area(&points, &indices).0 - 12.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_invalid() {
+        let bowtie = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(1.0, 1.0),
+            Vec2::new(1.0, 0.0),
+            Vec2::new(0.0, 1.0),
+        ];
+        assert!(triangulate(&bowtie, &[]).is_err());
+        assert!(triangulate(&bowtie[..2], &[]).is_err());
+        let line = [Vec2::ZERO, Vec2::X, Vec2::X * 2.0];
+        assert!(triangulate(&line, &[]).is_err());
+        let outline = square(2.0, Vec2::ZERO);
+        assert!(triangulate(&outline, &[square(1.0, Vec2::new(5.0, 0.0))]).is_err());
+        assert!(triangulate(&outline, &[square(2.0, Vec2::new(1.0, 0.0))]).is_err());
+        let nested = [square(1.0, Vec2::ZERO), square(0.5, Vec2::ZERO)];
+        assert!(triangulate(&outline, &nested).is_err());
+    }
+}
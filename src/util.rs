@@ -1,6 +1,9 @@
 use std::{
+    borrow::Cow,
     collections::{HashMap, HashSet},
     hash::BuildHasherDefault,
+    io::{Error, Result},
+    path::PathBuf,
 };
 
 use hashers::fx_hash::FxHasher;
@@ -10,3 +13,48 @@ pub(crate) type FxHashMap<K, V> = HashMap<K, V, BuildHasherDefault<FxHasher>>;
 
 /// A [`HashSet`] using the [`FxHasher`] for faster but non-cryptographically safe hashing.
 pub(crate) type FxHashSet<T> = HashSet<T, BuildHasherDefault<FxHasher>>;
+
+/// Where the text of an asset file, such as a shape file or an SVG, comes from.
+#[derive(Debug, Clone)]
+pub enum AssetSource {
+    /// A file read from disk when the initializer runs.
+    Path(PathBuf),
+    /// The text of the file, such as one included with `include_str!`.
+    Text(String),
+}
+
+impl AssetSource {
+    /// Reads the text of the asset, reading it from disk if it's a file.
+    ///
+    /// # Errors
+    /// Returns an [`std::io::Error`] starting with the path if the file can't be read.
+    pub(crate) fn read_to_string(&self) -> Result<Cow<'_, str>> {
+        match self {
+            Self::Path(path) => std::fs::read_to_string(path)
+                .map(Cow::Owned)
+                .map_err(|err| Error::new(err.kind(), format!("{}: {err}", path.display()))),
+            Self::Text(text) => Ok(Cow::Borrowed(text)),
+        }
+    }
+
+    /// Reads the text of the asset and parses it, starting any error with the path of the file.
+    ///
+    /// # Errors
+    /// Returns an [`std::io::Error`] if the file can't be read, or the error from `parse`.
+    pub(crate) fn parse<T>(&self, parse: impl FnOnce(&str) -> Result<T>) -> Result<T> {
+        let text = self.read_to_string()?;
+        match self {
+            Self::Path(path) => parse(&text)
+                .map_err(|err| Error::new(err.kind(), format!("{}: {err}", path.display()))),
+            Self::Text(_) => parse(&text),
+        }
+    }
+
+    /// A description of where the asset comes from, for logging.
+    pub(crate) fn describe(&self) -> String {
+        match self {
+            Self::Path(path) => path.display().to_string(),
+            Self::Text(_) => "from text".to_string(),
+        }
+    }
+}
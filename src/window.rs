@@ -36,6 +36,9 @@ pub struct PrimWindowOptions {
     pub window_decorations: bool,
 
     /// Whether to sync the frame rate with the displays refresh rate.
+    ///
+    /// Can be changed while running through the [`libprim::render_settings::RenderSettings`]
+    /// resource, along with the `clear_color` and `sample_count`.
     pub vsync: bool,
 
     /// The default background color when nothing else is drawn.
@@ -43,7 +46,7 @@ pub struct PrimWindowOptions {
 
     /// Anti-aliasing sample count. Currently supports either `1` or `4`.
     ///
-    /// Not supported on all hardware.
+    /// Not supported on all hardware, which falls back to `1`.
     pub sample_count: u32,
 }
 